@@ -0,0 +1,274 @@
+//! Armas: pistola (hitscan con el DDA de `lanzar_rayo` + círculos de los enemigos)
+//! y lanzador (proyectiles que viajan y chocan con el `Mapa`).
+//! La munición se recoge de las cajas 'B'. El arma se dibuja en el framebuffer
+//! y su fogonazo ilumina la escena (`destello`).
+
+use crate::enemigos::{Enemigos, RADIO};
+use crate::motor::*;
+use crate::sprites::{Sprite, SpriteKind};
+use raylib::prelude::*;
+
+const DANIO_BALA: i32 = 35;
+const DANIO_COHETE: i32 = 80;
+const DANIO_EXPLOSION: i32 = 40;  // a los que estén cerca del impacto
+const RADIO_EXPLOSION: f32 = 1.2;
+const VEL_COHETE: f32 = 6.0 / 60.0;
+const ALCANCE_COHETE: u32 = 180;   // frames antes de apagarse
+const ESPERA_PISTOLA: u32 = 15;
+const ESPERA_LANZADOR: u32 = 45;
+const FRAMES_DESTELLO: u32 = 6;
+
+// munición al entrar al nivel y por cada caja 'B'
+const BALAS_INICIO: u32 = 12;
+const COHETES_INICIO: u32 = 2;
+const BALAS_CAJA: u32 = 8;
+const COHETES_CAJA: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TipoArma {
+    Pistola,
+    Lanzador,
+}
+
+#[derive(Clone, Copy)]
+pub struct Proyectil {
+    pub x: f32,
+    pub y: f32,
+    pub dx: f32,
+    pub dy: f32,
+    pub vida: u32, // frames restantes
+}
+
+pub struct Armas {
+    pub actual: TipoArma,
+    pub balas: u32,
+    pub cohetes: u32,
+    pub proyectiles: Vec<Proyectil>,
+    espera: u32,     // frames hasta poder volver a disparar
+    destello: u32,   // frames de luz (fogonazo o explosión) restantes
+    fogonazo: u32,   // frames de fogonazo en la boca del arma
+    balanceo: f32,   // fase del balanceo al caminar
+}
+
+impl Armas {
+    pub fn nuevo() -> Self {
+        Self {
+            actual: TipoArma::Pistola,
+            balas: BALAS_INICIO,
+            cohetes: COHETES_INICIO,
+            proyectiles: Vec::new(),
+            espera: 0,
+            destello: 0,
+            fogonazo: 0,
+            balanceo: 0.0,
+        }
+    }
+
+    pub fn municion(&self) -> u32 {
+        match self.actual {
+            TipoArma::Pistola => self.balas,
+            TipoArma::Lanzador => self.cohetes,
+        }
+    }
+
+    /// Suma la munición de `cajas` cajas 'B'
+    pub fn recoger_municion(&mut self, cajas: usize) {
+        self.balas += BALAS_CAJA * cajas as u32;
+        self.cohetes += COHETES_CAJA * cajas as u32;
+    }
+
+    /// Intensidad del fogonazo para la iluminación (0..1)
+    pub fn destello(&self) -> f32 {
+        self.destello as f32 / FRAMES_DESTELLO as f32
+    }
+
+    /// Dispara el arma actual si hay munición y no está en espera.
+    /// Devuelve el punto de impacto del hitscan (pared o enemigo), si hubo.
+    pub fn disparar(&mut self, j: &Jugador, mapa: &Mapa, enemigos: &mut Enemigos) -> Option<(f32, f32)> {
+        if self.espera > 0 || self.municion() == 0 { return None; }
+        self.destello = FRAMES_DESTELLO;
+        self.fogonazo = FRAMES_DESTELLO;
+        match self.actual {
+            TipoArma::Pistola => {
+                self.balas -= 1;
+                self.espera = ESPERA_PISTOLA;
+                match hitscan(mapa, j.x, j.y, j.ang, enemigos) {
+                    Impacto::Enemigo(i, x, y) => {
+                        enemigos.lista[i].herir(DANIO_BALA, (j.x, j.y));
+                        Some((x, y))
+                    }
+                    Impacto::Pared(x, y) => Some((x, y)),
+                    Impacto::Nada => None,
+                }
+            }
+            TipoArma::Lanzador => {
+                self.cohetes -= 1;
+                self.espera = ESPERA_LANZADOR;
+                self.proyectiles.push(Proyectil {
+                    x: j.x + j.ang.cos() * 0.3,
+                    y: j.y + j.ang.sin() * 0.3,
+                    dx: j.ang.cos() * VEL_COHETE,
+                    dy: j.ang.sin() * VEL_COHETE,
+                    vida: ALCANCE_COHETE,
+                });
+                None
+            }
+        }
+    }
+
+    /// Avanza proyectiles y temporizadores. Devuelve los puntos de explosión.
+    pub fn actualizar(&mut self, mapa: &Mapa, enemigos: &mut Enemigos, caminando: bool) -> Vec<(f32, f32)> {
+        self.espera = self.espera.saturating_sub(1);
+        self.destello = self.destello.saturating_sub(1);
+        self.fogonazo = self.fogonazo.saturating_sub(1);
+        if caminando { self.balanceo += 0.2; }
+
+        let mut explosiones = Vec::new();
+        self.proyectiles.retain_mut(|p| {
+            p.x += p.dx;
+            p.y += p.dy;
+            p.vida = p.vida.saturating_sub(1);
+            let choca_pared = !matches!(celda(mapa, p.x.floor() as i32, p.y.floor() as i32), Some(c) if es_caminable(c));
+            let choca_enemigo = enemigos.lista.iter()
+                .position(|e| (e.x - p.x).powi(2) + (e.y - p.y).powi(2) <= (RADIO + 0.1).powi(2));
+            if let Some(i) = choca_enemigo {
+                enemigos.lista[i].herir(DANIO_COHETE - DANIO_EXPLOSION, (p.x, p.y));
+            }
+            if choca_pared || choca_enemigo.is_some() {
+                // retroceder un paso para que la explosión no quede dentro de la pared
+                explosiones.push((p.x - p.dx, p.y - p.dy));
+                return false;
+            }
+            p.vida > 0
+        });
+
+        for &(ex, ey) in &explosiones {
+            for e in enemigos.lista.iter_mut() {
+                if (e.x - ex).powi(2) + (e.y - ey).powi(2) <= RADIO_EXPLOSION * RADIO_EXPLOSION {
+                    e.herir(DANIO_EXPLOSION, (ex, ey));
+                }
+            }
+            self.destello = FRAMES_DESTELLO;
+        }
+        explosiones
+    }
+
+    /// Arma, munición y temporizadores en una línea (los proyectiles se guardan aparte):
+    /// `arma balas cohetes espera destello fogonazo balanceo`
+    pub fn a_texto(&self) -> String {
+        format!("{} {} {} {} {} {} {}", self.actual as u8, self.balas, self.cohetes,
+                self.espera, self.destello, self.fogonazo, self.balanceo)
+    }
+
+    /// Inverso de `a_texto` (sin proyectiles)
+    pub fn desde_texto(txt: &str) -> Option<Self> {
+        let c: Vec<&str> = txt.split_whitespace().collect();
+        if c.len() != 7 { return None; }
+        let n = |i: usize| c[i].parse::<u32>().ok();
+        let actual = match c[0] {
+            "0" => TipoArma::Pistola,
+            "1" => TipoArma::Lanzador,
+            _ => return None,
+        };
+        Some(Self {
+            actual,
+            balas: n(1)?,
+            cohetes: n(2)?,
+            proyectiles: Vec::new(),
+            espera: n(3)?,
+            destello: n(4)?,
+            fogonazo: n(5)?,
+            balanceo: c[6].parse().ok()?,
+        })
+    }
+
+    pub fn sprites(&self) -> Vec<Sprite> {
+        self.proyectiles.iter()
+            .map(|p| Sprite { x: p.x, y: p.y, kind: SpriteKind::Projectile })
+            .collect()
+    }
+
+    /// Arma en primera persona, abajo al centro, con balanceo y retroceso.
+    pub fn dibujar(&self, fb: &mut Framebuffer) {
+        let w = fb.w as i32;
+        let h = fb.h as i32;
+        let bob_x = (self.balanceo.sin() * 4.0) as i32;
+        let bob_y = ((self.balanceo * 2.0).sin().abs() * 3.0) as i32;
+        let retroceso = if self.fogonazo > 0 { 4 } else { 0 };
+        let cx = w / 2 + 20 + bob_x;
+        let base = h + bob_y + retroceso;
+
+        let rect = |fb: &mut Framebuffer, x0: i32, y0: i32, ancho: i32, alto: i32, c: Color| {
+            for y in y0..y0 + alto {
+                for x in x0..x0 + ancho { fb.set(x, y, c); }
+            }
+        };
+
+        let boca_y = match self.actual {
+            TipoArma::Pistola => {
+                rect(fb, cx - 8, base - 22, 16, 22, Color::DARKGRAY);   // empuñadura
+                rect(fb, cx - 5, base - 40, 10, 20, Color::GRAY);       // cañón
+                rect(fb, cx - 2, base - 42, 4, 3, Color::LIGHTGRAY);    // mira
+                base - 42
+            }
+            TipoArma::Lanzador => {
+                rect(fb, cx - 14, base - 26, 28, 26, Color::DARKGREEN); // cuerpo
+                rect(fb, cx - 9, base - 46, 18, 22, Color::GREEN);      // tubo
+                rect(fb, cx - 6, base - 48, 12, 3, Color::BLACK);       // boca
+                base - 48
+            }
+        };
+
+        // fogonazo en la boca del arma
+        if self.fogonazo > 0 {
+            let r = 3 + self.fogonazo as i32;
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    let d2 = dx * dx + dy * dy;
+                    if d2 <= r * r && (dx == 0 || dy == 0 || d2 <= r * r / 3) {
+                        let c = if d2 <= r * r / 4 { Color::WHITE } else { Color::YELLOW };
+                        fb.set(cx + dx, boca_y - 2 + dy, c);
+                    }
+                }
+            }
+        }
+    }
+}
+
+enum Impacto {
+    Nada,
+    Pared(f32, f32),
+    Enemigo(usize, f32, f32),
+}
+
+/// Rayo desde (ox, oy): pared más cercana por DDA y enemigo más cercano por
+/// intersección rayo-círculo; gana el que esté antes.
+fn hitscan(mapa: &Mapa, ox: f32, oy: f32, ang: f32, enemigos: &Enemigos) -> Impacto {
+    let (rdx, rdy) = (ang.cos(), ang.sin());
+    let dist_pared = lanzar_rayo(mapa, ox, oy, ang).map(|r| r.dist).unwrap_or(f32::INFINITY);
+
+    let mut mejor: Option<(usize, f32)> = None;
+    for (i, e) in enemigos.lista.iter().enumerate() {
+        let (ex, ey) = (e.x - ox, e.y - oy);
+        let t = ex * rdx + ey * rdy;         // proyección sobre el rayo
+        if t <= 0.0 { continue; }
+        let perp2 = ex * ex + ey * ey - t * t;
+        if perp2 > RADIO * RADIO { continue; }
+        let t_hit = t - (RADIO * RADIO - perp2).sqrt();
+        if t_hit >= dist_pared { continue; }
+        match mejor {
+            Some((_, d)) if d <= t_hit => {}
+            _ => mejor = Some((i, t_hit)),
+        }
+    }
+
+    match mejor {
+        Some((i, t)) => Impacto::Enemigo(i, ox + rdx * t, oy + rdy * t),
+        None if dist_pared.is_finite() => {
+            // un pelín antes de la pared
+            let d = dist_pared - 0.05;
+            Impacto::Pared(ox + rdx * d, oy + rdy * d)
+        }
+        None => Impacto::Nada,
+    }
+}
@@ -0,0 +1,168 @@
+//! Bloques que se empujan ('X') y placas de presión ('_').
+//!
+//! Caminando contra un bloque se lo corre una celda, si la de atrás está
+//! libre: piso, una placa o un pozo. En un pozo el bloque cae y lo tapa (la
+//! celda queda como piso). Sobre una placa la aprieta; las placas solo las
+//! aprietan los bloques, y los disparadores `apretar`/`soltar` (ver
+//! `eventos`) miran las de su zona.
+//!
+//! En el mapa el bloque salta de celda en el momento; el deslizamiento es
+//! solo lo que se dibuja (como las partículas, no se guarda).
+
+use crate::motor::{celda, Mapa};
+
+pub const DESLIZAR: u32 = 12; // frames que tarda en verse llegar a la otra celda
+
+/// Cómo terminó un empujón
+pub enum Empuje {
+    Movido,
+    Pozo(i32, i32), // cayó en el pozo de esa celda
+}
+
+struct Deslizamiento {
+    desde: (i32, i32),
+    hasta: (i32, i32),
+    t: u32,
+}
+
+pub struct Bloques {
+    placas: Vec<(i32, i32)>, // del nivel: al irse un bloque de ahí vuelve la placa
+    deslizando: Vec<Deslizamiento>,
+}
+
+impl Bloques {
+    /// Las placas salen del mapa original del nivel (en la partida puede
+    /// haber bloques encima).
+    pub fn nuevo(mapa_nivel: &Mapa) -> Self {
+        let placas = mapa_nivel.iter().enumerate()
+            .flat_map(|(y, fila)| fila.iter().enumerate()
+                .filter(|(_, &c)| c == '_')
+                .map(move |(x, _)| (x as i32, y as i32)))
+            .collect();
+        Self::con_placas(placas)
+    }
+
+    /// Con las placas ya sabidas (las de una partida guardada)
+    pub fn con_placas(placas: Vec<(i32, i32)>) -> Self {
+        Self { placas, deslizando: Vec::new() }
+    }
+
+    pub fn placas(&self) -> &[(i32, i32)] {
+        &self.placas
+    }
+
+    /// Empuja el bloque de (`x`, `y`) un paso en (`dx`, `dy`). `ocupada` dice
+    /// si en una celda hay algo que no se ve en el mapa (un enemigo).
+    pub fn empujar(&mut self, mapa: &mut Mapa, x: i32, y: i32, dx: i32, dy: i32,
+                   ocupada: impl Fn(i32, i32) -> bool) -> Option<Empuje> {
+        let (nx, ny) = (x + dx, y + dy);
+        if celda(mapa, x, y) != Some('X') || ocupada(nx, ny) { return None; }
+        let destino = celda(mapa, nx, ny)?;
+        if !matches!(destino, ' ' | '_' | 'P') { return None; }
+
+        mapa[y as usize][x as usize] = if self.placas.contains(&(x, y)) { '_' } else { ' ' };
+        mapa[ny as usize][nx as usize] = if destino == 'P' { ' ' } else { 'X' };
+        // si todavía se estaba deslizando, sigue desde donde llegó
+        self.deslizando.retain(|d| d.hasta != (x, y));
+        self.deslizando.push(Deslizamiento { desde: (x, y), hasta: (nx, ny), t: 0 });
+        Some(if destino == 'P' { Empuje::Pozo(nx, ny) } else { Empuje::Movido })
+    }
+
+    /// Si todas las placas del rectángulo tienen un bloque (y hay alguna)
+    pub fn apretadas(&self, mapa: &Mapa, x: i32, y: i32, w: i32, h: i32) -> bool {
+        let mut zona = self.placas.iter().filter(|&&(px, py)| px >= x && py >= y && px < x + w && py < y + h).peekable();
+        zona.peek().is_some() && zona.all(|&(px, py)| celda(mapa, px, py) == Some('X'))
+    }
+
+    /// Avanza los deslizamientos (un frame de dibujo)
+    pub fn avanzar(&mut self) {
+        for d in &mut self.deslizando { d.t += 1; }
+        self.deslizando.retain(|d| d.t < DESLIZAR);
+    }
+
+    /// Celdas donde el mapa ya tiene el bloque pero todavía no se lo ve llegar
+    pub fn en_camino(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.deslizando.iter().map(|d| d.hasta)
+    }
+
+    /// Esquina de cada bloque que se está deslizando, en coordenadas de mundo
+    pub fn cajas(&self) -> Vec<(f32, f32)> {
+        self.deslizando.iter().map(|d| {
+            let k = d.t as f32 / DESLIZAR as f32;
+            let k = k * k * (3.0 - 2.0 * k); // arranca y frena suave
+            (d.desde.0 as f32 + (d.hasta.0 - d.desde.0) as f32 * k,
+             d.desde.1 as f32 + (d.hasta.1 - d.desde.1) as f32 * k)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eventos::Eventos;
+    use crate::mapas::{Cuando, Disparador};
+
+    fn mapa(filas: &[&str]) -> Mapa {
+        filas.iter().map(|f| f.chars().collect()).collect()
+    }
+
+    fn fila(m: &Mapa, y: usize) -> String {
+        m[y].iter().collect()
+    }
+
+    #[test]
+    fn empuje_bloqueado() {
+        let mut m = mapa(&["111111", "1 XX11", "1  X 1", "111111"]);
+        let mut b = Bloques::nuevo(&m);
+        let libre = |_, _| false;
+        // contra otro bloque, contra la pared y contra un enemigo
+        assert!(b.empujar(&mut m, 2, 1, 1, 0, libre).is_none());
+        assert!(b.empujar(&mut m, 3, 1, 1, 0, libre).is_none());
+        assert!(b.empujar(&mut m, 3, 2, 1, 0, |x, y| (x, y) == (4, 2)).is_none());
+        // donde no hay bloque tampoco pasa nada
+        assert!(b.empujar(&mut m, 1, 1, 1, 0, libre).is_none());
+        assert_eq!(m, mapa(&["111111", "1 XX11", "1  X 1", "111111"]));
+        assert_eq!(b.en_camino().count(), 0);
+    }
+
+    #[test]
+    fn tapa_el_pozo() {
+        let mut m = mapa(&["11111", "1XP 1", "11111"]);
+        let mut b = Bloques::nuevo(&m);
+        assert!(matches!(b.empujar(&mut m, 1, 1, 1, 0, |_, _| false), Some(Empuje::Pozo(2, 1))));
+        assert_eq!(fila(&m, 1), "1   1");
+        // se lo ve deslizarse hasta que llega
+        assert_eq!(b.en_camino().collect::<Vec<_>>(), [(2, 1)]);
+        for _ in 0..DESLIZAR { b.avanzar(); }
+        assert_eq!(b.en_camino().count(), 0);
+    }
+
+    #[test]
+    fn apretar_y_soltar_placa() {
+        let mut m = mapa(&["111111", "1 X_ 1", "111111"]);
+        let mut b = Bloques::nuevo(&m);
+        let mut ev = Eventos::nuevo(&[
+            Disparador { x: 3, y: 1, w: 1, h: 1, cuando: Cuando::Apretar, acciones: "sumar a 1".to_string() },
+            Disparador { x: 3, y: 1, w: 1, h: 1, cuando: Cuando::Soltar, acciones: "sumar s 1".to_string() },
+        ]);
+        let mut frame = |m: &Mapa, b: &Bloques| {
+            for (i, o) in ev.revisar(0, 0, |x, y, w, h| b.apretadas(m, x, y, w, h)) { ev.resolver(i, o); }
+            (ev.vars.get("a").copied().unwrap_or(0), ev.vars.get("s").copied().unwrap_or(0))
+        };
+        assert_eq!(frame(&m, &b), (0, 0));
+
+        assert!(matches!(b.empujar(&mut m, 2, 1, 1, 0, |_, _| false), Some(Empuje::Movido)));
+        assert_eq!(fila(&m, 1), "1  X 1");
+        assert!(b.apretadas(&m, 3, 1, 1, 1));
+        assert_eq!(frame(&m, &b), (1, 0));
+        assert_eq!(frame(&m, &b), (1, 0));
+
+        // al irse el bloque vuelve la placa
+        b.empujar(&mut m, 3, 1, 1, 0, |_, _| false).unwrap();
+        assert_eq!(fila(&m, 1), "1  _X1");
+        assert!(!b.apretadas(&m, 3, 1, 1, 1));
+        assert_eq!(frame(&m, &b), (1, 1));
+        // una zona sin placas nunca está apretada
+        assert!(!b.apretadas(&m, 1, 1, 1, 1));
+    }
+}
@@ -0,0 +1,302 @@
+//! Búsqueda de caminos sobre el `Mapa`: A*, campos de distancia (flow field)
+//! y suavizado de rutas. Lo usan los enemigos y el validador de niveles.
+//!
+//! Una celda es transitable si `es_caminable` lo dice; los peligros
+//! ('P', 'S', 'L', 'V') se pueden pisar pero la IA los evita cuando
+//! `evitar_peligros` es true.
+
+use crate::motor::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Celdas caminables que hacen daño: la IA no pasa por ellas.
+pub const PELIGROS: &[char] = &['P', 'S', 'L', 'V'];
+
+const COSTO_RECTO: u32 = 10;
+const COSTO_DIAG: u32 = 14;
+
+// vecinos en 8 direcciones: (dx, dy, costo)
+const VECINOS: [(i32, i32, u32); 8] = [
+    (1, 0, COSTO_RECTO), (-1, 0, COSTO_RECTO), (0, 1, COSTO_RECTO), (0, -1, COSTO_RECTO),
+    (1, 1, COSTO_DIAG), (1, -1, COSTO_DIAG), (-1, 1, COSTO_DIAG), (-1, -1, COSTO_DIAG),
+];
+
+pub fn transitable(mapa: &Mapa, x: i32, y: i32, evitar_peligros: bool) -> bool {
+    match celda(mapa, x, y) {
+        Some(c) => es_caminable(c) && !(evitar_peligros && PELIGROS.contains(&c)),
+        None => false,
+    }
+}
+
+/// Un paso (dx, dy) desde (x, y) es válido si la celda destino es transitable
+/// y, en diagonal, no corta esquinas de pared.
+fn paso_valido(mapa: &Mapa, x: i32, y: i32, dx: i32, dy: i32, evitar: bool) -> bool {
+    if !transitable(mapa, x + dx, y + dy, evitar) { return false; }
+    if dx != 0 && dy != 0 {
+        return transitable(mapa, x + dx, y, evitar) && transitable(mapa, x, y + dy, evitar);
+    }
+    true
+}
+
+/// Heurística octil (admisible con los costos 10/14)
+fn heuristica(a: (i32, i32), b: (i32, i32)) -> u32 {
+    let dx = (a.0 - b.0).unsigned_abs();
+    let dy = (a.1 - b.1).unsigned_abs();
+    COSTO_RECTO * dx.max(dy) + (COSTO_DIAG - COSTO_RECTO) * dx.min(dy)
+}
+
+/// A* entre dos celdas. Devuelve la lista de celdas (incluye origen y destino)
+/// o `None` si no hay camino.
+pub fn buscar_camino(mapa: &Mapa, desde: (i32, i32), hasta: (i32, i32), evitar_peligros: bool) -> Option<Vec<(i32, i32)>> {
+    if !transitable(mapa, hasta.0, hasta.1, false) || celda(mapa, desde.0, desde.1).is_none() {
+        return None;
+    }
+    let w = mapa[0].len() as i32;
+    let idx = |x: i32, y: i32| (y * w + x) as usize;
+    let n = mapa.len() * mapa[0].len();
+
+    let mut g = vec![u32::MAX; n];
+    let mut padre: Vec<Option<(i32, i32)>> = vec![None; n];
+    let mut abiertos = BinaryHeap::new();
+
+    g[idx(desde.0, desde.1)] = 0;
+    abiertos.push(Reverse((heuristica(desde, hasta), 0u32, desde)));
+
+    while let Some(Reverse((_, costo, (x, y)))) = abiertos.pop() {
+        if (x, y) == hasta {
+            let mut camino = vec![hasta];
+            let mut actual = hasta;
+            while let Some(p) = padre[idx(actual.0, actual.1)] {
+                camino.push(p);
+                actual = p;
+            }
+            camino.reverse();
+            return Some(camino);
+        }
+        if costo > g[idx(x, y)] { continue; } // entrada vieja del heap
+
+        for &(dx, dy, c) in VECINOS.iter() {
+            // el destino siempre se acepta aunque sea peligro (p.ej. el jugador parado en un pozo)
+            let es_destino = (x + dx, y + dy) == hasta;
+            if !paso_valido(mapa, x, y, dx, dy, evitar_peligros && !es_destino) { continue; }
+            let (nx, ny) = (x + dx, y + dy);
+            let ng = costo + c;
+            if ng < g[idx(nx, ny)] {
+                g[idx(nx, ny)] = ng;
+                padre[idx(nx, ny)] = Some((x, y));
+                abiertos.push(Reverse((ng + heuristica((nx, ny), hasta), ng, (nx, ny))));
+            }
+        }
+    }
+    None
+}
+
+/// ¿Se puede llegar de `desde` a `hasta`?
+pub fn es_alcanzable(mapa: &Mapa, desde: (i32, i32), hasta: (i32, i32), evitar_peligros: bool) -> bool {
+    buscar_camino(mapa, desde, hasta, evitar_peligros).is_some()
+}
+
+/// Campo de distancias (Dijkstra) desde un origen: sirve como flow field,
+/// cada celda apunta al vecino más cercano al origen.
+pub struct CampoDistancias {
+    w: i32,
+    h: i32,
+    dist: Vec<u32>,
+}
+
+impl CampoDistancias {
+    pub fn calcular(mapa: &Mapa, origen: (i32, i32), evitar_peligros: bool) -> Self {
+        let w = mapa[0].len() as i32;
+        let h = mapa.len() as i32;
+        let mut dist = vec![u32::MAX; (w * h) as usize];
+        let mut cola = BinaryHeap::new();
+        if celda(mapa, origen.0, origen.1).is_some() {
+            dist[(origen.1 * w + origen.0) as usize] = 0;
+            cola.push(Reverse((0u32, origen)));
+        }
+        while let Some(Reverse((d, (x, y)))) = cola.pop() {
+            if d > dist[(y * w + x) as usize] { continue; }
+            for &(dx, dy, c) in VECINOS.iter() {
+                if !paso_valido(mapa, x, y, dx, dy, evitar_peligros) { continue; }
+                let i = ((y + dy) * w + x + dx) as usize;
+                if d + c < dist[i] {
+                    dist[i] = d + c;
+                    cola.push(Reverse((d + c, (x + dx, y + dy))));
+                }
+            }
+        }
+        Self { w, h, dist }
+    }
+
+    /// Distancia (en décimas de celda) al origen, `None` si es inalcanzable.
+    pub fn distancia(&self, x: i32, y: i32) -> Option<u32> {
+        if x < 0 || y < 0 || x >= self.w || y >= self.h { return None; }
+        let d = self.dist[(y * self.w + x) as usize];
+        if d == u32::MAX { None } else { Some(d) }
+    }
+
+    /// Vecino que más acerca al origen (bajando por el campo).
+    pub fn hacia_origen(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let mut mejor = (self.distancia(x, y)?, None);
+        for &(dx, dy, _) in VECINOS.iter() {
+            if let Some(d) = self.distancia(x + dx, y + dy) {
+                if d < mejor.0 { mejor = (d, Some((x + dx, y + dy))); }
+            }
+        }
+        mejor.1
+    }
+
+    /// Vecino que más aleja del origen (subiendo por el campo).
+    pub fn lejos_de_origen(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let mut mejor = (self.distancia(x, y)?, None);
+        for &(dx, dy, _) in VECINOS.iter() {
+            if let Some(d) = self.distancia(x + dx, y + dy) {
+                if d > mejor.0 { mejor = (d, Some((x + dx, y + dy))); }
+            }
+        }
+        mejor.1
+    }
+}
+
+/// Tramo recto entre dos puntos sin pisar celdas no transitables
+/// (muestreo fino; cubre también los peligros, a diferencia de `linea_de_vision`).
+fn tramo_libre(mapa: &Mapa, a: (f32, f32), b: (f32, f32), evitar_peligros: bool) -> bool {
+    let dist = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+    let pasos = (dist / 0.1).ceil().max(1.0) as i32;
+    (0..=pasos).all(|i| {
+        let t = i as f32 / pasos as f32;
+        let x = a.0 + (b.0 - a.0) * t;
+        let y = a.1 + (b.1 - a.1) * t;
+        transitable(mapa, x.floor() as i32, y.floor() as i32, evitar_peligros)
+    })
+}
+
+/// Convierte un camino de celdas en puntos (centros de celda) y quita los
+/// intermedios que se pueden saltar en línea recta ("string pulling").
+pub fn suavizar_camino(mapa: &Mapa, camino: &[(i32, i32)], evitar_peligros: bool) -> Vec<(f32, f32)> {
+    let puntos: Vec<(f32, f32)> = camino.iter()
+        .map(|&(x, y)| (x as f32 + 0.5, y as f32 + 0.5))
+        .collect();
+    if puntos.len() <= 2 { return puntos; }
+
+    let mut res = vec![puntos[0]];
+    let mut ancla = 0;
+    for i in 2..puntos.len() {
+        if !tramo_libre(mapa, puntos[ancla], puntos[i], evitar_peligros) {
+            ancla = i - 1;
+            res.push(puntos[ancla]);
+        }
+    }
+    res.push(*puntos.last().unwrap());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    fn mapa(filas: &[&str]) -> Mapa {
+        filas.iter().map(|f| f.chars().collect()).collect()
+    }
+
+    const PARED: [&str; 6] = ["1111111", "1     1", "1  1  1", "1  1  1", "1  1  1", "1111111"];
+
+    #[test]
+    fn rodea_la_pared() {
+        let m = mapa(&PARED);
+        let camino = buscar_camino(&m, (1, 4), (5, 4), false).unwrap();
+        assert_eq!((camino[0], *camino.last().unwrap()), ((1, 4), (5, 4)));
+        for par in camino.windows(2) {
+            let (dx, dy) = (par[1].0 - par[0].0, par[1].1 - par[0].1);
+            assert!(dx.abs() <= 1 && dy.abs() <= 1 && (dx, dy) != (0, 0), "{par:?}");
+            assert!(paso_valido(&m, par[0].0, par[0].1, dx, dy, false), "{par:?}");
+        }
+        // tiene que subir hasta la fila 1 para pasar por encima de la pared
+        assert!(camino.contains(&(3, 1)));
+        assert_eq!(camino.len(), 9); // sin cortar las esquinas de la pared
+    }
+
+    #[test]
+    fn evita_peligros() {
+        let m = mapa(&["11111", "1 P 1", "1 1 1", "11111"]);
+        assert!(es_alcanzable(&m, (1, 1), (3, 1), false));
+        assert!(!es_alcanzable(&m, (1, 1), (3, 1), true));
+        // pero el destino se acepta aunque sea peligro
+        assert_eq!(buscar_camino(&m, (1, 1), (2, 1), true), Some(vec![(1, 1), (2, 1)]));
+    }
+
+    #[test]
+    fn destino_encerrado() {
+        let m = mapa(&["1111111", "1   111", "1   1 1", "1   111", "1111111"]);
+        assert_eq!(buscar_camino(&m, (1, 1), (5, 2), false), None);
+        assert_eq!(buscar_camino(&m, (1, 1), (4, 2), false), None); // pared
+        assert_eq!(buscar_camino(&m, (1, 1), (9, 9), false), None); // fuera del mapa
+        let campo = CampoDistancias::calcular(&m, (1, 1), false);
+        assert_eq!(campo.distancia(5, 2), None);
+        assert_eq!(campo.hacia_origen(5, 2), None);
+    }
+
+    #[test]
+    fn campo_igual_a_bfs() {
+        // pasillos de una celda: no hay diagonales posibles, así que cada paso cuesta 10
+        let m = mapa(&[
+            "111111111",
+            "1   1   1",
+            "1 1 1 1 1",
+            "1 1   1 1",
+            "1 11111 1",
+            "1     1 1",
+            "111111111",
+        ]);
+        let origen = (1, 1);
+        let mut pasos = vec![vec![None; m[0].len()]; m.len()];
+        let mut cola = VecDeque::from([origen]);
+        pasos[1][1] = Some(0u32);
+        while let Some((x, y)) = cola.pop_front() {
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if transitable(&m, nx, ny, false) && pasos[ny as usize][nx as usize].is_none() {
+                    pasos[ny as usize][nx as usize] = Some(pasos[y as usize][x as usize].unwrap() + 1);
+                    cola.push_back((nx, ny));
+                }
+            }
+        }
+
+        let campo = CampoDistancias::calcular(&m, origen, false);
+        for y in 0..m.len() as i32 {
+            for x in 0..m[0].len() as i32 {
+                let esperado = pasos[y as usize][x as usize].map(|p| p * COSTO_RECTO);
+                assert_eq!(campo.distancia(x, y), esperado, "({x}, {y})");
+                let Some(d) = esperado else { continue };
+                if let Some((hx, hy)) = campo.hacia_origen(x, y) {
+                    assert!(campo.distancia(hx, hy).unwrap() < d);
+                }
+                if let Some((lx, ly)) = campo.lejos_de_origen(x, y) {
+                    assert!(campo.distancia(lx, ly).unwrap() > d);
+                }
+            }
+        }
+        // (5, 5) y (7, 5) quedan a los dos lados de la pared: hay que dar toda la vuelta
+        assert_eq!(campo.distancia(7, 5), Some(14 * COSTO_RECTO));
+        assert_eq!(campo.hacia_origen(1, 1), None);
+    }
+
+    #[test]
+    fn suavizado() {
+        let libre = mapa(&["111111", "1    1", "1    1", "1    1", "111111"]);
+        let camino = [(1, 1), (2, 1), (3, 1), (4, 1), (4, 2), (4, 3)];
+        assert_eq!(suavizar_camino(&libre, &camino, false), vec![(1.5, 1.5), (4.5, 3.5)]);
+        assert_eq!(suavizar_camino(&libre, &[(2, 2)], false), vec![(2.5, 2.5)]);
+
+        // con la pared en medio hay que conservar los quiebres
+        let m = mapa(&PARED);
+        let camino = buscar_camino(&m, (1, 4), (5, 4), false).unwrap();
+        let puntos = suavizar_camino(&m, &camino, false);
+        assert!(puntos.len() > 2 && puntos.len() < camino.len(), "{puntos:?}");
+        assert_eq!((puntos[0], *puntos.last().unwrap()), ((1.5, 4.5), (5.5, 4.5)));
+        for par in puntos.windows(2) {
+            assert!(tramo_libre(&m, par[0], par[1], false), "{par:?}");
+        }
+    }
+}
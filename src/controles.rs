@@ -0,0 +1,454 @@
+//! Controles por acción: cada `Accion` tiene una lista de botones (teclas,
+//! botones del mouse o del gamepad) y los ejes del gamepad, su zona muerta,
+//! su curva de respuesta y las sensibilidades son configurables. Se guardan
+//! en `controles.txt` dentro del directorio de datos, en texto plano, y se
+//! pueden cambiar desde la pantalla de controles. Los gamepads se leen todos
+//! a la vez (ver `mandos`).
+
+use crate::guardado::directorio_datos;
+use crate::idiomas::{Idioma, Mensaje};
+use raylib::core::input::key_from_i32;
+use raylib::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+const VERSION: u32 = 1;
+const CABECERA: &str = "raycaster-controles";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Accion {
+    Avanzar,
+    Retroceder,
+    PasoIzq,
+    PasoDer,
+    GirarIzq,
+    GirarDer,
+    Disparar,
+    Pistola,
+    Lanzador,
+    CambiarArma,
+    Usar,  // aceptar en menús
+    Pausa,
+    Mouse, // activar/desactivar mirar con el mouse
+    // navegación de menús
+    Arriba,
+    Abajo,
+    Izquierda,
+    Derecha,
+    Volver,
+}
+
+impl Accion {
+    pub const TODAS: [Accion; 18] = [
+        Accion::Avanzar, Accion::Retroceder, Accion::PasoIzq, Accion::PasoDer,
+        Accion::GirarIzq, Accion::GirarDer, Accion::Disparar, Accion::Pistola,
+        Accion::Lanzador, Accion::CambiarArma, Accion::Usar, Accion::Pausa, Accion::Mouse,
+        Accion::Arriba, Accion::Abajo, Accion::Izquierda, Accion::Derecha, Accion::Volver,
+    ];
+
+    /// Clave en el archivo de configuración
+    fn clave(self) -> &'static str {
+        match self {
+            Accion::Avanzar => "avanzar",
+            Accion::Retroceder => "retroceder",
+            Accion::PasoIzq => "paso_izq",
+            Accion::PasoDer => "paso_der",
+            Accion::GirarIzq => "girar_izq",
+            Accion::GirarDer => "girar_der",
+            Accion::Disparar => "disparar",
+            Accion::Pistola => "pistola",
+            Accion::Lanzador => "lanzador",
+            Accion::CambiarArma => "cambiar_arma",
+            Accion::Usar => "usar",
+            Accion::Pausa => "pausa",
+            Accion::Mouse => "mouse",
+            Accion::Arriba => "arriba",
+            Accion::Abajo => "abajo",
+            Accion::Izquierda => "izquierda",
+            Accion::Derecha => "derecha",
+            Accion::Volver => "volver",
+        }
+    }
+
+    /// Texto para la pantalla de controles ("accion.<clave>" en `idiomas`)
+    pub fn nombre(self, l: Idioma) -> String {
+        l.t(&format!("accion.{}", self.clave())).to_string()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Boton {
+    Tecla(KeyboardKey),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+}
+
+const BOTONES_MOUSE: [MouseButton; 3] =
+    [MouseButton::MOUSE_LEFT_BUTTON, MouseButton::MOUSE_RIGHT_BUTTON, MouseButton::MOUSE_MIDDLE_BUTTON];
+
+pub(crate) const BOTONES_PAD: [GamepadButton; 17] = {
+    use GamepadButton::*;
+    [
+        GAMEPAD_BUTTON_LEFT_FACE_UP, GAMEPAD_BUTTON_LEFT_FACE_RIGHT, GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+        GAMEPAD_BUTTON_LEFT_FACE_LEFT, GAMEPAD_BUTTON_RIGHT_FACE_UP, GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+        GAMEPAD_BUTTON_RIGHT_FACE_DOWN, GAMEPAD_BUTTON_RIGHT_FACE_LEFT, GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+        GAMEPAD_BUTTON_LEFT_TRIGGER_2, GAMEPAD_BUTTON_RIGHT_TRIGGER_1, GAMEPAD_BUTTON_RIGHT_TRIGGER_2,
+        GAMEPAD_BUTTON_MIDDLE_LEFT, GAMEPAD_BUTTON_MIDDLE, GAMEPAD_BUTTON_MIDDLE_RIGHT,
+        GAMEPAD_BUTTON_LEFT_THUMB, GAMEPAD_BUTTON_RIGHT_THUMB,
+    ]
+};
+
+pub(crate) const EJES_PAD: [GamepadAxis; 6] = {
+    use GamepadAxis::*;
+    [
+        GAMEPAD_AXIS_LEFT_X, GAMEPAD_AXIS_LEFT_Y, GAMEPAD_AXIS_RIGHT_X,
+        GAMEPAD_AXIS_RIGHT_Y, GAMEPAD_AXIS_LEFT_TRIGGER, GAMEPAD_AXIS_RIGHT_TRIGGER,
+    ]
+};
+
+/// Todas las teclas que conoce raylib
+fn teclas() -> impl Iterator<Item = KeyboardKey> {
+    (0..350).filter_map(key_from_i32)
+}
+
+// nombres cortos a partir de los de raylib: KEY_W -> W, GAMEPAD_AXIS_LEFT_X -> LEFT_X
+fn corto(largo: String, prefijo: &str, sufijo: &str) -> String {
+    largo.trim_start_matches(prefijo).trim_end_matches(sufijo).to_string()
+}
+
+pub fn nombre_eje(e: GamepadAxis) -> String {
+    corto(format!("{e:?}"), "GAMEPAD_AXIS_", "")
+}
+
+fn eje_desde_nombre(s: &str) -> Option<GamepadAxis> {
+    EJES_PAD.into_iter().find(|e| nombre_eje(*e) == s)
+}
+
+impl Boton {
+    /// "tecla W", "mouse LEFT", "pad RIGHT_TRIGGER_2"
+    pub fn texto(&self) -> String {
+        match self {
+            Boton::Tecla(k) => format!("tecla {}", corto(format!("{k:?}"), "KEY_", "")),
+            Boton::Mouse(b) => format!("mouse {}", corto(format!("{b:?}"), "MOUSE_", "_BUTTON")),
+            Boton::Pad(b) => format!("pad {}", corto(format!("{b:?}"), "GAMEPAD_BUTTON_", "")),
+        }
+    }
+
+    /// Nombre para mostrar: "W", "Mouse LEFT", "Pad RIGHT_TRIGGER_2"
+    pub fn etiqueta(&self) -> String {
+        let t = self.texto();
+        match self {
+            Boton::Tecla(_) => t["tecla ".len()..].to_string(),
+            Boton::Mouse(_) => format!("Mouse {}", &t["mouse ".len()..]),
+            Boton::Pad(_) => format!("Pad {}", &t["pad ".len()..]),
+        }
+    }
+
+    fn desde_texto(s: &str) -> Option<Self> {
+        let todos = teclas().map(Boton::Tecla)
+            .chain(BOTONES_MOUSE.into_iter().map(Boton::Mouse))
+            .chain(BOTONES_PAD.into_iter().map(Boton::Pad));
+        todos.into_iter().find(|b| b.texto() == s)
+    }
+
+    // `pads`: gamepads conectados; cualquiera sirve
+    fn pulsado(&self, d: &RaylibHandle, pads: &[i32]) -> bool {
+        match *self {
+            Boton::Tecla(k) => d.is_key_pressed(k),
+            Boton::Mouse(b) => d.is_mouse_button_pressed(b),
+            Boton::Pad(b) => pads.iter().any(|&id| d.is_gamepad_button_pressed(id, b)),
+        }
+    }
+
+    fn abajo(&self, d: &RaylibHandle, pads: &[i32]) -> bool {
+        match *self {
+            Boton::Tecla(k) => d.is_key_down(k),
+            Boton::Mouse(b) => d.is_mouse_button_down(b),
+            Boton::Pad(b) => pads.iter().any(|&id| d.is_gamepad_button_down(id, b)),
+        }
+    }
+}
+
+/// Primer botón que se apretó en este frame (para reasignar)
+pub fn boton_pulsado(d: &RaylibHandle, pads: &[i32]) -> Option<Boton> {
+    teclas().map(Boton::Tecla)
+        .chain(BOTONES_MOUSE.into_iter().map(Boton::Mouse))
+        .chain(BOTONES_PAD.into_iter().map(Boton::Pad))
+        .find(|b| b.pulsado(d, pads))
+}
+
+/// Respuesta de un eje: lo que cae en la zona muerta vale 0 y el resto se
+/// reescala a 0..1 y se eleva a `curva` (1 = lineal, más = más fino al centro).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AjusteEje {
+    pub zona_muerta: f32,
+    pub curva: f32,
+}
+
+impl AjusteEje {
+    const NORMAL: AjusteEje = AjusteEje { zona_muerta: 0.15, curva: 1.0 };
+
+    pub fn aplicar(&self, v: f32) -> f32 {
+        let a = v.abs();
+        if a <= self.zona_muerta { return 0.0; }
+        let t = ((a - self.zona_muerta) / (1.0 - self.zona_muerta)).min(1.0);
+        t.powf(self.curva).copysign(v)
+    }
+}
+
+/// Filas de la pantalla de controles
+#[derive(Clone, Copy, PartialEq)]
+pub enum Fila {
+    Accion(Accion),
+    SensMouse,
+    SensPad,
+    Sticks,
+    ZonaMuerta(GamepadAxis),
+    Curva(GamepadAxis),
+}
+
+pub struct Controles {
+    pub botones: Vec<(Accion, Boton)>,
+    pub sens_mouse: f32, // radianes por pixel
+    pub sens_pad: f32,   // radianes por frame con el stick a fondo
+    pub eje_avance: GamepadAxis,
+    pub eje_lateral: GamepadAxis,
+    pub eje_giro: GamepadAxis,
+    pub ajustes: [AjusteEje; 6], // por eje, en el orden de `EJES_PAD`
+    ruta: PathBuf,
+    pub aviso: Option<Mensaje>, // problema al cargar/guardar, para mostrar en el menú
+}
+
+impl Controles {
+    /// WASD + Q/E, mouse para mirar y disparar, gamepad con stick izquierdo para moverse.
+    pub fn por_defecto() -> Self {
+        use Accion::*;
+        use KeyboardKey::*;
+        let t = Boton::Tecla;
+        let p = Boton::Pad;
+        let botones = vec![
+            (Avanzar, t(KEY_W)), (Retroceder, t(KEY_S)), (PasoIzq, t(KEY_A)), (PasoDer, t(KEY_D)),
+            (GirarIzq, t(KEY_Q)), (GirarDer, t(KEY_E)),
+            (Disparar, t(KEY_SPACE)), (Disparar, Boton::Mouse(MouseButton::MOUSE_LEFT_BUTTON)),
+            (Disparar, p(GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)),
+            (Pistola, t(KEY_ONE)), (Lanzador, t(KEY_TWO)),
+            (CambiarArma, p(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP)),
+            (Usar, t(KEY_ENTER)), (Usar, p(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)),
+            (Pausa, t(KEY_P)), (Pausa, p(GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT)),
+            (Mouse, t(KEY_M)),
+            (Arriba, t(KEY_UP)), (Arriba, p(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)),
+            (Abajo, t(KEY_DOWN)), (Abajo, p(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN)),
+            (Izquierda, t(KEY_LEFT)), (Izquierda, p(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)),
+            (Derecha, t(KEY_RIGHT)), (Derecha, p(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)),
+            (Volver, t(KEY_BACKSPACE)), (Volver, p(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)),
+        ];
+        Self {
+            botones,
+            sens_mouse: 0.0035,
+            sens_pad: 0.04,
+            eje_avance: GamepadAxis::GAMEPAD_AXIS_LEFT_Y,
+            eje_lateral: GamepadAxis::GAMEPAD_AXIS_LEFT_X,
+            eje_giro: GamepadAxis::GAMEPAD_AXIS_RIGHT_X,
+            ajustes: [AjusteEje::NORMAL; 6],
+            ruta: directorio_datos().join("controles.txt"),
+            aviso: None,
+        }
+    }
+
+    /// Lee `controles.txt`; si no existe quedan los de siempre. Las líneas
+    /// que no se entienden se saltean con un aviso.
+    pub fn cargar() -> Self {
+        let mut c = Self::por_defecto();
+        let txt = match fs::read_to_string(&c.ruta) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return c,
+            Err(e) => {
+                c.aviso = Some(Mensaje::con("aviso.controles_no_leidos", &[&e]));
+                return c;
+            }
+        };
+        let mut lineas = txt.lines().enumerate();
+        let version = lineas.next()
+            .and_then(|(_, cab)| cab.strip_prefix(CABECERA))
+            .and_then(|v| v.trim().parse::<u32>().ok());
+        if version.is_none_or(|v| v > VERSION) {
+            c.aviso = Some(Mensaje::nuevo("aviso.controles_invalidos"));
+            return c;
+        }
+
+        c.botones.clear();
+        let mut malas = Vec::new();
+        for (i, l) in lineas {
+            let l = l.trim();
+            if l.is_empty() || l.starts_with('#') { continue; }
+            let (clave, resto) = l.split_once(' ').unwrap_or((l, ""));
+            let ok = match clave {
+                "sens_mouse" => resto.parse().map(|v| c.sens_mouse = v).is_ok(),
+                "sens_pad" => resto.parse().map(|v| c.sens_pad = v).is_ok(),
+                "eje_avance" => eje_desde_nombre(resto).map(|e| c.eje_avance = e).is_some(),
+                "eje_lateral" => eje_desde_nombre(resto).map(|e| c.eje_lateral = e).is_some(),
+                "eje_giro" => eje_desde_nombre(resto).map(|e| c.eje_giro = e).is_some(),
+                "ajuste_eje" => {
+                    // ajuste_eje LEFT_X <zona muerta> <curva>
+                    let p: Vec<&str> = resto.split_whitespace().collect();
+                    let eje = p.first().and_then(|n| eje_desde_nombre(n));
+                    let zona = p.get(1).and_then(|v| v.parse::<f32>().ok());
+                    let curva = p.get(2).and_then(|v| v.parse::<f32>().ok());
+                    match (eje, zona, curva) {
+                        (Some(e), Some(z), Some(k)) if (0.0..1.0).contains(&z) && k > 0.0 => {
+                            c.ajustes[e as usize] = AjusteEje { zona_muerta: z, curva: k };
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                _ => match (Accion::TODAS.into_iter().find(|a| a.clave() == clave), Boton::desde_texto(resto)) {
+                    (Some(a), Some(b)) => { c.botones.push((a, b)); true }
+                    _ => false,
+                },
+            };
+            if !ok { malas.push((i + 1).to_string()); }
+        }
+        // acciones que el archivo no menciona (p. ej. agregadas después) conservan las de siempre
+        let faltan: Vec<Accion> = Accion::TODAS.into_iter().filter(|&a| c.de(a).is_empty()).collect();
+        c.botones.extend(Self::por_defecto().botones.into_iter().filter(|(a, _)| faltan.contains(a)));
+        if !malas.is_empty() {
+            c.aviso = Some(Mensaje::con("aviso.controles_lineas", &[&malas.join(", ")]));
+        }
+        c
+    }
+
+    pub fn guardar(&mut self) {
+        let mut s = format!("{CABECERA} {VERSION}\n");
+        s += &format!("sens_mouse {}\n", self.sens_mouse);
+        s += &format!("sens_pad {}\n", self.sens_pad);
+        s += &format!("eje_avance {}\n", nombre_eje(self.eje_avance));
+        s += &format!("eje_lateral {}\n", nombre_eje(self.eje_lateral));
+        s += &format!("eje_giro {}\n", nombre_eje(self.eje_giro));
+        for e in EJES_PAD {
+            let a = self.ajustes[e as usize];
+            s += &format!("ajuste_eje {} {} {}\n", nombre_eje(e), a.zona_muerta, a.curva);
+        }
+        for (a, b) in &self.botones {
+            s += &format!("{} {}\n", a.clave(), b.texto());
+        }
+        let res = (|| -> std::io::Result<()> {
+            if let Some(dir) = self.ruta.parent() { fs::create_dir_all(dir)?; }
+            fs::write(&self.ruta, s)
+        })();
+        if let Err(e) = res {
+            self.aviso = Some(Mensaje::con("aviso.controles_no_guardados", &[&e]));
+        }
+    }
+
+    /// Botones asignados a `a`
+    pub fn de(&self, a: Accion) -> Vec<Boton> {
+        self.botones.iter().filter(|(x, _)| *x == a).map(|(_, b)| *b).collect()
+    }
+
+    pub fn pulsado(&self, d: &RaylibHandle, pads: &[i32], a: Accion) -> bool {
+        self.botones.iter().any(|(x, b)| *x == a && b.pulsado(d, pads))
+    }
+
+    pub fn abajo(&self, d: &RaylibHandle, pads: &[i32], a: Accion) -> bool {
+        self.botones.iter().any(|(x, b)| *x == a && b.abajo(d, pads))
+    }
+
+    /// Valor del eje `e` con zona muerta y curva; con varios gamepads gana
+    /// el que más se mueve.
+    pub fn eje(&self, d: &RaylibHandle, pads: &[i32], e: GamepadAxis) -> f32 {
+        let aj = self.ajustes[e as usize];
+        pads.iter()
+            .map(|&id| aj.aplicar(d.get_gamepad_axis_movement(id, e)))
+            .fold(0.0, |m: f32, v| if v.abs() > m.abs() { v } else { m })
+    }
+
+    /// Filas de la pantalla de controles: acciones, sensibilidades y los
+    /// ajustes de los tres ejes en uso.
+    pub fn filas(&self) -> Vec<Fila> {
+        let mut f: Vec<Fila> = Accion::TODAS.iter().map(|&a| Fila::Accion(a)).collect();
+        f.extend([Fila::SensMouse, Fila::SensPad, Fila::Sticks]);
+        for e in [self.eje_avance, self.eje_lateral, self.eje_giro] {
+            f.extend([Fila::ZonaMuerta(e), Fila::Curva(e)]);
+        }
+        f
+    }
+
+    pub fn nombre_fila(&self, f: Fila, l: Idioma) -> String {
+        match f {
+            Fila::Accion(a) => a.nombre(l),
+            Fila::SensMouse => l.t("opcion.sens_mouse").to_string(),
+            Fila::SensPad => l.t("opcion.sens_pad").to_string(),
+            Fila::Sticks => l.t("controles.sticks").to_string(),
+            Fila::ZonaMuerta(e) => l.f("controles.zona_muerta", &[&nombre_eje(e)]),
+            Fila::Curva(e) => l.f("controles.curva", &[&nombre_eje(e)]),
+        }
+    }
+
+    pub fn valor_fila(&self, f: Fila) -> String {
+        match f {
+            Fila::Accion(a) => self.resumen(a),
+            Fila::SensMouse => format!("{:.4}", self.sens_mouse),
+            Fila::SensPad => format!("{:.3}", self.sens_pad),
+            Fila::Sticks => nombre_eje(self.eje_lateral),
+            Fila::ZonaMuerta(e) => format!("{:.2}", self.ajustes[e as usize].zona_muerta),
+            Fila::Curva(e) => format!("{:.2}", self.ajustes[e as usize].curva),
+        }
+    }
+
+    /// ←/→ sobre una fila de ajuste (`paso` = -1 o 1); las acciones no cambian.
+    pub fn ajustar_fila(&mut self, f: Fila, paso: i32) {
+        let factor = if paso > 0 { 1.1 } else { 1.0 / 1.1 };
+        match f {
+            Fila::Accion(_) => {}
+            Fila::SensMouse => self.sens_mouse *= factor,
+            Fila::SensPad => self.sens_pad *= factor,
+            Fila::Sticks => self.intercambiar_sticks(),
+            Fila::ZonaMuerta(e) => {
+                let z = &mut self.ajustes[e as usize].zona_muerta;
+                *z = (*z + 0.05 * paso as f32).clamp(0.0, 0.5);
+            }
+            Fila::Curva(e) => {
+                let k = &mut self.ajustes[e as usize].curva;
+                *k = (*k + 0.25 * paso as f32).clamp(0.5, 3.0);
+            }
+        }
+    }
+
+    /// Botones de `a` para mostrar ("-" si no tiene)
+    pub fn resumen(&self, a: Accion) -> String {
+        let b: Vec<String> = self.de(a).iter().map(|b| b.etiqueta()).collect();
+        if b.is_empty() { "-".to_string() } else { b.join(" / ") }
+    }
+
+    /// Asigna `b` a `a`: reemplaza el botón del mismo dispositivo (teclado,
+    /// mouse o gamepad) y se lo quita a cualquier otra acción que lo tuviera.
+    pub fn asignar(&mut self, a: Accion, b: Boton) {
+        let mismo = |y: &Boton| std::mem::discriminant(y) == std::mem::discriminant(&b);
+        self.botones.retain(|(x, y)| *y != b && !(*x == a && mismo(y)));
+        self.botones.push((a, b));
+    }
+
+    /// Intercambia los sticks: moverse con el derecho y girar con el izquierdo.
+    pub fn intercambiar_sticks(&mut self) {
+        use GamepadAxis::*;
+        let diestro = self.eje_lateral == GAMEPAD_AXIS_LEFT_X;
+        (self.eje_avance, self.eje_lateral, self.eje_giro) = if diestro {
+            (GAMEPAD_AXIS_RIGHT_Y, GAMEPAD_AXIS_RIGHT_X, GAMEPAD_AXIS_LEFT_X)
+        } else {
+            (GAMEPAD_AXIS_LEFT_Y, GAMEPAD_AXIS_LEFT_X, GAMEPAD_AXIS_RIGHT_X)
+        };
+    }
+
+    /// Vuelve a los controles de siempre (conserva ruta y aviso)
+    pub fn restaurar(&mut self) {
+        let d = Self::por_defecto();
+        self.botones = d.botones;
+        self.sens_mouse = d.sens_mouse;
+        self.sens_pad = d.sens_pad;
+        self.eje_avance = d.eje_avance;
+        self.eje_lateral = d.eje_lateral;
+        self.eje_giro = d.eje_giro;
+        self.ajustes = d.ajustes;
+    }
+}
@@ -0,0 +1,193 @@
+//! Cuevas con autómata celular: el mapa se llena de roca al azar, se suaviza
+//! unas cuantas pasadas (una celda es roca si la mayoría de sus vecinas lo
+//! es), queda solo la región abierta más grande y se cierran los bordes.
+//! Los pozos van cerca de las paredes y las antorchas en los lugares abiertos.
+//! Con la misma semilla sale la misma cueva, para poder pasársela a otros
+//! (por eso el azar es ChaCha8, como en `laberintos`).
+
+use crate::generacion::{celdas_con, celdas_libres, mas_lejana, repartir};
+use crate::mapas::{Generado, Nivel};
+use crate::motor::{celda, Mapa};
+use crate::reglas::Regla;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::VecDeque;
+
+pub const TIPO: &str = "cueva";
+const ROCA: char = '6';
+const MUSGO: char = '3';
+const MAX_INTENTOS: u32 = 20;
+
+/// Configuración del generador
+#[derive(Clone, Copy)]
+pub struct Cueva {
+    pub ancho: usize,
+    pub alto: usize,
+    pub relleno: f64, // probabilidad inicial de roca
+    pub pasadas: u32, // de suavizado
+    pub monedas: usize,
+    pub antorchas: usize,
+    pub pozos: usize,
+}
+
+impl Default for Cueva {
+    fn default() -> Self {
+        Self { ancho: 40, alto: 30, relleno: 0.45, pasadas: 5, monedas: 8, antorchas: 6, pozos: 6 }
+    }
+}
+
+impl Cueva {
+    pub fn generar(&self, semilla: u64) -> Nivel {
+        let mut r = ChaCha8Rng::seed_from_u64(semilla);
+        // si la región que queda es muy chica se prueba de nuevo (con el mismo generador)
+        let mut mapa = self.excavar(&mut r);
+        for _ in 0..MAX_INTENTOS {
+            if celdas_libres(&mapa).len() >= self.ancho * self.alto / 3 { break; }
+            mapa = self.excavar(&mut r);
+        }
+
+        // musgo en parte de las paredes que dan a la cueva
+        for (x, y) in celdas_con(&mapa, &[ROCA]) {
+            if rocas_vecinas(&mapa, x, y) < 8 && r.gen_bool(0.25) {
+                mapa[y as usize][x as usize] = MUSGO;
+            }
+        }
+
+        let mut libres = celdas_libres(&mapa);
+        libres.shuffle(&mut r);
+        let abiertas: Vec<_> = libres.iter().copied().filter(|&(x, y)| rocas_vecinas(&mapa, x, y) == 0).collect();
+        let inicio = abiertas.first().or(libres.first()).copied().unwrap_or((1, 1));
+        if let Some((x, y)) = mas_lejana(&mapa, inicio) {
+            mapa[y as usize][x as usize] = 'E';
+        }
+
+        let monedas = repartir(&mut mapa, inicio, &libres, 'C', self.monedas);
+        repartir(&mut mapa, inicio, &separadas(&abiertas, 4), 'A', self.antorchas);
+        let bordes: Vec<_> = libres.iter().copied()
+            .filter(|&(x, y)| rocas_vecinas(&mapa, x, y) >= 3)
+            .filter(|&(x, y)| (x - inicio.0).abs() + (y - inicio.1).abs() > 4)
+            .collect();
+        repartir(&mut mapa, inicio, &bordes, 'P', self.pozos);
+
+        Nivel {
+            id: format!("{TIPO}-{semilla}"),
+            titulo: None,
+            generado: Some(Generado { tipo: TIPO, semilla }),
+            mapa,
+            inicio: (inicio.0, inicio.1, 0.0),
+            reglas: if monedas > 0 { vec![Regla::MinimoMonedas(monedas * 3 / 4)] } else { Vec::new() },
+            disparadores: Vec::new(),
+            teletransportes: Vec::new(),
+        }
+    }
+
+    /// Relleno, suavizado, región más grande y bordes cerrados
+    fn excavar(&self, r: &mut ChaCha8Rng) -> Mapa {
+        let (w, h) = (self.ancho.max(3), self.alto.max(3));
+        let mut mapa: Mapa = (0..h)
+            .map(|_| (0..w).map(|_| if r.gen_bool(self.relleno) { ROCA } else { ' ' }).collect())
+            .collect();
+        for _ in 0..self.pasadas {
+            let antes = mapa.clone();
+            for (y, fila) in mapa.iter_mut().enumerate() {
+                for (x, c) in fila.iter_mut().enumerate() {
+                    let n = rocas_vecinas(&antes, x as i32, y as i32);
+                    if n > 4 { *c = ROCA; } else if n < 4 { *c = ' '; }
+                }
+            }
+        }
+        for (y, fila) in mapa.iter_mut().enumerate() {
+            for (x, c) in fila.iter_mut().enumerate() {
+                if x == 0 || y == 0 || x == w - 1 || y == h - 1 { *c = ROCA; }
+            }
+        }
+
+        // la región más grande se queda; las demás se tapan
+        let mut region = vec![vec![usize::MAX; w]; h];
+        let mut tamanos = Vec::new();
+        for (x, y) in celdas_libres(&mapa) {
+            if region[y as usize][x as usize] != usize::MAX { continue; }
+            let id = tamanos.len();
+            let mut n = 0;
+            let mut cola = VecDeque::from([(x, y)]);
+            region[y as usize][x as usize] = id;
+            while let Some((cx, cy)) = cola.pop_front() {
+                n += 1;
+                for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                    let (nx, ny) = (cx + dx, cy + dy);
+                    if celda(&mapa, nx, ny) == Some(' ') && region[ny as usize][nx as usize] == usize::MAX {
+                        region[ny as usize][nx as usize] = id;
+                        cola.push_back((nx, ny));
+                    }
+                }
+            }
+            tamanos.push(n);
+        }
+        let mayor = (0..tamanos.len()).max_by_key(|&i| tamanos[i]);
+        for (x, y) in celdas_libres(&mapa) {
+            if Some(region[y as usize][x as usize]) != mayor {
+                mapa[y as usize][x as usize] = ROCA;
+            }
+        }
+        mapa
+    }
+}
+
+/// Vecinas (de las 8) que no son piso; fuera del mapa cuenta como roca
+fn rocas_vecinas(mapa: &Mapa, x: i32, y: i32) -> usize {
+    let mut n = 0;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if (dx, dy) != (0, 0) && celda(mapa, x + dx, y + dy).is_none_or(|c| c == ROCA || c == MUSGO) {
+                n += 1;
+            }
+        }
+    }
+    n
+}
+
+/// Se queda con las celdas que están a más de `dist` (en ambos ejes) de las ya elegidas
+fn separadas(celdas: &[(i32, i32)], dist: i32) -> Vec<(i32, i32)> {
+    let mut v: Vec<(i32, i32)> = Vec::new();
+    for &(x, y) in celdas {
+        if v.iter().all(|&(a, b)| (a - x).abs().max((b - y).abs()) > dist) {
+            v.push((x, y));
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generacion::asegurar_ganable;
+
+    #[test]
+    fn todas_se_pueden_ganar() {
+        asegurar_ganable(|s| Cueva::default().generar(s));
+    }
+
+    /// La cueva de una semilla no puede cambiar: se comparte por id
+    const FOTO: [&str; 12] = [
+        "66666666663666666666",
+        "6666666636        63",
+        "666666666          6",
+        "666666663     36P  6",
+        "66666666     6336 36",
+        "666666       6666666",
+        "66363     C   636666",
+        "633           C66666",
+        "36  A           3666",
+        "3P    63   A    6666",
+        "6E   6663    C 66666",
+        "66663366363366666666",
+    ];
+
+    #[test]
+    fn foto() {
+        let gen = Cueva { ancho: 20, alto: 12, monedas: 3, antorchas: 2, pozos: 2, ..Default::default() };
+        let filas: Vec<String> = gen.generar(1234).mapa.iter().map(|f| f.iter().collect()).collect();
+        assert_eq!(filas, FOTO);
+    }
+}
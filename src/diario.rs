@@ -0,0 +1,83 @@
+//! Desafío del día: la semilla sale de la fecha (en UTC, así es el mismo día
+//! en todas partes) y el generador va rotando entre laberinto, mazmorra y
+//! cueva. Todo es local: los récords del día se guardan como los de
+//! cualquier nivel, con el id "diario-AAAAMMDD".
+
+use crate::cuevas::Cueva;
+use crate::laberintos::Laberinto;
+use crate::mapas::{Generado, Nivel};
+use crate::mazmorras::Mazmorra;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const TIPO: &str = "diario";
+
+/// Fecha de hoy como AAAAMMDD
+pub fn hoy() -> u64 {
+    let segundos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (a, m, d) = civil((segundos / 86_400) as i64);
+    a as u64 * 10_000 + m as u64 * 100 + d as u64
+}
+
+/// AAAAMMDD → "AAAA-MM-DD"
+pub fn fecha_texto(fecha: u64) -> String {
+    format!("{:04}-{:02}-{:02}", fecha / 10_000, fecha / 100 % 100, fecha % 100)
+}
+
+/// Días desde 1970-01-01 → (año, mes, día), calendario gregoriano
+fn civil(dias: i64) -> (i64, u32, u32) {
+    let z = dias + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let a = yoe + era * 400 + (m <= 2) as i64;
+    (a, m, d)
+}
+
+/// Semilla del generador para el día `fecha`. La fecha se mezcla para que
+/// dos días seguidos no den niveles parecidos.
+fn semilla(fecha: u64) -> u64 {
+    fecha.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 16
+}
+
+/// El nivel del día `fecha` (AAAAMMDD)
+pub fn generar(fecha: u64) -> Nivel {
+    let semilla = semilla(fecha);
+    let mut n = match fecha % 3 {
+        0 => Laberinto::default().generar(semilla),
+        1 => Mazmorra::default().generar(semilla),
+        _ => Cueva::default().generar(semilla),
+    };
+    n.id = format!("{TIPO}-{fecha}");
+    n.generado = Some(Generado { tipo: TIPO, semilla: fecha });
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fechas() {
+        assert_eq!(civil(0), (1970, 1, 1));
+        assert_eq!(civil(11_016), (2000, 2, 29));
+        assert_eq!(civil(20_744), (2026, 10, 18));
+        assert_eq!(fecha_texto(20261018), "2026-10-18");
+    }
+
+    /// El mismo día da el mismo nivel para todos: depende solo de la fecha
+    /// y de generadores que tienen su foto fija (ver sus tests).
+    #[test]
+    fn nivel_del_dia() {
+        assert_eq!(semilla(20261018), 216_925_282_106_250);
+        let n = generar(20261019);
+        assert_eq!(n.id, "diario-20261019");
+        assert_eq!(n.generado, Some(Generado { tipo: TIPO, semilla: 20261019 }));
+        assert_eq!(n.mapa, Laberinto::default().generar(semilla(20261019)).mapa);
+        assert_eq!(generar(20261020).mapa, Mazmorra::default().generar(semilla(20261020)).mapa);
+        assert_eq!(generar(20261018).mapa, Cueva::default().generar(semilla(20261018)).mapa);
+    }
+}
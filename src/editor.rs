@@ -0,0 +1,406 @@
+//! Editor de niveles: el mapa visto desde arriba (como el minimapa, pero
+//! grande), una paleta de celdas y herramientas de pincel, relleno,
+//! rectángulo e inicio. Cada trazo se puede deshacer; el nivel se prueba
+//! desde el cursor sin salir del editor y se guarda en `niveles/<id>.txt`
+//! (ver `mapas`).
+
+use crate::caminos::PELIGROS;
+use crate::fuente::ALTO;
+use crate::idiomas::{Idioma, Mensaje};
+use crate::mandos::Nav;
+use crate::mapas::{Cuando, Disparador, Nivel, Teletransporte};
+use crate::motor::{celda, color_plano, dibujar_plano, es_caminable, linea_bresenham, Framebuffer, Mapa};
+use crate::reglas::Regla;
+use crate::teletransportes::enlazar;
+use raylib::prelude::*;
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_PI_4;
+
+pub const PALETA: [char; 21] = [' ', '1', '2', '3', '4', '5', '6', 'D', 'T', 'X', '_', 'E', 'A', 'C', 'P', 'S', 'L', 'V', 'H', 'B', 'M'];
+const MAX_DESHACER: usize = 100;
+// zona de la grilla dentro del framebuffer
+const GRILLA_X: i32 = 4;
+const GRILLA_Y: i32 = 14;
+const GRILLA_W: i32 = 240;
+const GRILLA_H: i32 = 148;
+const PALETA_X: i32 = 252;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Herramienta {
+    Pincel,
+    Relleno,
+    Rectangulo,
+    Inicio,
+}
+
+impl Herramienta {
+    pub const TODAS: [Herramienta; 4] = [Herramienta::Pincel, Herramienta::Relleno, Herramienta::Rectangulo, Herramienta::Inicio];
+
+    pub fn nombre(self, l: Idioma) -> &'static str {
+        l.t(match self {
+            Herramienta::Pincel => "editor.pincel",
+            Herramienta::Relleno => "editor.relleno",
+            Herramienta::Rectangulo => "editor.rectangulo",
+            Herramienta::Inicio => "editor.inicio",
+        })
+    }
+}
+
+/// Lo que el editor le pide al bucle principal
+pub enum Pedido {
+    Probar(Nivel),
+    Guardar(Nivel),
+    Salir,
+}
+
+/// Estado que se guarda para deshacer
+#[derive(Clone)]
+struct Foto {
+    mapa: Mapa,
+    inicio: (i32, i32, f32),
+}
+
+pub struct Editor {
+    pub id: String,
+    titulo: Option<String>,
+    reglas: Vec<Regla>,
+    disparadores: Vec<Disparador>,
+    teletransportes: Vec<Teletransporte>,
+    mapa: Mapa,
+    inicio: (i32, i32, f32),
+    cursor: (i32, i32),
+    pincel: usize, // índice en `PALETA`
+    herramienta: Herramienta,
+    esquina: Option<(i32, i32)>, // primera esquina del rectángulo
+    trazo: bool,                 // pintando con el botón apretado
+    deshacer: Vec<Foto>,
+    rehacer: Vec<Foto>,
+    cambios: bool, // hay cambios sin guardar
+    pub aviso: Option<Mensaje>,
+}
+
+impl Editor {
+    /// Edita una copia de `n`. Un nivel generado se guarda con otro id, para
+    /// no tapar al generador.
+    pub fn nuevo(n: &Nivel, l: Idioma) -> Self {
+        let (id, titulo) = match n.generado {
+            Some(_) => (format!("{}-editado", n.id), Some(n.nombre(l))),
+            None => (n.id.clone(), n.titulo.clone()),
+        };
+        Self {
+            id,
+            titulo,
+            reglas: n.reglas.clone(),
+            disparadores: n.disparadores.clone(),
+            teletransportes: n.teletransportes.clone(),
+            mapa: n.mapa.clone(),
+            inicio: n.inicio,
+            cursor: (n.inicio.0, n.inicio.1),
+            pincel: 1,
+            herramienta: Herramienta::Pincel,
+            esquina: None,
+            trazo: false,
+            deshacer: Vec::new(),
+            rehacer: Vec::new(),
+            cambios: false,
+            aviso: None,
+        }
+    }
+
+    /// Nivel vacío de `ancho`×`alto` con el borde de pared
+    pub fn vacio(id: String, ancho: usize, alto: usize) -> Self {
+        let mut mapa = vec![vec![' '; ancho]; alto];
+        for (y, fila) in mapa.iter_mut().enumerate() {
+            for (x, c) in fila.iter_mut().enumerate() {
+                if x == 0 || y == 0 || x == ancho - 1 || y == alto - 1 { *c = '1'; }
+            }
+        }
+        let n = Nivel { titulo: Some(id.clone()), id, generado: None, mapa, inicio: (1, 1, 0.0),
+                       reglas: Vec::new(), disparadores: Vec::new(), teletransportes: Vec::new() };
+        Self::nuevo(&n, Idioma::POR_DEFECTO)
+    }
+
+    /// El nivel tal como está; `inicio` reemplaza al punto de inicio (para probar)
+    pub fn nivel(&self, inicio: Option<(i32, i32)>) -> Nivel {
+        let (x, y) = inicio.unwrap_or((self.inicio.0, self.inicio.1));
+        Nivel {
+            id: self.id.clone(),
+            titulo: self.titulo.clone(),
+            generado: None,
+            mapa: self.mapa.clone(),
+            inicio: (x, y, self.inicio.2),
+            reglas: self.reglas.clone(),
+            disparadores: self.disparadores.clone(),
+            teletransportes: self.teletransportes.clone(),
+        }
+    }
+
+    /// Un frame de edición. `usar_abajo`: el botón de usar sigue apretado.
+    pub fn actualizar(&mut self, d: &RaylibHandle, nav: &Nav, usar_abajo: bool) -> Option<Pedido> {
+        let ctrl = d.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || d.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let tecla = |k| d.is_key_pressed(k);
+
+        if ctrl && tecla(KeyboardKey::KEY_Z) { self.deshacer(); }
+        if ctrl && tecla(KeyboardKey::KEY_Y) { self.rehacer(); }
+        if ctrl && tecla(KeyboardKey::KEY_S) {
+            return Some(Pedido::Guardar(self.nivel(None)));
+        }
+        if ctrl { return None; }
+
+        // cursor: flechas (o stick) y mouse sobre la grilla
+        let (w, h) = (self.mapa[0].len() as i32, self.mapa.len() as i32);
+        let (mut cx, mut cy) = self.cursor;
+        cx += nav.derecha as i32 - nav.izquierda as i32;
+        cy += nav.abajo as i32 - nav.arriba as i32;
+        self.cursor = (cx.clamp(0, w - 1), cy.clamp(0, h - 1));
+        let raton = self.celda_en(nav.raton);
+        if let Some(c) = raton.filter(|_| nav.movio || nav.clic) {
+            self.cursor = c;
+        }
+
+        if tecla(KeyboardKey::KEY_Q) { self.pincel = (self.pincel + PALETA.len() - 1) % PALETA.len(); }
+        if tecla(KeyboardKey::KEY_E) { self.pincel = (self.pincel + 1) % PALETA.len(); }
+        // clic en la paleta
+        if nav.clic && (PALETA_X..PALETA_X + 20).contains(&(nav.raton.x as i32)) {
+            if let Some(i) = nav.fila(GRILLA_Y, 7, PALETA.len()) { self.pincel = i; }
+        }
+        let teclas = [KeyboardKey::KEY_ONE, KeyboardKey::KEY_TWO, KeyboardKey::KEY_THREE, KeyboardKey::KEY_FOUR];
+        for (k, h) in teclas.into_iter().zip(Herramienta::TODAS) {
+            if tecla(k) {
+                self.herramienta = h;
+                self.esquina = None;
+            }
+        }
+        if tecla(KeyboardKey::KEY_R) {
+            self.marcar();
+            self.inicio.2 = ((self.inicio.2 / FRAC_PI_4).round() + 1.0).rem_euclid(8.0) * FRAC_PI_4;
+        }
+
+        // el pincel pinta mientras el botón sigue apretado; lo demás, al apretar
+        if self.herramienta == Herramienta::Pincel {
+            let raton_abajo = d.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) && raton.is_some();
+            if usar_abajo || raton_abajo {
+                if !self.trazo { self.marcar(); }
+                self.trazo = true;
+                self.aplicar();
+            } else {
+                self.trazo = false;
+            }
+        } else if nav.usar || (nav.clic && raton.is_some()) {
+            self.aplicar();
+        }
+
+        if tecla(KeyboardKey::KEY_P) {
+            let (x, y) = self.cursor;
+            if !celda(&self.mapa, x, y).is_some_and(es_caminable) || PELIGROS.contains(&self.mapa[y as usize][x as usize]) {
+                self.aviso = Some(Mensaje::nuevo("editor.no_caminable"));
+                return None;
+            }
+            return Some(Pedido::Probar(self.nivel(Some(self.cursor))));
+        }
+        if nav.volver {
+            if self.cambios {
+                // el primer intento solo avisa
+                self.cambios = false;
+                self.aviso = Some(Mensaje::nuevo("editor.sin_guardar"));
+            } else {
+                return Some(Pedido::Salir);
+            }
+        }
+        None
+    }
+
+    /// Resultado de un `Pedido::Guardar`: los cambios quedan guardados solo
+    /// si se pudo escribir el archivo.
+    pub fn guardado(&mut self, ok: bool, aviso: Mensaje) {
+        self.cambios &= !ok;
+        self.aviso = Some(aviso);
+    }
+
+    /// Guarda una foto para deshacer (una por trazo o acción)
+    fn marcar(&mut self) {
+        self.aviso = None;
+        self.deshacer.push(Foto { mapa: self.mapa.clone(), inicio: self.inicio });
+        if self.deshacer.len() > MAX_DESHACER { self.deshacer.remove(0); }
+        self.rehacer.clear();
+        self.cambios = true;
+    }
+
+    fn deshacer(&mut self) {
+        if let Some(f) = self.deshacer.pop() {
+            let actual = Foto { mapa: std::mem::replace(&mut self.mapa, f.mapa), inicio: self.inicio };
+            self.inicio = f.inicio;
+            self.rehacer.push(actual);
+            self.cambios = true;
+        }
+    }
+
+    fn rehacer(&mut self) {
+        if let Some(f) = self.rehacer.pop() {
+            let actual = Foto { mapa: std::mem::replace(&mut self.mapa, f.mapa), inicio: self.inicio };
+            self.inicio = f.inicio;
+            self.deshacer.push(actual);
+            self.cambios = true;
+        }
+    }
+
+    /// La herramienta elegida en la celda del cursor
+    fn aplicar(&mut self) {
+        let c = PALETA[self.pincel];
+        let (x, y) = self.cursor;
+        match self.herramienta {
+            Herramienta::Pincel => {
+                self.cambios |= self.mapa[y as usize][x as usize] != c;
+                self.mapa[y as usize][x as usize] = c;
+            }
+            Herramienta::Relleno => {
+                let antes = self.mapa[y as usize][x as usize];
+                if antes == c { return; }
+                self.marcar();
+                // relleno por inundación (4 vecinas) de la región del mismo carácter
+                let mut cola = VecDeque::from([(x, y)]);
+                self.mapa[y as usize][x as usize] = c;
+                while let Some((cx, cy)) = cola.pop_front() {
+                    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                        let (nx, ny) = (cx + dx, cy + dy);
+                        if celda(&self.mapa, nx, ny) == Some(antes) {
+                            self.mapa[ny as usize][nx as usize] = c;
+                            cola.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+            Herramienta::Rectangulo => match self.esquina.take() {
+                None => self.esquina = Some((x, y)),
+                Some((ex, ey)) => {
+                    self.marcar();
+                    for fila in &mut self.mapa[y.min(ey) as usize..=y.max(ey) as usize] {
+                        fila[x.min(ex) as usize..=x.max(ex) as usize].fill(c);
+                    }
+                }
+            },
+            Herramienta::Inicio => {
+                self.marcar();
+                self.inicio.0 = x;
+                self.inicio.1 = y;
+            }
+        }
+    }
+
+    /// Lado de cada celda en píxeles, para que el mapa entre en la grilla
+    fn lado(&self) -> i32 {
+        (GRILLA_W / self.mapa[0].len() as i32).min(GRILLA_H / self.mapa.len() as i32).max(1)
+    }
+
+    /// Celda bajo un punto del framebuffer
+    fn celda_en(&self, p: Vector2) -> Option<(i32, i32)> {
+        let s = self.lado();
+        let (x, y) = ((p.x as i32 - GRILLA_X).div_euclid(s), (p.y as i32 - GRILLA_Y).div_euclid(s));
+        celda(&self.mapa, x, y).map(|_| (x, y))
+    }
+
+    pub fn dibujar(&self, fb: &mut Framebuffer, l: Idioma) {
+        let s = self.lado();
+        let (gx, gy) = (GRILLA_X, GRILLA_Y);
+        let titulo = self.titulo.clone().unwrap_or_else(|| l.t(&format!("nivel.{}", self.id)).to_string());
+        let marca = if self.cambios { " *" } else { "" };
+        fb.texto(4, 2, &l.f("editor.titulo", &[&titulo, &marca]), Color::RAYWHITE);
+        dibujar_plano(fb, &self.mapa, gx, gy, s, color_celda);
+        for d in &self.disparadores {
+            let col = if d.cuando == Cuando::Entrar { Color::VIOLET } else { Color::ORANGE };
+            marco(fb, gx + d.x * s, gy + d.y * s, d.w * s, d.h * s, col);
+        }
+        // teletransportadores: línea de cada 'T' a su destino (los pares se ven al pintar)
+        for t in enlazar(&self.mapa, &self.teletransportes) {
+            let centro = |x: i32, y: i32| (gx + x * s + s / 2, gy + y * s + s / 2);
+            let ((x0, y0), (x1, y1)) = (centro(t.x, t.y), centro(t.dx, t.dy));
+            linea_bresenham(fb, x0, y0, x1, y1, Color::MAGENTA);
+        }
+
+        // inicio: punto y hacia dónde mira
+        let (ix, iy) = (gx + self.inicio.0 * s + s / 2, gy + self.inicio.1 * s + s / 2);
+        let largo = s.max(4) as f32;
+        linea_bresenham(fb, ix, iy, ix + (self.inicio.2.cos() * largo) as i32, iy + (self.inicio.2.sin() * largo) as i32, Color::WHITE);
+        marco(fb, ix - 1, iy - 1, 3, 3, Color::WHITE);
+
+        // cursor y rectángulo en curso
+        let (cx, cy) = self.cursor;
+        if let Some((ex, ey)) = self.esquina {
+            let (x0, y0) = (cx.min(ex), cy.min(ey));
+            marco(fb, gx + x0 * s, gy + y0 * s, ((cx - ex).abs() + 1) * s, ((cy - ey).abs() + 1) * s, Color::SKYBLUE);
+        }
+        marco(fb, gx + cx * s, gy + cy * s, s, s, Color::YELLOW);
+
+        // paleta
+        for (i, &c) in PALETA.iter().enumerate() {
+            let y = gy + 7 * i as i32;
+            for yy in 0..6 {
+                for xx in 0..6 { fb.set(PALETA_X + xx, y + yy, color_celda(c)); }
+            }
+            if i == self.pincel { marco(fb, PALETA_X - 1, y - 1, 8, 8, Color::YELLOW); }
+            let simbolo = if c == ' ' { "·".to_string() } else { c.to_string() };
+            let col = if i == self.pincel { Color::YELLOW } else { Color::GRAY };
+            fb.texto(PALETA_X + 10, y - 1, &simbolo, col);
+        }
+
+        // estado y ayuda
+        let bajo = celda(&self.mapa, cx, cy).unwrap_or(' ');
+        let estado = l.f("editor.estado", &[&self.herramienta.nombre(l), &nombre_celda(PALETA[self.pincel], l),
+                                            &cx, &cy, &nombre_celda(bajo, l)]);
+        let y = GRILLA_Y + GRILLA_H + 2;
+        fb.texto(4, y, &estado, Color::LIGHTGRAY);
+        match &self.aviso {
+            Some(a) => fb.texto(4, y + ALTO, &a.texto(l), Color::ORANGE),
+            None => fb.texto(4, y + ALTO, l.t("editor.ayuda1"), Color::GRAY),
+        }
+        fb.texto(4, y + 2 * ALTO, l.t("editor.ayuda2"), Color::GRAY);
+    }
+}
+
+/// Las paredes con el color de su textura; lo demás como en el minimapa
+fn color_celda(c: char) -> Color {
+    match c {
+        '1' => Color::BLUE,
+        '2' => Color::RED,
+        '3' => Color::GREEN,
+        '4' => Color::BROWN,
+        '5' => Color::PURPLE,
+        '6' => Color::GRAY,
+        'M' => Color::MAROON,
+        'D' => Color::DARKBROWN, // no confundir con la pared '4'
+        _ => color_plano(c),
+    }
+}
+
+fn nombre_celda(c: char, l: Idioma) -> String {
+    match c {
+        '1'..='6' => l.f("editor.pared", &[&c]),
+        ' ' => l.t("editor.piso").to_string(),
+        'E' => l.t("editor.salida").to_string(),
+        'A' => l.t("editor.antorcha").to_string(),
+        'C' => l.t("editor.moneda").to_string(),
+        'P' => l.t("editor.pozo").to_string(),
+        'S' => l.t("editor.pinchos").to_string(),
+        'L' => l.t("editor.lava").to_string(),
+        'V' => l.t("editor.veneno").to_string(),
+        'H' => l.t("editor.botiquin").to_string(),
+        'B' => l.t("editor.municion").to_string(),
+        'D' => l.t("editor.puerta").to_string(),
+        'T' => l.t("editor.teletransportador").to_string(),
+        'X' => l.t("editor.bloque").to_string(),
+        '_' => l.t("editor.placa").to_string(),
+        _ => l.t("editor.enemigo").to_string(),
+    }
+}
+
+/// Borde de un rectángulo de 1 px
+fn marco(fb: &mut Framebuffer, x: i32, y: i32, w: i32, h: i32, c: Color) {
+    for i in 0..w {
+        fb.set(x + i, y, c);
+        fb.set(x + i, y + h - 1, c);
+    }
+    for j in 0..h {
+        fb.set(x, y + j, c);
+        fb.set(x + w - 1, y + j, c);
+    }
+}
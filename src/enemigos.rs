@@ -0,0 +1,262 @@
+//! Enemigos (M): máquina de estados simple.
+//! Ven al jugador con `linea_de_vision` (mismo DDA que las paredes) y se
+//! mueven con colisiones igual que el jugador. Cuando lo pierden de vista
+//! van a la última posición conocida con A* (ver `caminos`).
+
+use crate::caminos::*;
+use crate::motor::*;
+use crate::sprites::{Sprite, SpriteKind};
+use raylib::prelude::*;
+
+const VISTA: f32 = 7.0;           // celdas de alcance de la vista
+const ALCANCE_ATAQUE: f32 = 0.7;  // distancia para golpear
+const VEL_PATRULLA: f32 = 0.8 / 60.0;
+const VEL_PERSECUCION: f32 = 1.4 / 60.0;
+const ESPERA_ATAQUE: u32 = 30;    // frames de "carga" antes de golpear
+const DURACION_HUIDA: u32 = 90;
+const VIDA: i32 = 100;
+pub const RADIO: f32 = 0.3;       // círculo de colisión para disparos
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EstadoIA {
+    Quieto,
+    Patrulla,
+    Persecucion,
+    Ataque,
+    Huida,
+}
+
+const ESTADOS: [EstadoIA; 5] =
+    [EstadoIA::Quieto, EstadoIA::Patrulla, EstadoIA::Persecucion, EstadoIA::Ataque, EstadoIA::Huida];
+
+#[derive(Clone)]
+pub struct Enemigo {
+    pub x: f32,
+    pub y: f32,
+    pub ang: f32,
+    pub estado: EstadoIA,
+    pub vida: i32,
+    t_estado: u32,                // frames en el estado actual
+    ult_visto: Option<(f32, f32)>, // última posición conocida del jugador
+    ruta: Vec<(f32, f32)>,         // puntos pendientes hacia `ult_visto`
+}
+
+impl Enemigo {
+    pub fn nuevo(x: f32, y: f32) -> Self {
+        Self { x, y, ang: 0.0, estado: EstadoIA::Quieto, vida: VIDA, t_estado: 0, ult_visto: None, ruta: Vec::new() }
+    }
+
+    fn cambiar(&mut self, e: EstadoIA) {
+        if self.estado != e {
+            self.estado = e;
+            self.t_estado = 0;
+            self.ruta.clear();
+        }
+    }
+
+    /// Recibe un disparo: con poca vida huye, si no se pone en alerta.
+    pub fn herir(&mut self, danio: i32, desde: (f32, f32)) {
+        self.vida -= danio;
+        if self.vida <= VIDA / 3 {
+            self.cambiar(EstadoIA::Huida);
+        } else if self.estado != EstadoIA::Ataque {
+            self.ult_visto = Some(desde);
+            self.cambiar(EstadoIA::Persecucion);
+        }
+    }
+
+    /// Avanza hacia (tx, ty). Devuelve false si no pudo moverse (pared).
+    fn avanzar_hacia(&mut self, tx: f32, ty: f32, vel: f32, mapa: &Mapa) -> bool {
+        let dx = tx - self.x;
+        let dy = ty - self.y;
+        let len = (dx * dx + dy * dy).sqrt();
+        if len < 0.01 { return false; }
+        self.ang = dy.atan2(dx);
+        let (ox, oy) = (self.x, self.y);
+        mover_entidad(&mut self.x, &mut self.y, dx / len * vel, dy / len * vel, mapa);
+        // no se meten en peligros (pozos...)
+        if !transitable(mapa, self.x.floor() as i32, self.y.floor() as i32, true) {
+            self.x = ox;
+            self.y = oy;
+        }
+        (self.x - ox).abs() + (self.y - oy).abs() > 0.0001
+    }
+
+    /// Sigue `ruta` (calculándola si hace falta). Devuelve false al terminarla
+    /// o si no hay camino.
+    fn seguir_ruta(&mut self, destino: (f32, f32), mapa: &Mapa) -> bool {
+        if self.ruta.is_empty() {
+            let desde = (self.x.floor() as i32, self.y.floor() as i32);
+            let hasta = (destino.0.floor() as i32, destino.1.floor() as i32);
+            match buscar_camino(mapa, desde, hasta, true) {
+                Some(c) => {
+                    self.ruta = suavizar_camino(mapa, &c, true);
+                    self.ruta.remove(0); // la celda donde ya estamos
+                }
+                None => return false,
+            }
+        }
+        let Some(&(tx, ty)) = self.ruta.first() else { return false; };
+        if ((tx - self.x).powi(2) + (ty - self.y).powi(2)).sqrt() < 0.1
+            || !self.avanzar_hacia(tx, ty, VEL_PERSECUCION, mapa)
+        {
+            self.ruta.remove(0);
+        }
+        !self.ruta.is_empty()
+    }
+
+    /// Estado completo en una línea (para guardar la partida):
+    /// `x y ang estado vida t_estado visto_x visto_y n_ruta [x y]...`
+    pub fn a_texto(&self) -> String {
+        let visto = match self.ult_visto {
+            Some((vx, vy)) => format!("{vx} {vy}"),
+            None => "- -".to_string(),
+        };
+        let mut s = format!("{} {} {} {:?} {} {} {visto} {}",
+                            self.x, self.y, self.ang, self.estado, self.vida, self.t_estado, self.ruta.len());
+        for (rx, ry) in &self.ruta { s += &format!(" {rx} {ry}"); }
+        s
+    }
+
+    /// Inverso de `a_texto`
+    pub fn desde_texto(txt: &str) -> Option<Self> {
+        let c: Vec<&str> = txt.split_whitespace().collect();
+        if c.len() < 9 { return None; }
+        let f = |i: usize| c[i].parse::<f32>().ok();
+        let estado = *ESTADOS.iter().find(|e| format!("{e:?}") == c[3])?;
+        let ult_visto = if c[6] == "-" { None } else { Some((f(6)?, f(7)?)) };
+        let n: usize = c[8].parse().ok()?;
+        if c.len() != 9 + 2 * n { return None; }
+        let ruta = (0..n).map(|k| Some((f(9 + 2 * k)?, f(10 + 2 * k)?))).collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            x: f(0)?, y: f(1)?, ang: f(2)?, estado,
+            vida: c[4].parse().ok()?,
+            t_estado: c[5].parse().ok()?,
+            ult_visto, ruta,
+        })
+    }
+
+    /// Un paso de IA. Devuelve true si golpeó al jugador en este frame.
+    /// `campo` es la distancia al jugador, para huir cuesta arriba.
+    pub fn actualizar(&mut self, j: &Jugador, mapa: &Mapa, campo: &CampoDistancias) -> bool {
+        self.t_estado += 1;
+        let dist = ((j.x - self.x).powi(2) + (j.y - self.y).powi(2)).sqrt();
+        let lo_ve = dist <= VISTA && linea_de_vision(mapa, self.x, self.y, j.x, j.y);
+        if lo_ve { self.ult_visto = Some((j.x, j.y)); }
+
+        match self.estado {
+            EstadoIA::Quieto => {
+                if lo_ve { self.cambiar(EstadoIA::Persecucion); }
+                else if self.t_estado > 90 { self.cambiar(EstadoIA::Patrulla); }
+            }
+            EstadoIA::Patrulla => {
+                if lo_ve {
+                    self.cambiar(EstadoIA::Persecucion);
+                } else {
+                    let (tx, ty) = (self.x + self.ang.cos(), self.y + self.ang.sin());
+                    let ang = self.ang;
+                    if !self.avanzar_hacia(tx, ty, VEL_PATRULLA, mapa) {
+                        // pared: girar 90° y seguir
+                        self.ang = ang + std::f32::consts::FRAC_PI_2;
+                    }
+                    if self.t_estado > 240 { self.cambiar(EstadoIA::Quieto); }
+                }
+            }
+            EstadoIA::Persecucion => {
+                if dist <= ALCANCE_ATAQUE {
+                    self.cambiar(EstadoIA::Ataque);
+                } else if lo_ve {
+                    // a la vista: directo hacia él
+                    self.ruta.clear();
+                    if !self.avanzar_hacia(j.x, j.y, VEL_PERSECUCION, mapa) {
+                        // trabado en una esquina: bajar por el campo de distancias
+                        if let Some((cx, cy)) = campo.hacia_origen(self.x.floor() as i32, self.y.floor() as i32) {
+                            self.avanzar_hacia(cx as f32 + 0.5, cy as f32 + 0.5, VEL_PERSECUCION, mapa);
+                        }
+                    }
+                } else if let Some(destino) = self.ult_visto {
+                    if !self.seguir_ruta(destino, mapa) {
+                        // llegamos a donde lo vimos por última vez y no está
+                        self.ult_visto = None;
+                        self.cambiar(EstadoIA::Patrulla);
+                    }
+                } else {
+                    self.cambiar(EstadoIA::Patrulla);
+                }
+            }
+            EstadoIA::Ataque => {
+                if dist > ALCANCE_ATAQUE {
+                    self.cambiar(EstadoIA::Persecucion);
+                } else if self.t_estado >= ESPERA_ATAQUE {
+                    // golpe y retirada
+                    self.cambiar(EstadoIA::Huida);
+                    return true;
+                }
+            }
+            EstadoIA::Huida => {
+                // subir por el campo de distancias; si no hay, alejarse en línea recta
+                let (tx, ty) = match campo.lejos_de_origen(self.x.floor() as i32, self.y.floor() as i32) {
+                    Some((cx, cy)) => (cx as f32 + 0.5, cy as f32 + 0.5),
+                    None => (2.0 * self.x - j.x, 2.0 * self.y - j.y),
+                };
+                self.avanzar_hacia(tx, ty, VEL_PERSECUCION, mapa);
+                // heridos de gravedad no dejan de huir
+                if self.t_estado > DURACION_HUIDA && self.vida > VIDA / 3 { self.cambiar(EstadoIA::Quieto); }
+            }
+        }
+        false
+    }
+}
+
+pub struct Enemigos {
+    pub lista: Vec<Enemigo>,
+}
+
+impl Enemigos {
+    pub fn nuevo() -> Self {
+        Self { lista: Vec::new() }
+    }
+
+    /// Crea un enemigo por cada 'M' del mapa.
+    pub fn rellenar_desde_mapa(&mut self, mapa: &Mapa) {
+        self.lista.clear();
+        for y in 0..mapa.len() as i32 {
+            for x in 0..mapa[0].len() as i32 {
+                if celda(mapa, x, y) == Some('M') {
+                    self.lista.push(Enemigo::nuevo(x as f32 + 0.5, y as f32 + 0.5));
+                }
+            }
+        }
+    }
+
+    /// Actualiza la IA de todos. Devuelve cuántos golpearon al jugador.
+    pub fn actualizar(&mut self, j: &Jugador, mapa: &Mapa) -> usize {
+        if self.lista.is_empty() { return 0; }
+        let campo = CampoDistancias::calcular(mapa, (j.x.floor() as i32, j.y.floor() as i32), true);
+        let mut golpes = 0;
+        for e in &mut self.lista {
+            if e.actualizar(j, mapa, &campo) { golpes += 1; }
+        }
+        golpes
+    }
+
+    /// Quita los enemigos sin vida. Devuelve cuántos murieron.
+    pub fn quitar_muertos(&mut self) -> usize {
+        let antes = self.lista.len();
+        self.lista.retain(|e| e.vida > 0);
+        antes - self.lista.len()
+    }
+
+    /// Sprites para `dibujar_sprites` (posición actual de cada enemigo).
+    pub fn sprites(&self) -> Vec<Sprite> {
+        self.lista.iter()
+            .map(|e| Sprite { x: e.x, y: e.y, kind: SpriteKind::Enemy })
+            .collect()
+    }
+
+    pub fn dibujar_en_minimapa(&self, fb: &mut Framebuffer) {
+        for e in &self.lista {
+            marcar_minimapa(fb, e.x, e.y, Color::MAGENTA);
+        }
+    }
+}
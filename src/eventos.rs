@@ -0,0 +1,297 @@
+//! Disparadores y eventos. Un disparador es un rectángulo de celdas del
+//! nivel con una lista de acciones separadas por ';' que se ejecutan cada
+//! vez que el jugador entra en él (o, según su `Cuando`, cada vez que sus
+//! placas de presión quedan todas apretadas o dejan de estarlo):
+//!
+//!   mensaje <texto>            muestra el texto en pantalla
+//!   abrir X Y / cerrar X Y     abre o cierra la puerta 'D' de esa celda
+//!   enemigo X Y                hace aparecer un enemigo
+//!   teletransportar X Y [G]    lleva al jugador a la celda (mirando a G grados)
+//!   sonido <nombre>            reproduce `sonidos/<nombre>.wav`
+//!   ganar / perder             termina el nivel
+//!   poner V N / sumar V N      cambia la variable V (empiezan en 0)
+//!   si V <op> N <acción>       la acción solo si se cumple (=, !=, <, >, <=, >=)
+//!   apagar                     el disparador no vuelve a funcionar
+//!
+//! Por ejemplo: `sumar palancas 1; si palancas = 2 abrir 5 3; apagar`.
+//! El estado (variables, quién está adentro, quién se apagó) es parte de la
+//! partida: se guarda con ella y entra en la huella de las repeticiones.
+
+use crate::mapas::{Cuando, Disparador};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparacion {
+    Igual,
+    Distinto,
+    Menor,
+    Mayor,
+    MenorIgual,
+    MayorIgual,
+}
+
+impl Comparacion {
+    fn desde_texto(s: &str) -> Option<Self> {
+        Some(match s {
+            "=" | "==" => Comparacion::Igual,
+            "!=" => Comparacion::Distinto,
+            "<" => Comparacion::Menor,
+            ">" => Comparacion::Mayor,
+            "<=" => Comparacion::MenorIgual,
+            ">=" => Comparacion::MayorIgual,
+            _ => return None,
+        })
+    }
+
+    fn cumple(self, a: i32, b: i32) -> bool {
+        match self {
+            Comparacion::Igual => a == b,
+            Comparacion::Distinto => a != b,
+            Comparacion::Menor => a < b,
+            Comparacion::Mayor => a > b,
+            Comparacion::MenorIgual => a <= b,
+            Comparacion::MayorIgual => a >= b,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Orden {
+    Mensaje(String),
+    Abrir(i32, i32),
+    Cerrar(i32, i32),
+    Enemigo(i32, i32),
+    Teletransportar(i32, i32, Option<f32>), // ángulo en radianes
+    Sonido(String),
+    Ganar,
+    Perder,
+    Poner(String, i32),
+    Sumar(String, i32),
+    Si(String, Comparacion, i32, Box<Orden>),
+    Apagar,
+}
+
+impl Orden {
+    /// Si abre la puerta de esa celda (directo o dentro de un `si`)
+    pub fn abre(&self, x: i32, y: i32) -> bool {
+        match self {
+            Orden::Abrir(ax, ay) => (*ax, *ay) == (x, y),
+            Orden::Si(_, _, _, o) => o.abre(x, y),
+            _ => false,
+        }
+    }
+}
+
+/// Lee las acciones de un disparador
+pub fn parsear(acciones: &str) -> Result<Vec<Orden>, String> {
+    acciones.split(';')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| orden(a).ok_or_else(|| format!("acción inválida '{a}'")))
+        .collect()
+}
+
+/// Lo que queda de `a` después de sus primeras `k` palabras, tal cual
+pub fn resto(a: &str, k: usize) -> &str {
+    (0..k).fold(a.trim_start(), |r, _| r.trim_start_matches(|c: char| !c.is_whitespace()).trim_start())
+}
+
+fn orden(a: &str) -> Option<Orden> {
+    let p: Vec<&str> = a.split_whitespace().collect();
+    let n = |k: usize| p.get(k).and_then(|t| t.parse::<i32>().ok());
+    let var = || p.get(1).filter(|v| v.chars().all(|c| c.is_alphanumeric() || c == '_')).map(|v| v.to_string());
+    let exacto = |k: usize| (p.len() == k).then_some(());
+    Some(match *p.first()? {
+        "mensaje" if p.len() > 1 => Orden::Mensaje(resto(a, 1).to_string()),
+        "abrir" => { exacto(3)?; Orden::Abrir(n(1)?, n(2)?) }
+        "cerrar" => { exacto(3)?; Orden::Cerrar(n(1)?, n(2)?) }
+        "enemigo" => { exacto(3)?; Orden::Enemigo(n(1)?, n(2)?) }
+        "teletransportar" => match p.len() {
+            3 => Orden::Teletransportar(n(1)?, n(2)?, None),
+            4 => Orden::Teletransportar(n(1)?, n(2)?, Some((n(3)? as f32).to_radians())),
+            _ => return None,
+        },
+        "sonido" => { exacto(2)?; Orden::Sonido(p[1].to_string()) }
+        "ganar" => { exacto(1)?; Orden::Ganar }
+        "perder" => { exacto(1)?; Orden::Perder }
+        "poner" => { exacto(3)?; Orden::Poner(var()?, n(2)?) }
+        "sumar" => { exacto(3)?; Orden::Sumar(var()?, n(2)?) }
+        "si" if p.len() > 4 => Orden::Si(var()?, Comparacion::desde_texto(p[2])?, n(3)?, Box::new(orden(resto(a, 4))?)),
+        "apagar" => { exacto(1)?; Orden::Apagar }
+        _ => return None,
+    })
+}
+
+/// Un disparador en juego
+#[derive(Clone)]
+pub struct Zona {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    cuando: Cuando,
+    ordenes: Vec<Orden>,
+    pub dentro: bool, // el frame anterior el jugador estaba adentro (o las placas apretadas)
+    pub activa: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct Eventos {
+    pub zonas: Vec<Zona>,
+    pub vars: BTreeMap<String, i32>,
+    pub mensajes: Vec<String>, // para la pantalla; los saca `main`
+    pub sonidos: Vec<String>,  // ídem
+}
+
+impl Eventos {
+    /// Una zona por disparador, en el mismo orden. Las acciones ya se
+    /// validaron al cargar el nivel; si alguna no se entiende queda vacía.
+    pub fn nuevo(ds: &[Disparador]) -> Self {
+        let zonas = ds.iter()
+            .map(|d| Zona {
+                x: d.x,
+                y: d.y,
+                w: d.w,
+                h: d.h,
+                cuando: d.cuando,
+                ordenes: parsear(&d.acciones).unwrap_or_default(),
+                dentro: false,
+                activa: true,
+            })
+            .collect();
+        Self { zonas, ..Default::default() }
+    }
+
+    /// Órdenes (con el índice de su zona) de las zonas que se disparan
+    /// este frame, con el jugador en la celda `x`, `y`. `apretadas(x, y, w, h)`
+    /// dice si todas las placas de ese rectángulo tienen un bloque encima.
+    pub fn revisar(&mut self, x: i32, y: i32, apretadas: impl Fn(i32, i32, i32, i32) -> bool) -> Vec<(usize, Orden)> {
+        let mut v = Vec::new();
+        for (i, z) in self.zonas.iter_mut().enumerate() {
+            let ahora = match z.cuando {
+                Cuando::Entrar => x >= z.x && y >= z.y && x < z.x + z.w && y < z.y + z.h,
+                Cuando::Apretar | Cuando::Soltar => apretadas(z.x, z.y, z.w, z.h),
+            };
+            let dispara = match z.cuando {
+                Cuando::Entrar | Cuando::Apretar => ahora && !z.dentro,
+                Cuando::Soltar => !ahora && z.dentro,
+            };
+            if dispara && z.activa {
+                v.extend(z.ordenes.iter().map(|o| (i, o.clone())));
+            }
+            z.dentro = ahora;
+        }
+        v
+    }
+
+    /// Resuelve lo que no toca la partida (variables, `si`, mensajes,
+    /// sonidos, `apagar`); devuelve la orden que queda por ejecutar, si hay.
+    pub fn resolver(&mut self, zona: usize, o: Orden) -> Option<Orden> {
+        match o {
+            Orden::Poner(v, n) => { self.vars.insert(v, n); None }
+            Orden::Sumar(v, n) => { *self.vars.entry(v).or_insert(0) += n; None }
+            Orden::Si(v, cmp, n, o) => {
+                let actual = self.vars.get(&v).copied().unwrap_or(0);
+                if cmp.cumple(actual, n) { self.resolver(zona, *o) } else { None }
+            }
+            Orden::Apagar => { self.zonas[zona].activa = false; None }
+            Orden::Mensaje(t) => { self.mensajes.push(t); None }
+            Orden::Sonido(s) => { self.sonidos.push(s); None }
+            otra => Some(otra),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zona(x: i32, y: i32, w: i32, h: i32, cuando: Cuando, acciones: &str) -> Disparador {
+        Disparador { x, y, w, h, cuando, acciones: acciones.to_string() }
+    }
+
+    /// Corre un frame como la partida: revisar y resolver lo que sale
+    fn frame(ev: &mut Eventos, x: i32, y: i32, apretadas: bool) -> Vec<Orden> {
+        let ordenes = ev.revisar(x, y, |_, _, _, _| apretadas);
+        ordenes.into_iter().filter_map(|(i, o)| ev.resolver(i, o)).collect()
+    }
+
+    #[test]
+    fn acciones_validas() {
+        let v = parsear("mensaje  Hola,   mundo ; abrir 3 4;cerrar 3 4; enemigo 1 2; teletransportar 5 6; \
+                         teletransportar 5 6 90; sonido campana; ganar; perder; poner a 2; sumar a -1; apagar;;").unwrap();
+        assert_eq!(v, [
+            Orden::Mensaje("Hola,   mundo".to_string()),
+            Orden::Abrir(3, 4),
+            Orden::Cerrar(3, 4),
+            Orden::Enemigo(1, 2),
+            Orden::Teletransportar(5, 6, None),
+            Orden::Teletransportar(5, 6, Some(90f32.to_radians())),
+            Orden::Sonido("campana".to_string()),
+            Orden::Ganar,
+            Orden::Perder,
+            Orden::Poner("a".to_string(), 2),
+            Orden::Sumar("a".to_string(), -1),
+            Orden::Apagar,
+        ]);
+        assert_eq!(parsear("  ").unwrap(), []);
+    }
+
+    #[test]
+    fn acciones_invalidas() {
+        for a in ["mensaje", "abrir 3", "abrir 3 4 5", "abrir x 4", "teletransportar 1", "teletransportar 1 2 3 4",
+                  "sonido", "sonido a b", "ganar ya", "poner a", "poner a-b 1", "sumar a uno", "si a = 1",
+                  "si a ~ 1 ganar", "si a = 1 volar", "apagar todo", "saltar"] {
+            let e = parsear(&format!("ganar; {a}")).unwrap_err();
+            assert_eq!(e, format!("acción inválida '{a}'"));
+        }
+    }
+
+    #[test]
+    fn si_anidado() {
+        let v = parsear("si a >= 2 si b != 0 mensaje  listo  ya").unwrap();
+        let dentro = Orden::Si("b".to_string(), Comparacion::Distinto, 0, Box::new(Orden::Mensaje("listo  ya".to_string())));
+        assert_eq!(v, [Orden::Si("a".to_string(), Comparacion::MayorIgual, 2, Box::new(dentro))]);
+        assert!(parsear("si a = 1 si b = 2 abrir 4 5").unwrap()[0].abre(4, 5));
+
+        let mut ev = Eventos::nuevo(&[zona(0, 0, 1, 1, Cuando::Entrar, "sumar a 1; si a >= 2 si b = 0 abrir 4 5")]);
+        assert_eq!(frame(&mut ev, 0, 0, false), []);
+        frame(&mut ev, 1, 0, false);
+        assert_eq!(frame(&mut ev, 0, 0, false), [Orden::Abrir(4, 5)]);
+        ev.vars.insert("b".to_string(), 1);
+        frame(&mut ev, 1, 0, false);
+        assert_eq!(frame(&mut ev, 0, 0, false), []);
+    }
+
+    #[test]
+    fn apagar() {
+        let mut ev = Eventos::nuevo(&[zona(2, 2, 1, 1, Cuando::Entrar, "mensaje hola; apagar; ganar")]);
+        // el resto de las acciones de esa vez sí se ejecutan
+        assert_eq!(frame(&mut ev, 2, 2, false), [Orden::Ganar]);
+        assert_eq!(ev.mensajes, ["hola"]);
+        assert!(!ev.zonas[0].activa);
+        frame(&mut ev, 3, 2, false);
+        assert_eq!(frame(&mut ev, 2, 2, false), []);
+        assert_eq!(ev.mensajes.len(), 1);
+    }
+
+    #[test]
+    fn dispara_solo_en_el_flanco() {
+        let mut ev = Eventos::nuevo(&[
+            zona(1, 1, 2, 2, Cuando::Entrar, "sumar e 1"),
+            zona(5, 5, 1, 1, Cuando::Apretar, "sumar a 1"),
+            zona(5, 5, 1, 1, Cuando::Soltar, "sumar s 1"),
+        ]);
+        let var = |ev: &Eventos, v: &str| ev.vars.get(v).copied().unwrap_or(0);
+        // entrar: una vez al pasar de afuera a adentro, no mientras se queda
+        for (x, y) in [(0, 0), (1, 1), (2, 1), (2, 2), (3, 2), (2, 2)] {
+            frame(&mut ev, x, y, false);
+        }
+        assert_eq!(var(&ev, "e"), 2);
+        // apretar y soltar: cuando las placas cambian, no en cada frame
+        for apretadas in [false, true, true, false, false, true] {
+            frame(&mut ev, 0, 0, apretadas);
+        }
+        assert_eq!((var(&ev, "a"), var(&ev, "s")), (2, 1));
+    }
+}
@@ -0,0 +1,61 @@
+//! Fantasma: el mejor recorrido del nivel, como sprite translúcido que
+//! avanza a la par del jugador. Es la grabación de esa corrida (ver
+//! `repeticion`) pasada por la simulación al entrar al nivel; de ahí salen
+//! la posición en cada frame y los parciales (frame de cada moneda).
+
+use crate::guardado::directorio_datos;
+use crate::mapas::Nivel;
+use crate::motor::*;
+use crate::partida::*;
+use crate::particulas::Particulas;
+use crate::repeticion::Grabacion;
+use crate::sprites::{Sprite, SpriteKind};
+use raylib::prelude::*;
+use std::path::PathBuf;
+
+pub struct Fantasma {
+    recorrido: Vec<(f32, f32)>, // posición después de cada frame
+    pub parciales: Vec<u32>,    // frame en que juntó la moneda n+1
+}
+
+/// Archivo con la grabación del mejor tiempo de un nivel
+pub fn ruta_fantasma(id: &str) -> PathBuf {
+    directorio_datos().join(format!("fantasma_{id}.rep"))
+}
+
+impl Fantasma {
+    /// Carga y simula el mejor recorrido guardado del nivel `idx`, si hay.
+    pub fn cargar(niveles: &[Nivel], idx: usize) -> Option<Self> {
+        let g = Grabacion::cargar(&ruta_fantasma(&niveles[idx].id)).ok()?;
+        if g.nivel != niveles[idx].id { return None; }
+
+        let mut p = Partida::nueva(niveles, idx, g.muertes, g.semilla);
+        let mut part = Particulas::nuevo(); // los efectos del fantasma no se ven
+        let mut recorrido = Vec::with_capacity(g.entradas.len());
+        let mut parciales = Vec::new();
+        for e in &g.entradas {
+            let res = p.actualizar(e, &niveles[idx].reglas, &mut part);
+            recorrido.push((p.jug.x, p.jug.y));
+            while parciales.len() < p.prog.monedas { parciales.push(p.prog.frames); }
+            if matches!(res, Resultado::Gano | Resultado::Perdio(_)) { break; }
+        }
+        Some(Self { recorrido, parciales })
+    }
+
+    /// Frames que tardó en llegar a la salida
+    pub fn frames(&self) -> u32 {
+        self.recorrido.len() as u32
+    }
+
+    /// Sprite en el frame `frame` de la partida (ya no está si terminó antes)
+    pub fn sprite(&self, frame: u32) -> Option<Sprite> {
+        let &(x, y) = self.recorrido.get((frame as usize).checked_sub(1)?)?;
+        Some(Sprite { x, y, kind: SpriteKind::Ghost })
+    }
+
+    pub fn dibujar_en_minimapa(&self, fb: &mut Framebuffer, frame: u32) {
+        if let Some(s) = self.sprite(frame) {
+            marcar_minimapa(fb, s.x, s.y, Color::SKYBLUE);
+        }
+    }
+}
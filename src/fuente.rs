@@ -0,0 +1,206 @@
+//! Fuente de mapa de bits de 5×7 para escribir dentro del `Framebuffer`,
+//! a la resolución interna. Cubre ASCII, las letras del castellano (tildes,
+//! ñ, ü, ¡, ¿) y algunos símbolos de la UI (flechas, °, ×). Cada celda mide
+//! 6×9: dos filas arriba para las tildes de las mayúsculas y una columna de
+//! separación; las líneas van cada 10 píxeles.
+
+use raylib::prelude::Color;
+
+pub const ANCHO: i32 = 6; // avance por carácter (escala 1)
+pub const ALTO: i32 = 10; // avance por línea (escala 1)
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Alinear {
+    Izquierda,
+    Centro,
+    Derecha,
+}
+
+/// Cómo se dibuja un texto: tamaño del píxel, sombra y respecto de qué
+/// borde se toma la `x`.
+#[derive(Clone, Copy)]
+pub struct Estilo {
+    pub escala: i32,
+    pub sombra: Option<Color>,
+    pub alinear: Alinear,
+}
+
+impl Default for Estilo {
+    fn default() -> Self {
+        Self { escala: 1, sombra: Some(Color::BLACK), alinear: Alinear::Izquierda }
+    }
+}
+
+impl Estilo {
+    pub fn escala(mut self, e: i32) -> Self {
+        self.escala = e;
+        self
+    }
+    pub fn centrado(mut self) -> Self {
+        self.alinear = Alinear::Centro;
+        self
+    }
+    pub fn a_la_derecha(mut self) -> Self {
+        self.alinear = Alinear::Derecha;
+        self
+    }
+}
+
+/// Ancho en píxeles de la línea más larga de `s`
+pub fn ancho(s: &str, escala: i32) -> i32 {
+    s.lines().map(|l| l.chars().count() as i32).max().unwrap_or(0) * ANCHO * escala
+}
+
+// ASCII 32..=126, filas de arriba a abajo, bit 4 = columna izquierda
+const ASCII: [[u8; 7]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+    [0x08, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00], // `
+    [0x00, 0x00, 0x0E, 0x01, 0x0F, 0x11, 0x0F], // a
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E], // b
+    [0x00, 0x00, 0x0E, 0x10, 0x10, 0x11, 0x0E], // c
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F], // d
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x10, 0x0E], // e
+    [0x06, 0x09, 0x08, 0x1C, 0x08, 0x08, 0x08], // f
+    [0x00, 0x0F, 0x11, 0x11, 0x0F, 0x01, 0x0E], // g
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x11], // h
+    [0x04, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E], // i
+    [0x02, 0x00, 0x06, 0x02, 0x02, 0x12, 0x0C], // j
+    [0x10, 0x10, 0x12, 0x14, 0x18, 0x14, 0x12], // k
+    [0x0C, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // l
+    [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11], // m
+    [0x00, 0x00, 0x16, 0x19, 0x11, 0x11, 0x11], // n
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E], // o
+    [0x00, 0x00, 0x1E, 0x11, 0x1E, 0x10, 0x10], // p
+    [0x00, 0x00, 0x0D, 0x13, 0x0F, 0x01, 0x01], // q
+    [0x00, 0x00, 0x16, 0x19, 0x10, 0x10, 0x10], // r
+    [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E], // s
+    [0x08, 0x08, 0x1C, 0x08, 0x08, 0x09, 0x06], // t
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x13, 0x0D], // u
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04], // v
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A], // w
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11], // x
+    [0x00, 0x00, 0x11, 0x11, 0x0F, 0x01, 0x0E], // y
+    [0x00, 0x00, 0x1F, 0x02, 0x04, 0x08, 0x1F], // z
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02], // {
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // |
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08], // }
+    [0x00, 0x00, 0x08, 0x15, 0x02, 0x00, 0x00], // ~
+];
+
+// marcas que van encima de la letra base
+const AGUDO: [u8; 2] = [0x02, 0x04];
+const TILDE: [u8; 2] = [0x0D, 0x16];
+const DIERESIS: [u8; 2] = [0x0A, 0x00];
+
+const I_SIN_PUNTO: [u8; 7] = [0x00, 0x00, 0x0C, 0x04, 0x04, 0x04, 0x0E];
+const DESCONOCIDO: [u8; 7] = [0x1F, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1F];
+
+fn base(c: char) -> [u8; 7] {
+    match c {
+        ' '..='~' => ASCII[c as usize - 32],
+        '¡' => [0x04, 0x00, 0x04, 0x04, 0x04, 0x04, 0x04],
+        '¿' => [0x04, 0x00, 0x04, 0x08, 0x10, 0x11, 0x0E],
+        '°' => [0x0C, 0x12, 0x12, 0x0C, 0x00, 0x00, 0x00],
+        '×' => [0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00],
+        '–' | '—' => ASCII['-' as usize - 32],
+        '…' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x15],
+        '←' => [0x00, 0x04, 0x08, 0x1F, 0x08, 0x04, 0x00],
+        '→' => [0x00, 0x04, 0x02, 0x1F, 0x02, 0x04, 0x00],
+        '↑' => [0x04, 0x0E, 0x15, 0x04, 0x04, 0x04, 0x04],
+        '↓' => [0x04, 0x04, 0x04, 0x04, 0x15, 0x0E, 0x04],
+        _ => DESCONOCIDO,
+    }
+}
+
+/// Filas de un carácter en su celda de 9 de alto (las dos primeras son
+/// para las tildes de las mayúsculas).
+pub fn glifo(c: char) -> [u8; 9] {
+    let (letra, marca) = match c {
+        'á' => ('a', AGUDO), 'é' => ('e', AGUDO), 'ó' => ('o', AGUDO), 'ú' => ('u', AGUDO),
+        'Á' => ('A', AGUDO), 'É' => ('E', AGUDO), 'Í' => ('I', AGUDO), 'Ó' => ('O', AGUDO), 'Ú' => ('U', AGUDO),
+        'ñ' => ('n', TILDE), 'Ñ' => ('N', TILDE),
+        'ü' => ('u', DIERESIS), 'Ü' => ('U', DIERESIS),
+        'í' => {
+            let mut g = [0; 9];
+            g[2..].copy_from_slice(&I_SIN_PUNTO);
+            g[1] = AGUDO[0];
+            g[2] = AGUDO[1];
+            return g;
+        }
+        _ => {
+            let mut g = [0; 9];
+            g[2..].copy_from_slice(&base(c));
+            return g;
+        }
+    };
+    let mut g = [0; 9];
+    g[2..].copy_from_slice(&base(letra));
+    // en minúscula la marca baja hasta las filas vacías de arriba de la letra
+    let fila = if letra.is_uppercase() { 0 } else { 1 };
+    g[fila] |= marca[0];
+    g[fila + 1] |= marca[1];
+    g
+}
@@ -0,0 +1,75 @@
+//! Piezas comunes de los generadores de niveles: la salida va a la celda más
+//! lejana del inicio y monedas, antorchas y pozos se reparten de a uno,
+//! descartando cualquier peligro que deje la salida o una moneda fuera de
+//! alcance sin pisar peligros (lo mismo que pide `Nivel::validar`).
+
+use crate::caminos::{CampoDistancias, PELIGROS};
+#[cfg(test)]
+use crate::mapas::Nivel;
+use crate::motor::{celda, Mapa};
+
+/// Celdas de piso (' ') del mapa
+pub fn celdas_libres(mapa: &Mapa) -> Vec<(i32, i32)> {
+    let mut v = Vec::new();
+    for (y, fila) in mapa.iter().enumerate() {
+        for (x, &c) in fila.iter().enumerate() {
+            if c == ' ' { v.push((x as i32, y as i32)); }
+        }
+    }
+    v
+}
+
+/// Celda libre más lejana del inicio (por el campo de distancias), para la salida.
+pub fn mas_lejana(mapa: &Mapa, inicio: (i32, i32)) -> Option<(i32, i32)> {
+    let campo = CampoDistancias::calcular(mapa, inicio, true);
+    celdas_libres(mapa).into_iter()
+        .filter_map(|(x, y)| campo.distancia(x, y).map(|d| (d, (x, y))))
+        .max_by_key(|&(d, _)| d)
+        .map(|(_, p)| p)
+}
+
+/// ¿La salida y todas las monedas se alcanzan desde el inicio sin pisar peligros?
+pub fn todo_alcanzable(mapa: &Mapa, inicio: (i32, i32)) -> bool {
+    let campo = CampoDistancias::calcular(mapa, inicio, true);
+    celdas_con(mapa, &['E', 'C']).into_iter().all(|(x, y)| campo.distancia(x, y).is_some())
+}
+
+/// Celdas con alguno de los caracteres `cs`
+pub fn celdas_con(mapa: &Mapa, cs: &[char]) -> Vec<(i32, i32)> {
+    let mut v = Vec::new();
+    for (y, fila) in mapa.iter().enumerate() {
+        for (x, c) in fila.iter().enumerate() {
+            if cs.contains(c) { v.push((x as i32, y as i32)); }
+        }
+    }
+    v
+}
+
+/// Pone hasta `n` veces `c` en las celdas `candidatas` (en ese orden) que
+/// sigan libres y no sean el inicio. Un peligro que corta el paso se
+/// saca y se prueba la próxima celda. Devuelve cuántos puso.
+pub fn repartir(mapa: &mut Mapa, inicio: (i32, i32), candidatas: &[(i32, i32)], c: char, n: usize) -> usize {
+    let mut puestos = 0;
+    for &(x, y) in candidatas {
+        if puestos == n { break; }
+        if (x, y) == inicio || celda(mapa, x, y) != Some(' ') { continue; }
+        mapa[y as usize][x as usize] = c;
+        if PELIGROS.contains(&c) && !todo_alcanzable(mapa, inicio) {
+            mapa[y as usize][x as usize] = ' ';
+            continue;
+        }
+        puestos += 1;
+    }
+    puestos
+}
+
+/// Para los tests de cada generador: con las primeras semillas el nivel
+/// pasa `Nivel::validar`.
+#[cfg(test)]
+pub fn asegurar_ganable(generar: impl Fn(u64) -> Nivel) {
+    for semilla in 0..60 {
+        let n = generar(semilla);
+        let problemas = n.validar();
+        assert!(problemas.is_empty(), "{} (semilla {semilla}): {problemas:?}", n.id);
+    }
+}
@@ -0,0 +1,260 @@
+//! Datos persistentes: niveles completados, mejores tiempos/monedas y tabla
+//! de puntajes por nivel. Se guardan en texto plano versionado dentro del
+//! directorio de datos del usuario; si el archivo está corrupto se aparta
+//! como `.corrupto` y se empieza de cero.
+
+use crate::idiomas::Mensaje;
+use std::cmp::Reverse;
+use std::fs;
+use std::path::PathBuf;
+
+const VERSION: u32 = 1;
+const CABECERA: &str = "raycaster-save";
+const MAX_PUNTAJES: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Marca {
+    pub puntaje: i32,
+    pub frames: u32,
+    pub monedas: usize,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct RegistroNivel {
+    pub id: String,
+    pub completado: bool,
+    pub mejor_tiempo: Option<u32>, // frames
+    pub mejores_monedas: usize,
+    pub puntajes: Vec<Marca>,      // de mayor a menor, hasta MAX_PUNTAJES
+}
+
+pub struct Guardado {
+    pub niveles: Vec<RegistroNivel>,
+    ruta: PathBuf,
+    pub aviso: Option<Mensaje>, // problema al cargar/guardar, para mostrar en el menú
+}
+
+/// Directorio de datos del usuario según la plataforma
+pub fn directorio_datos() -> PathBuf {
+    let env = |k: &str| std::env::var_os(k).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env("XDG_DATA_HOME").or_else(|| env("HOME").map(|h| h.join(".local").join("share")))
+    };
+    base.unwrap_or_else(|| PathBuf::from(".")).join("raycaster-javier")
+}
+
+impl Guardado {
+    /// Carga el archivo de guardado (o uno vacío si no existe o está roto).
+    pub fn cargar() -> Self {
+        Self::cargar_de(directorio_datos().join("progreso.txt"))
+    }
+
+    fn cargar_de(ruta: PathBuf) -> Self {
+        let mut g = Self { niveles: Vec::new(), ruta, aviso: None };
+        match fs::read_to_string(&g.ruta) {
+            Ok(txt) => match parsear(&txt) {
+                Ok(niveles) => g.niveles = niveles,
+                Err(e) => {
+                    // apartar el archivo roto para no pisarlo y arrancar de cero
+                    let copia = g.ruta.with_extension("corrupto");
+                    let _ = fs::rename(&g.ruta, &copia);
+                    g.aviso = Some(Mensaje::con("aviso.guardado_danado", &[&e]));
+                }
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => g.aviso = Some(Mensaje::con("aviso.guardado_no_leido", &[&e])),
+        }
+        g
+    }
+
+    /// Escribe a un temporal y renombra, para no dejar el archivo a medias.
+    pub fn guardar(&mut self) {
+        let res = (|| -> std::io::Result<()> {
+            if let Some(dir) = self.ruta.parent() { fs::create_dir_all(dir)?; }
+            let tmp = self.ruta.with_extension("tmp");
+            fs::write(&tmp, serializar(&self.niveles))?;
+            fs::rename(&tmp, &self.ruta)
+        })();
+        if let Err(e) = res {
+            self.aviso = Some(Mensaje::con("aviso.guardado_no_escrito", &[&e]));
+        }
+    }
+
+    pub fn nivel(&self, id: &str) -> Option<&RegistroNivel> {
+        self.niveles.iter().find(|n| n.id == id)
+    }
+
+    pub fn completado(&self, id: &str) -> bool {
+        self.nivel(id).is_some_and(|n| n.completado)
+    }
+
+    /// Anota una victoria. Devuelve la posición en la tabla de puntajes (0 = mejor), si entró.
+    pub fn registrar(&mut self, id: &str, m: Marca) -> Option<usize> {
+        let idx = match self.niveles.iter().position(|n| n.id == id) {
+            Some(i) => i,
+            None => {
+                self.niveles.push(RegistroNivel { id: id.to_string(), ..Default::default() });
+                self.niveles.len() - 1
+            }
+        };
+        let n = &mut self.niveles[idx];
+        n.completado = true;
+        n.mejor_tiempo = Some(n.mejor_tiempo.map_or(m.frames, |t| t.min(m.frames)));
+        n.mejores_monedas = n.mejores_monedas.max(m.monedas);
+
+        let pos = n.puntajes.iter().position(|p| m.puntaje > p.puntaje).unwrap_or(n.puntajes.len());
+        if pos >= MAX_PUNTAJES { return None; }
+        n.puntajes.insert(pos, m);
+        n.puntajes.truncate(MAX_PUNTAJES);
+        Some(pos)
+    }
+}
+
+fn serializar(niveles: &[RegistroNivel]) -> String {
+    let mut s = format!("{CABECERA} {VERSION}\n");
+    for n in niveles {
+        s += &format!("nivel {}\n", n.id);
+        s += &format!("completado {}\n", n.completado as u8);
+        if let Some(t) = n.mejor_tiempo { s += &format!("mejor_tiempo {t}\n"); }
+        s += &format!("mejores_monedas {}\n", n.mejores_monedas);
+        for p in &n.puntajes {
+            s += &format!("puntaje {} {} {}\n", p.puntaje, p.frames, p.monedas);
+        }
+        s += "fin\n";
+    }
+    s
+}
+
+fn parsear(txt: &str) -> Result<Vec<RegistroNivel>, String> {
+    let mut lineas = txt.lines().enumerate();
+    let (_, cab) = lineas.next().ok_or("archivo vacío")?;
+    let version: u32 = cab.strip_prefix(CABECERA)
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or("cabecera inválida")?;
+    if version > VERSION {
+        return Err(format!("versión {version} más nueva que la del juego ({VERSION})"));
+    }
+
+    let mut niveles = Vec::new();
+    let mut actual: Option<RegistroNivel> = None;
+    for (i, l) in lineas {
+        let l = l.trim();
+        if l.is_empty() { continue; }
+        let (clave, resto) = l.split_once(' ').unwrap_or((l, ""));
+        let err = || format!("línea {}: '{l}'", i + 1);
+        let num = |t: &str| t.parse::<u32>().map_err(|_| err());
+
+        if clave == "nivel" {
+            if actual.is_some() { return Err(err()); }
+            actual = Some(RegistroNivel { id: resto.to_string(), ..Default::default() });
+            continue;
+        }
+        let n = actual.as_mut().ok_or_else(err)?;
+        match clave {
+            "completado" => n.completado = num(resto)? != 0,
+            "mejor_tiempo" => n.mejor_tiempo = Some(num(resto)?),
+            "mejores_monedas" => n.mejores_monedas = num(resto)? as usize,
+            "puntaje" => {
+                let c: Vec<&str> = resto.split_whitespace().collect();
+                if c.len() != 3 { return Err(err()); }
+                let puntaje = c[0].parse::<i32>().map_err(|_| err())?;
+                n.puntajes.push(Marca { puntaje, frames: num(c[1])?, monedas: num(c[2])? as usize });
+            }
+            "fin" => {
+                let mut n = actual.take().unwrap();
+                n.puntajes.sort_by_key(|p| Reverse(p.puntaje));
+                n.puntajes.truncate(MAX_PUNTAJES);
+                niveles.push(n);
+            }
+            _ => return Err(err()),
+        }
+    }
+    if actual.is_some() { return Err("falta 'fin' al final".to_string()); }
+    Ok(niveles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marca(puntaje: i32, frames: u32) -> Marca {
+        Marca { puntaje, frames, monedas: 3 }
+    }
+
+    #[test]
+    fn tabla_de_puntajes() {
+        let mut g = Guardado { niveles: Vec::new(), ruta: PathBuf::new(), aviso: None };
+        assert_eq!(g.registrar("a", marca(500, 900)), Some(0));
+        assert_eq!(g.registrar("a", marca(700, 1200)), Some(0));
+        assert_eq!(g.registrar("a", marca(600, 800)), Some(1));
+        for _ in 0..2 { g.registrar("a", marca(100, 2000)); }
+        // la tabla está llena: uno peor no entra, uno igual al último tampoco
+        assert_eq!(g.registrar("a", marca(50, 100)), None);
+        assert_eq!(g.registrar("a", marca(100, 100)), None);
+        assert_eq!(g.registrar("a", marca(650, 3000)), Some(1));
+
+        let n = g.nivel("a").unwrap();
+        assert!(n.completado && g.completado("a") && !g.completado("b"));
+        assert_eq!(n.mejor_tiempo, Some(100));
+        let puntajes: Vec<i32> = n.puntajes.iter().map(|p| p.puntaje).collect();
+        assert_eq!(puntajes, [700, 650, 600, 500, 100]);
+    }
+
+    #[test]
+    fn ida_y_vuelta() {
+        let niveles = vec![
+            RegistroNivel { id: "laberinto-7".to_string(), completado: true, mejor_tiempo: Some(1234), mejores_monedas: 6,
+                            puntajes: vec![marca(900, 1300), marca(-5, 2000)] },
+            RegistroNivel { id: "nivel con espacios".to_string(), ..Default::default() },
+        ];
+        let leidos = parsear(&serializar(&niveles)).unwrap();
+        assert_eq!(format!("{leidos:?}"), format!("{niveles:?}"));
+
+        // desordenados o de más: se ordenan y se cortan al leer
+        let mut txt = format!("{CABECERA} 1\nnivel x\ncompletado 1\n");
+        for p in [3, 9, 1, 7, 5, 8] { txt += &format!("puntaje {p} 60 0\n"); }
+        let n = &parsear(&(txt + "fin\n")).unwrap()[0];
+        assert_eq!(n.puntajes.iter().map(|p| p.puntaje).collect::<Vec<_>>(), [9, 8, 7, 5, 3]);
+    }
+
+    #[test]
+    fn archivos_invalidos() {
+        let casos = [
+            ("", "archivo vacío"),
+            ("otra-cosa 1\n", "cabecera inválida"),
+            ("raycaster-save 2\n", "versión 2 más nueva"),
+            ("raycaster-save 1\ncompletado 1\n", "línea 2"),
+            ("raycaster-save 1\nnivel a\nnivel b\n", "línea 3"),
+            ("raycaster-save 1\nnivel a\npuntaje 1 2\nfin\n", "línea 3"),
+            ("raycaster-save 1\nnivel a\nvidas 3\nfin\n", "línea 3"),
+            ("raycaster-save 1\nnivel a\ncompletado 1\n", "falta 'fin'"),
+        ];
+        for (txt, esperado) in casos {
+            let e = parsear(txt).unwrap_err();
+            assert!(e.contains(esperado), "{txt:?}: '{e}' no dice '{esperado}'");
+        }
+    }
+
+    #[test]
+    fn archivo_roto_se_aparta() {
+        let dir = std::env::temp_dir().join(format!("raycaster-guardado-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ruta = dir.join("progreso.txt");
+        fs::write(&ruta, "raycaster-save 9\nnivel a\nfin\n").unwrap();
+
+        let g = Guardado::cargar_de(ruta.clone());
+        assert!(g.niveles.is_empty());
+        assert_eq!(g.aviso.as_ref().map(|a| a.clave), Some("aviso.guardado_danado"));
+        assert!(!ruta.exists());
+        assert_eq!(fs::read_to_string(ruta.with_extension("corrupto")).unwrap(), "raycaster-save 9\nnivel a\nfin\n");
+
+        // sin archivo se arranca vacío y sin aviso
+        let g = Guardado::cargar_de(ruta);
+        assert!(g.niveles.is_empty() && g.aviso.is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
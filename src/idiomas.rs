@@ -0,0 +1,435 @@
+//! Textos de la interfaz por idioma. Cada texto tiene una clave
+//! ("menu.titulo", "muerte.pozo", "nivel.<id>") y una tabla por idioma; si
+//! a un idioma le falta una clave se usa la del idioma por defecto (español)
+//! y, si tampoco está, la clave misma. Los `{0}`, `{1}`… se reemplazan por
+//! los argumentos en ese orden.
+
+use std::fmt::Display;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Idioma {
+    Es,
+    En,
+}
+
+impl Idioma {
+    pub const TODOS: [Idioma; 2] = [Idioma::Es, Idioma::En];
+    pub const POR_DEFECTO: Idioma = Idioma::Es;
+
+    /// Código para `opciones.txt`
+    pub fn codigo(self) -> &'static str {
+        match self {
+            Idioma::Es => "es",
+            Idioma::En => "en",
+        }
+    }
+
+    pub fn desde_codigo(s: &str) -> Option<Self> {
+        Self::TODOS.into_iter().find(|i| i.codigo() == s)
+    }
+
+    /// Nombre del idioma en sí mismo
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Idioma::Es => "Español",
+            Idioma::En => "English",
+        }
+    }
+
+    /// El siguiente (o anterior, con `paso` negativo) en la lista de idiomas
+    pub fn otro(self, paso: i32) -> Self {
+        let n = Self::TODOS.len() as i32;
+        let i = Self::TODOS.iter().position(|&x| x == self).unwrap_or(0) as i32;
+        Self::TODOS[(i + paso).rem_euclid(n) as usize]
+    }
+
+    fn tabla(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Idioma::Es => ES,
+            Idioma::En => EN,
+        }
+    }
+
+    fn buscar(self, clave: &str) -> Option<&'static str> {
+        self.tabla().iter().find(|(k, _)| *k == clave).map(|(_, v)| *v)
+    }
+
+    /// Texto de `clave` en este idioma (con las alternativas de arriba)
+    pub fn t(self, clave: &str) -> &str {
+        self.buscar(clave).or_else(|| Self::POR_DEFECTO.buscar(clave)).unwrap_or(clave)
+    }
+
+    /// Texto de `clave` con los `{n}` reemplazados por `args`. Se recorre la
+    /// plantilla una sola vez: lo que traen los argumentos queda tal cual,
+    /// aunque tenga llaves.
+    pub fn f(self, clave: &str, args: &[&dyn Display]) -> String {
+        let mut s = String::new();
+        let mut resto = self.t(clave);
+        while let Some(i) = resto.find('{') {
+            s.push_str(&resto[..i]);
+            resto = &resto[i + 1..];
+            let arg = resto.find('}')
+                .and_then(|j| Some((args.get(resto[..j].parse::<usize>().ok()?)?, j)));
+            match arg {
+                Some((a, j)) => {
+                    s += &a.to_string();
+                    resto = &resto[j + 1..];
+                }
+                None => s.push('{'),
+            }
+        }
+        s + resto
+    }
+}
+
+/// Mensaje guardado por clave, para avisos que se arman lejos de la UI y se
+/// muestran en el idioma que haya al dibujarlos.
+#[derive(Clone, Default)]
+pub struct Mensaje {
+    pub clave: &'static str,
+    pub args: Vec<String>,
+}
+
+impl Mensaje {
+    pub fn nuevo(clave: &'static str) -> Self {
+        Self { clave, args: Vec::new() }
+    }
+
+    pub fn con(clave: &'static str, args: &[&dyn Display]) -> Self {
+        Self { clave, args: args.iter().map(|a| a.to_string()).collect() }
+    }
+
+    pub fn texto(&self, l: Idioma) -> String {
+        let args: Vec<&dyn Display> = self.args.iter().map(|a| a as &dyn Display).collect();
+        l.f(self.clave, &args)
+    }
+}
+
+const ES: &[(&str, &str)] = &[
+    // niveles
+    ("nivel.pasillos", "Nivel 1 – Pasillos"),
+    ("nivel.patio", "Nivel 2 – Patio"),
+    ("nivel.diario", "Desafío del día ({0})"),
+    ("nivel.laberinto", "Laberinto aleatorio (semilla {0})"),
+    ("nivel.mazmorra", "Mazmorra aleatoria (semilla {0})"),
+    ("nivel.cueva", "Cueva (semilla {0})"),
+    // menú principal
+    ("menu.titulo", "RAYCASTER – BIENVENIDO"),
+    ("menu.elegir", "Usa ↑/↓ para elegir nivel y {0} para iniciar"),
+    ("menu.bloqueado", " [bloqueado]"),
+    ("menu.completado", " (completado)"),
+    ("menu.semilla", "  ←/→: semilla"),
+    ("menu.objetivo", "Objetivo: {0}"),
+    ("menu.records", "Mejor tiempo: {0}   Mejores monedas: {1}"),
+    ("menu.puntaje", "{0}. {1}  {2}  {3} monedas"),
+    ("menu.mover", "Mover: {0} | Girar: {1} / {2} o mouse"),
+    ("menu.disparar", "Disparar: {0}"),
+    ("menu.opciones", "C: cambiar controles | O o {0}: opciones"),
+    ("menu.partida", "F5/F9: guardar/cargar partida rápida\nR: ver la última partida | E/N: editar/nuevo nivel"),
+    ("aviso.nivel_recargado", "Nivel recargado: {0}"),
+    ("aviso.nivel_error", "No se pudo recargar {0}: {1}"),
+    ("evento.mensaje", "{0}"),
+    ("aviso.texturas_recargadas", "Texturas recargadas"),
+    ("aviso.texturas_error", "No se pudo cargar el atlas: {0}"),
+    ("editor.titulo", "EDITOR – {0}{1}"),
+    ("editor.pincel", "Pincel"),
+    ("editor.relleno", "Relleno"),
+    ("editor.rectangulo", "Rectángulo"),
+    ("editor.inicio", "Inicio"),
+    ("editor.estado", "{0}: {1} | ({2},{3}) {4}"),
+    ("editor.ayuda1", "1-4 herram. Q/E paleta R girar P probar"),
+    ("editor.ayuda2", "Ctrl+Z/Y deshacer/rehacer Ctrl+S guardar"),
+    ("editor.piso", "piso"),
+    ("editor.pared", "pared {0}"),
+    ("editor.salida", "salida"),
+    ("editor.antorcha", "antorcha"),
+    ("editor.moneda", "moneda"),
+    ("editor.pozo", "pozo"),
+    ("editor.pinchos", "pinchos"),
+    ("editor.lava", "lava"),
+    ("editor.veneno", "veneno"),
+    ("editor.botiquin", "botiquín"),
+    ("editor.municion", "munición"),
+    ("editor.enemigo", "enemigo"),
+    ("editor.puerta", "puerta"),
+    ("editor.teletransportador", "teletransportador"),
+    ("editor.bloque", "bloque"),
+    ("editor.placa", "placa de presión"),
+    ("editor.no_caminable", "Para probar, el cursor tiene que estar en piso"),
+    ("editor.sin_guardar", "Hay cambios sin guardar (otra vez para salir)"),
+    ("editor.guardado", "Guardado en {0}"),
+    ("editor.guardado_con_problemas", "Guardado, pero: {0}"),
+    ("editor.no_guardado", "No se pudo guardar: {0}"),
+    // HUD
+    ("hud.fps", "FPS {0}"),
+    ("hud.monedas", "Monedas {0}/{1}"),
+    ("hud.vida", "Vida {0}/{1}"),
+    ("hud.pistola", "Pistola"),
+    ("hud.lanzador", "Lanzador"),
+    ("hud.quedan", "Quedan {0}"),
+    ("hud.tiempo", "Tiempo {0}"),
+    ("hud.fantasma", "Fantasma {0}"),
+    ("hud.parcial", "Moneda {0}: {1}"),
+    ("hud.falta", "Falta: {0}"),
+    // repetición
+    ("rep.titulo", "REPETICIÓN {0}  {1}/{2}"),
+    ("rep.pausa", "PAUSA"),
+    ("rep.ayuda", "{0}: pausa | .: un frame\n→: adelantar | {1}: salir"),
+    ("rep.fin", "Fin de la grabación."),
+    ("rep.salida", "Llegó a la salida."),
+    ("rep.desvio", "¡Se desvió de la partida grabada!"),
+    // pausa y opciones
+    ("pausa.titulo", "PAUSA"),
+    ("pausa.continuar", "Continuar"),
+    ("pausa.reiniciar", "Reiniciar nivel"),
+    ("pausa.opciones", "Opciones"),
+    ("pausa.salir", "Salir al menú"),
+    ("pausa.ayuda", "{0}: elegir\n{1}: seguir jugando"),
+    ("opciones.titulo", "OPCIONES"),
+    ("opciones.ayuda", "←/→: ajustar | {0}: elegir\n{1}: guardar y volver"),
+    ("opcion.volumen", "Volumen"),
+    ("opcion.sens_mouse", "Sensibilidad del mouse"),
+    ("opcion.sens_pad", "Sensibilidad del gamepad"),
+    ("opcion.fov", "Campo de visión"),
+    ("opcion.escala", "Resolución (tamaño de ventana)"),
+    ("opcion.idioma", "Idioma"),
+    ("opcion.controles", "Controles..."),
+    // pantalla de controles
+    ("controles.titulo", "CONTROLES"),
+    ("controles.esperando", "Aprieta una tecla o botón para asignarlo\n(BACKSPACE cancela)"),
+    ("controles.ayuda", "Usar: reasignar | ←/→: ajustar\nSUPR: restaurar | Volver: guardar y salir"),
+    ("controles.sticks", "Stick para moverse"),
+    ("controles.zona_muerta", "Zona muerta {0}"),
+    ("controles.curva", "Curva {0}"),
+    ("accion.avanzar", "Avanzar"),
+    ("accion.retroceder", "Retroceder"),
+    ("accion.paso_izq", "Paso a la izquierda"),
+    ("accion.paso_der", "Paso a la derecha"),
+    ("accion.girar_izq", "Girar a la izquierda"),
+    ("accion.girar_der", "Girar a la derecha"),
+    ("accion.disparar", "Disparar"),
+    ("accion.pistola", "Pistola"),
+    ("accion.lanzador", "Lanzador"),
+    ("accion.cambiar_arma", "Cambiar de arma"),
+    ("accion.usar", "Usar / aceptar"),
+    ("accion.pausa", "Pausa"),
+    ("accion.mouse", "Mirar con el mouse"),
+    ("accion.arriba", "Menú: arriba"),
+    ("accion.abajo", "Menú: abajo"),
+    ("accion.izquierda", "Menú: izquierda / menos"),
+    ("accion.derecha", "Menú: derecha / más"),
+    ("accion.volver", "Menú: volver"),
+    // fin del nivel
+    ("exito.titulo", "¡ÉXITO!"),
+    ("exito.salida", "Has llegado a la salida."),
+    ("exito.monedas", "Monedas {0}/{1}"),
+    ("exito.todas", "Todas las monedas"),
+    ("exito.tiempo", "Tiempo {0}"),
+    ("exito.enemigos", "Enemigos derrotados {0}"),
+    ("exito.muertes", "Muertes {0}"),
+    ("exito.total", "TOTAL"),
+    ("exito.record", "¡Nuevo récord!"),
+    ("exito.puesto", "Puesto #{0} en la tabla"),
+    ("exito.fantasma", "Contra el fantasma: {0}"),
+    ("perdio.titulo", "¡PERDISTE!"),
+    ("fin.volver", "{0}: volver al menú"),
+    ("muerte.pozo", "Caíste en un pozo."),
+    ("muerte.enemigo", "Te atrapó un enemigo."),
+    ("muerte.pinchos", "Te ensartaron los pinchos."),
+    ("muerte.lava", "Te quemaste en la lava."),
+    ("muerte.veneno", "El veneno acabó contigo."),
+    ("muerte.tiempo", "Se acabó el tiempo."),
+    ("muerte.sin_vida", "Te quedaste sin vida."),
+    ("muerte.evento", "El nivel te atrapó."),
+    // reglas de salida
+    ("regla.todas", "todas las monedas"),
+    ("regla.minimo", "al menos {0} monedas"),
+    ("regla.tiempo", "antes de {0}"),
+    ("regla.enemigos", "derrotar a todos los enemigos"),
+    // avisos
+    ("aviso.partida_guardada", "Partida guardada"),
+    ("aviso.partida_no_guardada", "No se pudo guardar la partida: {0}"),
+    ("aviso.partida_cargada", "Partida cargada"),
+    ("aviso.partida_no_cargada", "No se pudo cargar la partida: {0}"),
+    ("aviso.rep_desconocida", "La repetición es de un nivel desconocido: {0}"),
+    ("aviso.sin_rep", "No hay repetición para ver: {0}"),
+    ("aviso.rep_no_guardada", "No se pudo guardar la repetición: {0}"),
+    ("aviso.gamepad_conectado", "Gamepad {0} conectado: {1}"),
+    ("aviso.gamepad_desconectado", "Gamepad {0} desconectado"),
+    ("aviso.guardado_danado", "Guardado dañado ({0}); se empezó de cero"),
+    ("aviso.guardado_no_leido", "No se pudo leer el guardado: {0}"),
+    ("aviso.guardado_no_escrito", "No se pudo guardar: {0}"),
+    ("aviso.controles_no_leidos", "No se pudieron leer los controles: {0}"),
+    ("aviso.controles_invalidos", "controles.txt inválido; se usan los de siempre"),
+    ("aviso.controles_lineas", "controles.txt: se ignoraron las líneas {0}"),
+    ("aviso.controles_no_guardados", "No se pudieron guardar los controles: {0}"),
+    ("aviso.opciones_no_leidas", "No se pudieron leer las opciones: {0}"),
+    ("aviso.opciones_invalidas", "opciones.txt inválido; se usan las de siempre"),
+    ("aviso.opciones_no_guardadas", "No se pudieron guardar las opciones: {0}"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("nivel.pasillos", "Level 1 – Corridors"),
+    ("nivel.patio", "Level 2 – Courtyard"),
+    ("nivel.diario", "Daily challenge ({0})"),
+    ("nivel.laberinto", "Random maze (seed {0})"),
+    ("nivel.mazmorra", "Random dungeon (seed {0})"),
+    ("nivel.cueva", "Cave (seed {0})"),
+    ("menu.titulo", "RAYCASTER – WELCOME"),
+    ("menu.elegir", "Use ↑/↓ to pick a level and {0} to start"),
+    ("menu.bloqueado", " [locked]"),
+    ("menu.completado", " (completed)"),
+    ("menu.semilla", "  ←/→: seed"),
+    ("menu.objetivo", "Goal: {0}"),
+    ("menu.records", "Best time: {0}   Most coins: {1}"),
+    ("menu.puntaje", "{0}. {1}  {2}  {3} coins"),
+    ("menu.mover", "Move: {0} | Turn: {1} / {2} or mouse"),
+    ("menu.disparar", "Fire: {0}"),
+    ("menu.opciones", "C: change controls | O or {0}: options"),
+    ("menu.partida", "F5/F9: quick save/load | R: watch the last run\nE/N: edit/new level"),
+    ("aviso.nivel_recargado", "Level reloaded: {0}"),
+    ("aviso.nivel_error", "Could not reload {0}: {1}"),
+    ("evento.mensaje", "{0}"),
+    ("aviso.texturas_recargadas", "Textures reloaded"),
+    ("aviso.texturas_error", "Could not load the atlas: {0}"),
+    ("editor.titulo", "EDITOR – {0}{1}"),
+    ("editor.pincel", "Brush"),
+    ("editor.relleno", "Fill"),
+    ("editor.rectangulo", "Rectangle"),
+    ("editor.inicio", "Start"),
+    ("editor.estado", "{0}: {1} | ({2},{3}) {4}"),
+    ("editor.ayuda1", "1-4 tools Q/E palette R turn P play-test"),
+    ("editor.ayuda2", "Ctrl+Z/Y undo/redo Ctrl+S save"),
+    ("editor.piso", "floor"),
+    ("editor.pared", "wall {0}"),
+    ("editor.salida", "exit"),
+    ("editor.antorcha", "torch"),
+    ("editor.moneda", "coin"),
+    ("editor.pozo", "pit"),
+    ("editor.pinchos", "spikes"),
+    ("editor.lava", "lava"),
+    ("editor.veneno", "poison"),
+    ("editor.botiquin", "medkit"),
+    ("editor.municion", "ammo"),
+    ("editor.enemigo", "enemy"),
+    ("editor.puerta", "door"),
+    ("editor.teletransportador", "teleporter"),
+    ("editor.bloque", "block"),
+    ("editor.placa", "pressure plate"),
+    ("editor.no_caminable", "To play-test, put the cursor on floor"),
+    ("editor.sin_guardar", "Unsaved changes (again to leave)"),
+    ("editor.guardado", "Saved to {0}"),
+    ("editor.guardado_con_problemas", "Saved, but: {0}"),
+    ("editor.no_guardado", "Could not save: {0}"),
+    ("hud.monedas", "Coins {0}/{1}"),
+    ("hud.vida", "Health {0}/{1}"),
+    ("hud.pistola", "Pistol"),
+    ("hud.lanzador", "Launcher"),
+    ("hud.quedan", "Left {0}"),
+    ("hud.tiempo", "Time {0}"),
+    ("hud.fantasma", "Ghost {0}"),
+    ("hud.parcial", "Coin {0}: {1}"),
+    ("hud.falta", "Still needed: {0}"),
+    ("rep.titulo", "REPLAY {0}  {1}/{2}"),
+    ("rep.pausa", "PAUSED"),
+    ("rep.ayuda", "{0}: pause | .: one frame\n→: fast-forward | {1}: exit"),
+    ("rep.fin", "End of the recording."),
+    ("rep.salida", "Reached the exit."),
+    ("rep.desvio", "It diverged from the recorded run!"),
+    ("pausa.titulo", "PAUSED"),
+    ("pausa.continuar", "Resume"),
+    ("pausa.reiniciar", "Restart level"),
+    ("pausa.opciones", "Options"),
+    ("pausa.salir", "Quit to menu"),
+    ("pausa.ayuda", "{0}: select\n{1}: keep playing"),
+    ("opciones.titulo", "OPTIONS"),
+    ("opciones.ayuda", "←/→: adjust | {0}: select\n{1}: save and go back"),
+    ("opcion.volumen", "Volume"),
+    ("opcion.sens_mouse", "Mouse sensitivity"),
+    ("opcion.sens_pad", "Gamepad sensitivity"),
+    ("opcion.fov", "Field of view"),
+    ("opcion.escala", "Resolution (window size)"),
+    ("opcion.idioma", "Language"),
+    ("opcion.controles", "Controls..."),
+    ("controles.titulo", "CONTROLS"),
+    ("controles.esperando", "Press a key or button to bind it\n(BACKSPACE cancels)"),
+    ("controles.ayuda", "Use: rebind | ←/→: adjust\nDEL: restore defaults | Back: save and exit"),
+    ("controles.sticks", "Movement stick"),
+    ("controles.zona_muerta", "Deadzone {0}"),
+    ("controles.curva", "Curve {0}"),
+    ("accion.avanzar", "Forward"),
+    ("accion.retroceder", "Back"),
+    ("accion.paso_izq", "Strafe left"),
+    ("accion.paso_der", "Strafe right"),
+    ("accion.girar_izq", "Turn left"),
+    ("accion.girar_der", "Turn right"),
+    ("accion.disparar", "Fire"),
+    ("accion.pistola", "Pistol"),
+    ("accion.lanzador", "Launcher"),
+    ("accion.cambiar_arma", "Switch weapon"),
+    ("accion.usar", "Use / accept"),
+    ("accion.pausa", "Pause"),
+    ("accion.mouse", "Mouse look"),
+    ("accion.arriba", "Menu: up"),
+    ("accion.abajo", "Menu: down"),
+    ("accion.izquierda", "Menu: left / less"),
+    ("accion.derecha", "Menu: right / more"),
+    ("accion.volver", "Menu: back"),
+    ("exito.titulo", "SUCCESS!"),
+    ("exito.salida", "You reached the exit."),
+    ("exito.monedas", "Coins {0}/{1}"),
+    ("exito.todas", "All coins"),
+    ("exito.tiempo", "Time {0}"),
+    ("exito.enemigos", "Enemies defeated {0}"),
+    ("exito.muertes", "Deaths {0}"),
+    ("exito.record", "New record!"),
+    ("exito.puesto", "Rank #{0} on the table"),
+    ("exito.fantasma", "Against the ghost: {0}"),
+    ("perdio.titulo", "YOU LOST!"),
+    ("fin.volver", "{0}: back to menu"),
+    ("muerte.pozo", "You fell into a pit."),
+    ("muerte.enemigo", "An enemy got you."),
+    ("muerte.pinchos", "The spikes skewered you."),
+    ("muerte.lava", "You burned in the lava."),
+    ("muerte.veneno", "The poison finished you off."),
+    ("muerte.tiempo", "Time ran out."),
+    ("muerte.sin_vida", "You ran out of health."),
+    ("muerte.evento", "The level got you."),
+    ("regla.todas", "all the coins"),
+    ("regla.minimo", "at least {0} coins"),
+    ("regla.tiempo", "before {0}"),
+    ("regla.enemigos", "defeat every enemy"),
+    ("aviso.partida_guardada", "Game saved"),
+    ("aviso.partida_no_guardada", "Could not save the game: {0}"),
+    ("aviso.partida_cargada", "Game loaded"),
+    ("aviso.partida_no_cargada", "Could not load the game: {0}"),
+    ("aviso.rep_desconocida", "The replay is for an unknown level: {0}"),
+    ("aviso.sin_rep", "No replay to watch: {0}"),
+    ("aviso.rep_no_guardada", "Could not save the replay: {0}"),
+    ("aviso.gamepad_conectado", "Gamepad {0} connected: {1}"),
+    ("aviso.gamepad_desconectado", "Gamepad {0} disconnected"),
+    ("aviso.guardado_danado", "Save file damaged ({0}); starting over"),
+    ("aviso.guardado_no_leido", "Could not read the save file: {0}"),
+    ("aviso.guardado_no_escrito", "Could not save: {0}"),
+    ("aviso.controles_no_leidos", "Could not read the controls: {0}"),
+    ("aviso.controles_invalidos", "Invalid controles.txt; using the defaults"),
+    ("aviso.controles_lineas", "controles.txt: ignored lines {0}"),
+    ("aviso.controles_no_guardados", "Could not save the controls: {0}"),
+    ("aviso.opciones_no_leidas", "Could not read the options: {0}"),
+    ("aviso.opciones_invalidas", "Invalid opciones.txt; using the defaults"),
+    ("aviso.opciones_no_guardadas", "Could not save the options: {0}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argumentos_en_una_pasada() {
+        let l = Idioma::POR_DEFECTO;
+        // la clave no existe: la plantilla es la clave misma
+        assert_eq!(l.f("{0} y {1}", &[&"{1}", &2]), "{1} y 2");
+        assert_eq!(l.f("{1}{0}{1}", &[&"a", &"b"]), "bab");
+        assert_eq!(l.f("{2} {x} { {0", &[&1]), "{2} {x} { {0");
+    }
+}
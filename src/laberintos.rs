@@ -0,0 +1,322 @@
+//! Laberintos aleatorios con semilla. Primero se talla un laberinto perfecto
+//! sobre una grilla de celdas (backtracker recursivo, Prim, Kruskal o Wilson),
+//! después se abren lazos y callejones sin salida y al final se reparten la
+//! salida, monedas, antorchas y pozos (ver `generacion`).
+//!
+//! La celda (cx, cy) del laberinto es la celda (2·cx+1, 2·cy+1) del `Mapa`;
+//! las paredes entre celdas quedan en medio.
+//!
+//! La semilla se comparte entre jugadores, así que el azar es ChaCha8 (no
+//! `StdRng`, que puede cambiar entre versiones de rand) y los números se
+//! sortean como u32: un `usize` sale distinto en 32 y 64 bits.
+
+use crate::generacion::{celdas_libres, mas_lejana, repartir};
+use crate::mapas::{Generado, Nivel};
+use crate::motor::{celda, es_caminable, Mapa};
+use crate::reglas::Regla;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub const TIPO: &str = "laberinto";
+const TEXTURAS: [char; 6] = ['1', '2', '3', '4', '5', '6'];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algoritmo {
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+}
+
+impl Algoritmo {
+    pub const TODOS: [Algoritmo; 4] = [Algoritmo::Backtracker, Algoritmo::Prim, Algoritmo::Kruskal, Algoritmo::Wilson];
+}
+
+/// Configuración del generador
+#[derive(Clone, Copy)]
+pub struct Laberinto {
+    pub ancho: usize,                 // en celdas del laberinto (el mapa mide 2·ancho+1)
+    pub alto: usize,
+    pub algoritmo: Option<Algoritmo>, // None = lo elige la semilla
+    pub lazos: f32,                   // fracción de las paredes internas que se abren de más (0 = perfecto)
+    pub sin_callejones: f32,          // fracción de los callejones sin salida que se abren
+    pub monedas: usize,
+    pub antorchas: usize,
+    pub pozos: usize,
+}
+
+impl Default for Laberinto {
+    fn default() -> Self {
+        Self {
+            ancho: 12,
+            alto: 8,
+            algoritmo: None,
+            lazos: 0.08,
+            sin_callejones: 0.3,
+            monedas: 6,
+            antorchas: 5,
+            pozos: 4,
+        }
+    }
+}
+
+impl Laberinto {
+    pub fn generar(&self, semilla: u64) -> Nivel {
+        let mut r = ChaCha8Rng::seed_from_u64(semilla);
+        let algoritmo = self.algoritmo.unwrap_or(Algoritmo::TODOS[(semilla % 4) as usize]);
+
+        // paredes con una textura base y manchas de otra
+        let k = r.gen_range(0..TEXTURAS.len() as u32) as usize;
+        let base = TEXTURAS[k];
+        let mancha = TEXTURAS[(k + r.gen_range(1..TEXTURAS.len() as u32) as usize) % TEXTURAS.len()];
+        let mut g = Grilla::nueva(self.ancho.max(2), self.alto.max(2), base);
+
+        match algoritmo {
+            Algoritmo::Backtracker => g.backtracker(&mut r),
+            Algoritmo::Prim => g.prim(&mut r),
+            Algoritmo::Kruskal => g.kruskal(&mut r),
+            Algoritmo::Wilson => g.wilson(&mut r),
+        }
+        g.abrir_lazos(self.lazos, &mut r);
+        g.abrir_callejones(self.sin_callejones, &mut r);
+
+        for fila in g.mapa.iter_mut() {
+            for c in fila.iter_mut() {
+                if *c == base && r.gen_bool(0.15) { *c = mancha; }
+            }
+        }
+
+        // inicio en la primera celda, mirando hacia donde haya pasillo
+        let inicio = (1, 1);
+        let ang = if g.mapa[1][2] == ' ' { 0.0 } else { std::f32::consts::FRAC_PI_2 };
+        let mut mapa = g.mapa;
+        if let Some((x, y)) = mas_lejana(&mapa, inicio) {
+            mapa[y as usize][x as usize] = 'E';
+        }
+
+        // monedas primero en los callejones que quedaron, después en cualquier lado
+        let mut callejones = callejones(&mapa);
+        callejones.shuffle(&mut r);
+        let mut libres = celdas_libres(&mapa);
+        libres.shuffle(&mut r);
+        callejones.extend(libres.iter().copied());
+        let monedas = repartir(&mut mapa, inicio, &callejones, 'C', self.monedas);
+        repartir(&mut mapa, inicio, &libres, 'A', self.antorchas);
+        // pozos no pegados al inicio
+        let lejos: Vec<_> = libres.iter().copied().filter(|&(x, y)| (x - 1).abs() + (y - 1).abs() > 3).collect();
+        repartir(&mut mapa, inicio, &lejos, 'P', self.pozos);
+
+        Nivel {
+            id: format!("{TIPO}-{semilla}"),
+            titulo: None,
+            generado: Some(Generado { tipo: TIPO, semilla }),
+            mapa,
+            inicio: (inicio.0, inicio.1, ang),
+            reglas: if monedas > 0 { vec![Regla::TodasLasMonedas] } else { Vec::new() },
+            disparadores: Vec::new(),
+            teletransportes: Vec::new(),
+        }
+    }
+}
+
+/// Celdas de piso con una sola salida
+fn callejones(mapa: &Mapa) -> Vec<(i32, i32)> {
+    let abiertas = |x: i32, y: i32| {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+            .filter(|(dx, dy)| celda(mapa, x + dx, y + dy).is_some_and(es_caminable))
+            .count()
+    };
+    celdas_libres(mapa).into_iter().filter(|&(x, y)| abiertas(x, y) == 1).collect()
+}
+
+/// Laberinto en construcción: mapa todo pared y celdas que se van uniendo.
+struct Grilla {
+    ancho: usize,
+    alto: usize,
+    mapa: Mapa,
+}
+
+impl Grilla {
+    fn nueva(ancho: usize, alto: usize, pared: char) -> Self {
+        Self { ancho, alto, mapa: vec![vec![pared; 2 * ancho + 1]; 2 * alto + 1] }
+    }
+
+    fn pos(&self, i: usize) -> (usize, usize) {
+        (2 * (i % self.ancho) + 1, 2 * (i / self.ancho) + 1)
+    }
+
+    fn vecinas(&self, i: usize) -> Vec<usize> {
+        let (cx, cy) = (i % self.ancho, i / self.ancho);
+        let mut v = Vec::with_capacity(4);
+        if cx > 0 { v.push(i - 1); }
+        if cx + 1 < self.ancho { v.push(i + 1); }
+        if cy > 0 { v.push(i - self.ancho); }
+        if cy + 1 < self.alto { v.push(i + self.ancho); }
+        v
+    }
+
+    /// Une dos celdas vecinas (abre las dos y la pared del medio)
+    fn abrir(&mut self, a: usize, b: usize) {
+        let (ax, ay) = self.pos(a);
+        let (bx, by) = self.pos(b);
+        self.mapa[ay][ax] = ' ';
+        self.mapa[by][bx] = ' ';
+        self.mapa[(ay + by) / 2][(ax + bx) / 2] = ' ';
+    }
+
+    fn unidas(&self, a: usize, b: usize) -> bool {
+        let (ax, ay) = self.pos(a);
+        let (bx, by) = self.pos(b);
+        self.mapa[(ay + by) / 2][(ax + bx) / 2] == ' '
+    }
+
+    fn total(&self) -> usize {
+        self.ancho * self.alto
+    }
+
+    /// Backtracker recursivo (con pila): pasillos largos y pocas bifurcaciones.
+    fn backtracker(&mut self, r: &mut ChaCha8Rng) {
+        let mut visto = vec![false; self.total()];
+        let mut pila = vec![0];
+        visto[0] = true;
+        while let Some(&i) = pila.last() {
+            let libres: Vec<usize> = self.vecinas(i).into_iter().filter(|&v| !visto[v]).collect();
+            match libres.choose(r) {
+                Some(&v) => {
+                    self.abrir(i, v);
+                    visto[v] = true;
+                    pila.push(v);
+                }
+                None => { pila.pop(); }
+            }
+        }
+    }
+
+    /// Prim: crece desde el inicio tomando bordes al azar; muchas ramas cortas.
+    fn prim(&mut self, r: &mut ChaCha8Rng) {
+        let mut visto = vec![false; self.total()];
+        visto[0] = true;
+        let mut borde: Vec<(usize, usize)> = self.vecinas(0).into_iter().map(|v| (0, v)).collect();
+        while !borde.is_empty() {
+            let (a, b) = borde.swap_remove(r.gen_range(0..borde.len() as u32) as usize);
+            if visto[b] { continue; }
+            self.abrir(a, b);
+            visto[b] = true;
+            borde.extend(self.vecinas(b).into_iter().filter(|&v| !visto[v]).map(|v| (b, v)));
+        }
+    }
+
+    /// Kruskal: une celdas de conjuntos distintos recorriendo las paredes al azar.
+    fn kruskal(&mut self, r: &mut ChaCha8Rng) {
+        let mut padre: Vec<usize> = (0..self.total()).collect();
+        fn raiz(padre: &mut [usize], mut i: usize) -> usize {
+            while padre[i] != i {
+                padre[i] = padre[padre[i]];
+                i = padre[i];
+            }
+            i
+        }
+        let mut paredes: Vec<(usize, usize)> = (0..self.total())
+            .flat_map(|i| self.vecinas(i).into_iter().filter(move |&v| v > i).map(move |v| (i, v)))
+            .collect();
+        paredes.shuffle(r);
+        for (a, b) in paredes {
+            let (ra, rb) = (raiz(&mut padre, a), raiz(&mut padre, b));
+            if ra != rb {
+                padre[ra] = rb;
+                self.abrir(a, b);
+            }
+        }
+    }
+
+    /// Wilson: caminatas al azar con los lazos borrados; laberinto uniforme.
+    fn wilson(&mut self, r: &mut ChaCha8Rng) {
+        let n = self.total();
+        let mut dentro = vec![false; n];
+        dentro[0] = true;
+        let (ax, ay) = self.pos(0);
+        self.mapa[ay][ax] = ' ';
+        let mut orden: Vec<usize> = (1..n).collect();
+        orden.shuffle(r);
+        let mut siguiente = vec![0usize; n];
+        for inicio in orden {
+            if dentro[inicio] { continue; }
+            // caminar hasta tocar el laberinto; pisar de nuevo una celda borra el lazo
+            let mut i = inicio;
+            while !dentro[i] {
+                let v = *self.vecinas(i).choose(r).unwrap();
+                siguiente[i] = v;
+                i = v;
+            }
+            let mut i = inicio;
+            while !dentro[i] {
+                self.abrir(i, siguiente[i]);
+                dentro[i] = true;
+                i = siguiente[i];
+            }
+        }
+    }
+
+    /// Abre una fracción de las paredes internas que quedan entre celdas.
+    fn abrir_lazos(&mut self, fraccion: f32, r: &mut ChaCha8Rng) {
+        let mut cerradas: Vec<(usize, usize)> = (0..self.total())
+            .flat_map(|i| self.vecinas(i).into_iter().filter(move |&v| v > i).map(move |v| (i, v)))
+            .filter(|&(a, b)| !self.unidas(a, b))
+            .collect();
+        cerradas.shuffle(r);
+        let n = (cerradas.len() as f32 * fraccion.clamp(0.0, 1.0)).round() as usize;
+        for &(a, b) in cerradas.iter().take(n) {
+            self.abrir(a, b);
+        }
+    }
+
+    /// Cada callejón sin salida se abre a otra vecina con probabilidad `fraccion`.
+    fn abrir_callejones(&mut self, fraccion: f32, r: &mut ChaCha8Rng) {
+        for i in 0..self.total() {
+            let vecinas = self.vecinas(i);
+            let abiertas = vecinas.iter().filter(|&&v| self.unidas(i, v)).count();
+            if abiertas != 1 || !r.gen_bool(fraccion.clamp(0.0, 1.0) as f64) { continue; }
+            let cerradas: Vec<usize> = vecinas.into_iter().filter(|&v| !self.unidas(i, v)).collect();
+            if let Some(&v) = cerradas.choose(r) {
+                self.abrir(i, v);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generacion::asegurar_ganable;
+
+    #[test]
+    fn todos_se_pueden_ganar() {
+        for algoritmo in Algoritmo::TODOS {
+            let gen = Laberinto { algoritmo: Some(algoritmo), ..Default::default() };
+            asegurar_ganable(|s| gen.generar(s));
+        }
+    }
+
+    /// El laberinto de una semilla no puede cambiar: se comparte por id
+    const FOTO: [&str; 9] = [
+        "5555655555565",
+        "5    C5C6   5",
+        "5 5 556 5P5 5",
+        "5      A  6 6",
+        "55655 555 565",
+        "6       5   5",
+        "5 5 5 5 5 5 5",
+        "5     5 6  E5",
+        "5555555555556",
+    ];
+
+    #[test]
+    fn foto() {
+        let gen = Laberinto { ancho: 6, alto: 4, monedas: 2, antorchas: 1, pozos: 1, ..Default::default() };
+        let n = gen.generar(1234);
+        let filas: Vec<String> = n.mapa.iter().map(|f| f.iter().collect()).collect();
+        assert_eq!(filas, FOTO);
+        assert_eq!(n.inicio, (1, 1, 0.0));
+    }
+}
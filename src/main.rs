@@ -1,21 +1,93 @@
 //! Ray-caster simple – Javier
 //! Ahora con monedas (C) y pozos (P). Si pisas P, pierdes.
 //! Monedas se muestran como sprites y se recolectan al pasar por la celda.
+//! Enemigos (M) patrullan y persiguen al jugador si lo ven.
+//! El jugador tiene vida: pozos, pinchos, lava, veneno y enemigos la bajan.
+//! Armas: pistola (hitscan) y lanzador de cohetes; munición en cajas (B).
+//! Partículas: brillo de monedas, brasas, polvo de impactos y escombros.
+//! Cada nivel tiene reglas para poder salir y la salida muestra el puntaje.
+//! El progreso (niveles completados, récords) se guarda entre sesiones.
+//! F5/F9 guardan y cargan la partida en curso (`--partida <archivo>` al arrancar).
+//! Cada partida se graba; R en el menú (o `--repeticion <archivo>`) la repite.
+//! El mejor recorrido de cada nivel corre como fantasma, con parciales por moneda.
+//! Los controles se asignan por acción y se cambian desde el menú (C).
+//! Gamepads: se conectan en caliente, varios a la vez, y manejan los menús.
+//! ESC (o P) pausa la partida; desde la pausa se reinicia o se cambian las opciones.
+//! Todo el texto (HUD, menús) se escribe en el framebuffer con fuente de mapa de bits.
+//! Los textos salen de tablas por idioma (español e inglés) y se cambian desde las opciones.
+//! El menú ofrece un laberinto, una mazmorra y una cueva aleatorios; ←/→ cambian su semilla
+//! y `--nivel cueva-1234` arranca con una semilla compartida.
+//! El desafío del día es el mismo nivel para todos (semilla = fecha) y guarda sus propios récords.
+//! E en el menú abre el editor de niveles (N, uno nuevo); se prueba con P y se guarda en `niveles/`.
+//! Los niveles de `niveles/` y el atlas `texturas.png` se recargan en caliente al cambiar en disco.
+//! En `niveles/` también van mapas de Tiled (.tmx/.tmj): tiles y objetos se traducen por sus propiedades.
+//! Los disparadores del nivel ejecutan acciones al entrar: mensajes, puertas (D), enemigos, sonidos, variables.
+//! Los teletransportadores (T) llevan a otra celda; los que no tienen destino se emparejan entre sí.
+//! Los bloques (X) se empujan: tapan pozos y aprietan placas (_) que disparan eventos.
 
 mod motor;
+mod fuente;
 mod mapas;
+mod generacion;
+mod laberintos;
+mod mazmorras;
+mod cuevas;
+mod diario;
 mod sprites;
+mod enemigos;
+mod caminos;
+mod salud;
+mod armas;
+mod particulas;
+mod reglas;
+mod guardado;
+mod partida;
+mod repeticion;
+mod fantasma;
+mod controles;
+mod mandos;
+mod opciones;
+mod idiomas;
+mod editor;
+mod recarga;
+mod tiled;
+mod eventos;
+mod teletransportes;
+mod bloques;
 
 use motor::*;
+use fuente::*;
 use mapas::*;
-use sprites::*;
+use laberintos::*;
+use mazmorras::*;
+use cuevas::*;
+use armas::*;
+use particulas::*;
+use reglas::*;
+use guardado::*;
+use partida::*;
+use repeticion::*;
+use fantasma::*;
+use controles::*;
+use mandos::*;
+use opciones::*;
+use idiomas::*;
+use editor::*;
+use recarga::*;
 
 use raylib::prelude::*;
+use std::collections::HashMap;
 
 const W: u32 = 320;   // resolución lógica (ancha)
 const H: u32 = 200;   // resolución lógica (alta)
 const SCALE: i32 = 3; // factor de escala a la ventana
 const BLOQUE: usize = 1;
+const ADELANTO: usize = 8; // frames simulados por frame al adelantar una repetición
+const LISTA_Y: i32 = 56;   // primera fila de los menús de pausa y opciones (px del framebuffer)
+const LISTA_ALTO: i32 = 20;
+const NIVELES_VISIBLES: usize = 5;   // filas de la lista de niveles del menú
+const CONTROLES_VISIBLES: usize = 14; // filas de la pantalla de controles
+const OPCIONES_PAUSA: [&str; 4] = ["pausa.continuar", "pausa.reiniciar", "pausa.opciones", "pausa.salir"];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Estado {
@@ -23,46 +95,27 @@ enum Estado {
     Juego,
     Exito,
     Perdio,
-}
-
-// --- helpers de spawn seguro ---
-fn es_walkable(mapa: &Mapa, x: i32, y: i32) -> bool {
-    if let Some(c) = celda(mapa, x, y) {
-        // Piso libre o celdas caminables (incluye monedas y pozos)
-        c == ' ' || c == 'E' || c == 'A' || c == 'C' || c == 'P'
-    } else {
-        false
-    }
-}
-
-fn spawn_mas_cercano(mapa: &Mapa, sx: i32, sy: i32) -> (i32, i32) {
-    if es_walkable(mapa, sx, sy) {
-        return (sx, sy);
-    }
-    let max_r = (mapa.len() + mapa[0].len()) as i32;
-    for r in 1..=max_r {
-        for dy in -r..=r {
-            for dx in -r..=r {
-                let nx = sx + dx;
-                let ny = sy + dy;
-                if es_walkable(mapa, nx, ny) {
-                    return (nx, ny);
-                }
-            }
-        }
-    }
-    (1, 1)
+    Repeticion,
+    Controles,
+    Pausa,
+    Opciones,
+    Editor,
 }
 
 fn main() {
+    // --- opciones (antes de abrir la ventana, que depende de la escala) ---
+    let mut opciones = Opciones::cargar(SCALE);
+    let mut escala_ventana = opciones.escala;
+
     // --- ventana ---
     let (mut rl, thread) = raylib::init()
-        .size((W as i32) * SCALE, (H as i32) * SCALE)
+        .size((W as i32) * escala_ventana, (H as i32) * escala_ventana)
         .title("Raycaster – Javier")
         .resizable()
         .build();
 
     rl.set_target_fps(60);
+    rl.set_exit_key(None); // ESC pausa; la ventana se cierra con la X
     rl.set_mouse_scale(1.0, 1.0);
     rl.set_mouse_cursor(raylib::consts::MouseCursor::MOUSE_CURSOR_CROSSHAIR);
 
@@ -72,24 +125,83 @@ fn main() {
     // --- estados ---
     let mut estado = Estado::Menu;
     let mut idx_nivel = 0usize;
-    let niveles = niveles();
-
-    // --- jugador ---
-    let mut jug = Jugador {
-        x: 2.5,
-        y: 2.5,
-        ang: 0.0,
-        vel: 2.0 / 60.0,
-        rot: 2.2 / 60.0,
-    };
+    let mut niveles = niveles();
+    for e in cargar_archivos(&mut niveles) {
+        eprintln!("{e}");
+    }
+    niveles.push(diario::generar(diario::hoy()));
+    niveles.push(Laberinto::default().generar(semilla_nueva() % 100_000));
+    niveles.push(Mazmorra::default().generar(semilla_nueva() % 100_000));
+    niveles.push(Cueva::default().generar(semilla_nueva() % 100_000));
+    for n in &niveles {
+        for p in n.validar() {
+            eprintln!("[{}] {}", n.id, p);
+        }
+    }
+
+    // --- partida en curso (jugador, sprites, enemigos, armas, progreso) ---
+    let mut partida = Partida::nueva(&niveles, 0, 0, 0); // se rehace al entrar al nivel
 
-    // --- texturas y sprites ---
+    // --- texturas y partículas ---
     let mut tex = Texturas::nuevo();
-    let mut spr = Sprites::nuevo();       // frames y lista vacía; se llena al entrar al nivel
+    if ruta_atlas().exists() {
+        if let Err(e) = tex.cargar_atlas(&ruta_atlas()) {
+            eprintln!("No se pudo cargar '{}': {e}", ruta_atlas().display());
+        }
+    }
+    let mut part = Particulas::nuevo();
+
+    // --- resultados ---
+    let mut muertes = vec![0u32; niveles.len()]; // intentos fallidos por nivel desde la última victoria
+    let mut desglose: Option<Desglose> = None;
+    let mut motivo = "";                          // por qué perdió (clave de `idiomas`)
+    let mut aviso: (Mensaje, u32) = (Mensaje::default(), 0); // mensaje en pantalla y frames restantes
+
+    // --- guardado persistente ---
+    let mut guardado = Guardado::cargar();
+    let mut puesto: Option<usize> = None; // lugar en la tabla de puntajes de la última victoria
+
+    // --- grabación de la partida en curso y repetición ---
+    let mut grab: Option<Grabacion> = None;
+    let mut rep: Option<Reproductor> = None;
+    let mut rep_pendiente: Option<Grabacion> = None; // se arranca al principio del próximo frame
+
+    // --- fantasma del mejor recorrido y parciales contra él ---
+    let mut fantasma: Option<Fantasma> = None;
+    let mut parcial: (String, Color, u32) = (String::new(), Color::WHITE, 0); // texto, color y frames restantes
+    let mut contra_fantasma: Option<i64> = None; // diferencia final en frames (negativa = más rápido)
+
+    // --- partida pasada por línea de comandos (para reproducir reportes) ---
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(ruta) = args.iter().position(|a| a == "--partida").and_then(|i| args.get(i + 1)) {
+        match Partida::cargar(std::path::Path::new(ruta), &mut niveles) {
+            Ok(p) => {
+                partida = p;
+                idx_nivel = partida.nivel;
+                part.rellenar_desde_mapa(&partida.mapa, partida.semilla);
+                fantasma = Fantasma::cargar(&niveles, idx_nivel);
+                estado = Estado::Juego;
+            }
+            Err(e) => eprintln!("No se pudo cargar la partida '{ruta}': {e}"),
+        }
+    }
+    if let Some(id) = args.iter().position(|a| a == "--nivel").and_then(|i| args.get(i + 1)) {
+        match buscar_o_generar(&mut niveles, id) {
+            Some(i) => idx_nivel = i,
+            None => eprintln!("Nivel desconocido '{id}'"),
+        }
+    }
+    if let Some(ruta) = args.iter().position(|a| a == "--repeticion").and_then(|i| args.get(i + 1)) {
+        match Grabacion::cargar(std::path::Path::new(ruta)) {
+            Ok(g) => rep_pendiente = Some(g),
+            Err(e) => eprintln!("No se pudo cargar la repetición '{ruta}': {e}"),
+        }
+    }
 
-    // --- monedas ---
-    let mut coins_total: usize = 0;
-    let mut coins_taken: usize = 0;
+    // --- controles (teclado, mouse y gamepad por acción) ---
+    let mut controles = Controles::cargar();
+    let mut sel_control = 0usize;   // fila elegida en la pantalla de controles
+    let mut esperando_boton = false; // esperando la tecla/botón a asignar
 
     // --- mouse look ---
     let mut mouse_on = true;
@@ -99,62 +211,287 @@ fn main() {
     ));
     let mut prev_mouse_x: i32 = (W as i32 * SCALE) / 2;
 
-    // --- gamepad ---
-    let mut gamepad_id = None;
-    for id in 0..4 {
-        if rl.is_gamepad_available(id) {
-            gamepad_id = Some(id);
-            break;
-        }
-    }
+    // --- gamepads (se revisan en cada frame) ---
+    let mut mandos = Mandos::nuevo();
+
+    // --- audio, pausa y pantalla de opciones ---
+    let mut audio = RaylibAudio::init_audio_device();
+    audio.set_master_volume(opciones.volumen);
+    let mut sonidos: HashMap<String, Option<Sound>> = HashMap::new(); // los de los disparadores, al usarlos
+    let mut empezar_nivel = false;          // se arranca al principio del próximo frame
+    let mut congelado: Option<Framebuffer> = None; // último cuadro de la partida en pausa, de fondo
+    let mut sel_pausa = 0usize;
+    let mut sel_opcion = 0usize;
+    let mut opciones_desde = Estado::Menu;  // adónde vuelve la pantalla de opciones
+    let mut controles_desde = Estado::Menu; // y la de controles
+
+    // --- editor de niveles (la prueba va al final de `niveles` y se saca al volver) ---
+    let mut editor: Option<Editor> = None;
+    let mut probando = false;
+    let mut nivel_editado = 0usize; // idx_nivel antes de probar
+
+    // --- recarga en caliente (se sondean las fechas de los archivos) ---
+    let vigilados = || {
+        let mut v = rutas_niveles();
+        v.push(ruta_atlas());
+        v
+    };
+    let mut vigilante = Vigilante::nuevo(vigilados());
 
     // --- bucle principal ---
     while !rl.window_should_close() {
-        let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::BLACK);
-        fb.limpiar(Color::BLACK);
+        if opciones.escala != escala_ventana {
+            escala_ventana = opciones.escala;
+            rl.set_window_size(W as i32 * escala_ventana, H as i32 * escala_ventana);
+        }
+        let mut ventana = rl.begin_drawing(&thread);
+        ventana.clear_background(Color::BLACK);
+        // todo se dibuja en coordenadas de la escala original y la cámara lo
+        // ajusta al tamaño real de la ventana
+        let zoom = (ventana.get_screen_width() as f32 / (W as i32 * SCALE) as f32)
+            .min(ventana.get_screen_height() as f32 / (H as i32 * SCALE) as f32);
+        let mut d = ventana.begin_mode2D(Camera2D {
+            offset: Vector2::zero(),
+            target: Vector2::zero(),
+            rotation: 0.0,
+            zoom,
+        });
+        match &congelado {
+            Some(f) => fb.clone_from(f),
+            None => fb.limpiar(Color::BLACK),
+        }
+        let l = opciones.idioma;
+        aviso.1 = aviso.1.saturating_sub(1);
+        parcial.2 = parcial.2.saturating_sub(1);
+
+        // --- gamepads y navegación de menús ---
+        if let Some(m) = mandos.actualizar(&d).pop() {
+            aviso = (m, 150);
+        }
+        let nav = mandos.nav(&d, &controles, zoom * SCALE as f32);
+
+        // --- fin de la prueba del editor ---
+        if probando && estado == Estado::Editor {
+            niveles.pop();
+            muertes.pop();
+            idx_nivel = nivel_editado;
+            probando = false;
+        }
+
+        // --- recarga en caliente de niveles y texturas ---
+        for ruta in vigilante.revisar(vigilados) {
+            if ruta == ruta_atlas() {
+                // si se borró, vuelven las texturas generadas
+                tex = Texturas::nuevo();
+                let res = if ruta.exists() { tex.cargar_atlas(&ruta) } else { Ok(()) };
+                aviso = match res {
+                    Ok(()) => (Mensaje::nuevo("aviso.texturas_recargadas"), 90),
+                    Err(e) => (Mensaje::con("aviso.texturas_error", &[&e]), 180),
+                };
+                continue;
+            }
+            if !ruta.exists() { continue; } // un nivel borrado sigue en la lista hasta reiniciar
+            match Nivel::cargar(&ruta) {
+                Ok(n) => {
+                    aviso = (Mensaje::con("aviso.nivel_recargado", &[&n.nombre(l)]), 90);
+                    let (i, nuevo) = poner_nivel(&mut niveles, n);
+                    if nuevo {
+                        muertes.insert(i, 0);
+                        for k in [&mut idx_nivel, &mut nivel_editado, &mut partida.nivel] {
+                            if *k >= i { *k += 1; }
+                        }
+                    }
+                    // la partida en curso de ese nivel sigue con el mapa nuevo
+                    if partida.nivel == i && matches!(estado, Estado::Juego | Estado::Pausa) {
+                        partida.recargar(&niveles[i]);
+                        part.rellenar_desde_mapa(&partida.mapa, partida.semilla);
+                        grab = None; // con otro mapa la grabación ya no se puede repetir
+                    }
+                }
+                Err(e) => aviso = (Mensaje::con("aviso.nivel_error", &[&ruta.display(), &e]), 180),
+            }
+        }
+
+        // --- partida rápida (no mientras se prueba un nivel del editor) ---
+        if estado == Estado::Juego && !probando && d.is_key_pressed(KeyboardKey::KEY_F5) {
+            aviso = match partida.guardar(&ruta_partida_rapida(), &niveles) {
+                Ok(()) => (Mensaje::nuevo("aviso.partida_guardada"), 90),
+                Err(e) => (Mensaje::con("aviso.partida_no_guardada", &[&e]), 180),
+            };
+        }
+        if matches!(estado, Estado::Menu | Estado::Juego) && !probando && d.is_key_pressed(KeyboardKey::KEY_F9) {
+            match Partida::cargar(&ruta_partida_rapida(), &mut niveles) {
+                Ok(p) => {
+                    partida = p;
+                    idx_nivel = partida.nivel;
+                    part.rellenar_desde_mapa(&partida.mapa, partida.semilla);
+                    grab = None; // la grabación solo sirve desde el inicio del nivel
+                    fantasma = Fantasma::cargar(&niveles, idx_nivel);
+                    aviso = (Mensaje::nuevo("aviso.partida_cargada"), 90);
+                    estado = Estado::Juego;
+                }
+                Err(e) => aviso = (Mensaje::con("aviso.partida_no_cargada", &[&e]), 180),
+            }
+        }
+
+        // --- arrancar repetición ---
+        if let Some(g) = rep_pendiente.take() {
+            match buscar_o_generar(&mut niveles, &g.nivel) {
+                Some(i) => {
+                    idx_nivel = i;
+                    partida = Partida::nueva(&niveles, i, g.muertes, g.semilla);
+                    part.rellenar_desde_mapa(&partida.mapa, partida.semilla);
+                    grab = None;
+                    fantasma = None;
+                    rep = Some(Reproductor::nuevo(g));
+                    estado = Estado::Repeticion;
+                }
+                None => aviso = (Mensaje::con("aviso.rep_desconocida", &[&g.nivel]), 180),
+            }
+        }
+
+        // --- arrancar (o reiniciar) el nivel elegido ---
+        if std::mem::take(&mut empezar_nivel) {
+            partida = Partida::nueva(&niveles, idx_nivel, muertes[idx_nivel], semilla_nueva());
+            aviso.1 = 0;
+            part.rellenar_desde_mapa(&partida.mapa, partida.semilla);
+            // las pruebas del editor no se graban ni tienen fantasma
+            grab = (!probando).then(|| Grabacion::nueva(&niveles[idx_nivel].id, partida.semilla, muertes[idx_nivel]));
+            fantasma = if probando { None } else { Fantasma::cargar(&niveles, idx_nivel) };
+            parcial.2 = 0;
+            congelado = None;
+            prev_mouse_x = d.get_mouse_x();
+
+            estado = Estado::Juego;
+        }
 
         match estado {
             Estado::Menu => {
-                if d.is_key_pressed(KeyboardKey::KEY_DOWN) {
+                if nav.abajo {
                     idx_nivel = (idx_nivel + 1) % niveles.len();
                 }
-                if d.is_key_pressed(KeyboardKey::KEY_UP) {
+                if nav.arriba {
                     idx_nivel = (idx_nivel + niveles.len() - 1) % niveles.len();
                 }
-                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                    // Spawn seguro
-                    let mapa = &niveles[idx_nivel].mapa;
-                    let (sx, sy, ang0) = niveles[idx_nivel].inicio;
-                    let (fx, fy) = spawn_mas_cercano(mapa, sx, sy);
-                    jug.x = fx as f32 + 0.5;
-                    jug.y = fy as f32 + 0.5;
-                    jug.ang = ang0;
-
-                    // Rellenar sprites desde mapa (A = antorcha, C = coin, P = pozo)
-                    coins_total = spr.rellenar_desde_mapa(mapa);
-                    coins_taken = 0;
+                // un nivel se desbloquea al completar el anterior; los generados siempre están abiertos
+                let bloqueado = idx_nivel > 0 && niveles[idx_nivel].generado.is_none()
+                    && !guardado.completado(&niveles[idx_nivel - 1].id);
+                // el del día cambia solo al cambiar la fecha; los otros generados, con ←/→
+                for (i, n) in niveles.iter_mut().enumerate() {
+                    if n.generado.is_some_and(|g| g.tipo == diario::TIPO && g.semilla != diario::hoy()) {
+                        *n = diario::generar(diario::hoy());
+                        muertes[i] = 0;
+                    }
+                }
+                if let Some(g) = niveles[idx_nivel].generado.filter(|g| g.tipo != diario::TIPO) {
+                    let paso = nav.derecha as i64 - nav.izquierda as i64;
+                    if paso != 0 {
+                        if let Some(n) = generar(g.tipo, g.semilla.wrapping_add_signed(paso)) {
+                            niveles[idx_nivel] = n;
+                            muertes[idx_nivel] = 0;
+                        }
+                    }
+                }
+                if nav.usar && !bloqueado {
+                    empezar_nivel = true;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_R) {
+                    match Grabacion::cargar(&ruta_ultima_repeticion()) {
+                        Ok(g) => rep_pendiente = Some(g),
+                        Err(e) => aviso = (Mensaje::con("aviso.sin_rep", &[&e]), 180),
+                    }
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_C) {
+                    sel_control = 0;
+                    esperando_boton = false;
+                    controles_desde = Estado::Menu;
+                    estado = Estado::Controles;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_O) || controles.pulsado(&d, &mandos.ids, Accion::Pausa) {
+                    sel_opcion = 0;
+                    opciones_desde = Estado::Menu;
+                    estado = Estado::Opciones;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_E) {
+                    editor = Some(Editor::nuevo(&niveles[idx_nivel], l));
+                    estado = Estado::Editor;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_N) {
+                    let id = (1..).map(|k| format!("propio-{k}")).find(|id| niveles.iter().all(|n| &n.id != id)).unwrap();
+                    editor = Some(Editor::vacio(id, 18, 10));
+                    estado = Estado::Editor;
+                }
+            }
 
-                    estado = Estado::Juego;
+            Estado::Editor => {
+                let usar_abajo = controles.abajo(&d, &mandos.ids, Accion::Usar);
+                let pedido = match editor.as_mut() {
+                    Some(ed) => ed.actualizar(&d, &nav, usar_abajo),
+                    None => Some(Pedido::Salir),
+                };
+                match pedido {
+                    Some(Pedido::Probar(n)) => {
+                        nivel_editado = idx_nivel;
+                        niveles.push(n);
+                        muertes.push(0);
+                        idx_nivel = niveles.len() - 1;
+                        probando = true;
+                        empezar_nivel = true;
+                    }
+                    Some(Pedido::Guardar(n)) => {
+                        let problemas = n.validar();
+                        let res = n.guardar();
+                        let aviso_ed = match &res {
+                            Ok(ruta) if problemas.is_empty() => Mensaje::con("editor.guardado", &[&ruta.display()]),
+                            Ok(_) => Mensaje::con("editor.guardado_con_problemas", &[&problemas[0]]),
+                            Err(e) => Mensaje::con("editor.no_guardado", &[e]),
+                        };
+                        if let Some(ed) = editor.as_mut() { ed.guardado(res.is_ok(), aviso_ed); }
+                        // el nivel guardado aparece en el menú
+                        let (i, nuevo) = poner_nivel(&mut niveles, n);
+                        if nuevo {
+                            muertes.insert(i, 0);
+                            if i <= idx_nivel { idx_nivel += 1; }
+                        }
+                    }
+                    Some(Pedido::Salir) => {
+                        editor = None;
+                        estado = Estado::Menu;
+                    }
+                    None => {}
+                }
+                if let Some(ed) = &editor {
+                    ed.dibujar(&mut fb, l);
                 }
             }
 
             Estado::Juego => {
-                let mapa = &niveles[idx_nivel].mapa;
-
                 // --- teclado ---
                 let mut dir_x = 0.0;
                 let mut dir_y = 0.0;
-
-                if d.is_key_down(KeyboardKey::KEY_W) { dir_y += 1.0; }
-                if d.is_key_down(KeyboardKey::KEY_S) { dir_y -= 1.0; }
-                if d.is_key_down(KeyboardKey::KEY_A) { dir_x -= 1.0; }
-                if d.is_key_down(KeyboardKey::KEY_D) { dir_x += 1.0; }
-                if d.is_key_down(KeyboardKey::KEY_Q) { jug.ang -= jug.rot; }
-                if d.is_key_down(KeyboardKey::KEY_E) { jug.ang += jug.rot; }
+                let mut giro = 0.0;
+                let mut arma = None;
+
+                let abajo = |a| controles.abajo(&d, &mandos.ids, a);
+                let pulsado = |a| controles.pulsado(&d, &mandos.ids, a);
+                if abajo(Accion::Avanzar) { dir_y += 1.0; }
+                if abajo(Accion::Retroceder) { dir_y -= 1.0; }
+                if abajo(Accion::PasoIzq) { dir_x -= 1.0; }
+                if abajo(Accion::PasoDer) { dir_x += 1.0; }
+                if abajo(Accion::GirarIzq) { giro -= partida.jug.rot; }
+                if abajo(Accion::GirarDer) { giro += partida.jug.rot; }
+                if pulsado(Accion::Pistola) { arma = Some(TipoArma::Pistola); }
+                if pulsado(Accion::Lanzador) { arma = Some(TipoArma::Lanzador); }
+                if pulsado(Accion::CambiarArma) {
+                    arma = Some(match arma.unwrap_or(partida.armas.actual) {
+                        TipoArma::Pistola => TipoArma::Lanzador,
+                        TipoArma::Lanzador => TipoArma::Pistola,
+                    });
+                }
+                let disparo = pulsado(Accion::Disparar);
 
                 // --- mouse toggle ---
-                if d.is_key_pressed(KeyboardKey::KEY_M) {
+                if pulsado(Accion::Mouse) {
                     mouse_on = !mouse_on;
                     prev_mouse_x = d.get_mouse_x();
                 }
@@ -163,110 +500,404 @@ fn main() {
                 if mouse_on {
                     let mx = d.get_mouse_x();
                     let dx = mx - prev_mouse_x;
-                    jug.ang += (dx as f32) * 0.0035;
+                    giro += (dx as f32) * controles.sens_mouse;
                     prev_mouse_x = mx;
                 }
 
-                // --- gamepad ---
-                if let Some(id) = gamepad_id {
-                    let lx = d.get_gamepad_axis_movement(id, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
-                    let ly = d.get_gamepad_axis_movement(id, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
-                    dir_x += lx as f32;
-                    dir_y += -ly as f32;
-
-                    let rx = d.get_gamepad_axis_movement(id, GamepadAxis::GAMEPAD_AXIS_RIGHT_X);
-                    jug.ang += (rx as f32) * 0.04;
+                // --- gamepads (ejes; los botones van por acción) ---
+                dir_x += controles.eje(&d, &mandos.ids, controles.eje_lateral);
+                dir_y -= controles.eje(&d, &mandos.ids, controles.eje_avance);
+                giro += controles.eje(&d, &mandos.ids, controles.eje_giro) * controles.sens_pad;
+
+                // --- simulación (lo único que se graba es la entrada) ---
+                let entrada = Entrada::nueva(dir_x, dir_y, giro, disparo, arma);
+                if let Some(g) = &mut grab { g.entradas.push(entrada); }
+                let monedas_antes = partida.prog.monedas;
+                let mut nuevo_mejor = false;
+                match partida.actualizar(&entrada, &niveles[idx_nivel].reglas, &mut part) {
+                    Resultado::Sigue => {}
+                    Resultado::Falta(faltan) => {
+                        if aviso.1 == 0 {
+                            let txt: Vec<String> = faltan.iter().map(|r| r.descripcion(l)).collect();
+                            aviso = (Mensaje::con("hud.falta", &[&txt.join(", ")]), 120);
+                        }
+                    }
+                    Resultado::Gano => {
+                        let prog = &partida.prog;
+                        let p = puntaje(prog);
+                        desglose = Some(p);
+                        let id = &niveles[idx_nivel].id;
+                        nuevo_mejor = guardado.nivel(id).and_then(|n| n.mejor_tiempo).is_none_or(|t| prog.frames < t);
+                        contra_fantasma = fantasma.as_ref().map(|f| prog.frames as i64 - f.frames() as i64);
+                        puesto = None;
+                        if !probando {
+                            puesto = guardado.registrar(&niveles[idx_nivel].id, Marca {
+                                puntaje: p.total,
+                                frames: prog.frames,
+                                monedas: prog.monedas,
+                            });
+                            guardado.guardar();
+                        }
+                        muertes[idx_nivel] = 0;
+                        estado = Estado::Exito;
+                    }
+                    Resultado::Perdio(m) => {
+                        motivo = m;
+                        muertes[idx_nivel] += 1;
+                        estado = Estado::Perdio;
+                    }
                 }
 
-                // normalizar input
-                let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
-                if len > 0.01 { dir_x /= len; dir_y /= len; }
+                // parcial contra el fantasma al juntar cada moneda
+                let k = partida.prog.monedas;
+                if k > monedas_antes {
+                    if let Some(&g) = fantasma.as_ref().and_then(|f| f.parciales.get(k - 1)) {
+                        let dif = partida.prog.frames as i64 - g as i64;
+                        let col = if dif <= 0 { Color::LIME } else { Color::RED };
+                        parcial = (l.f("hud.parcial", &[&k, &formato_diferencia(dif)]), col, 150);
+                    }
+                }
 
-                // mover con colisiones (C y P son caminables)
-                mover_con_colision(&mut jug, dir_x, dir_y, &mapa);
+                let extra: Vec<_> = fantasma.as_ref().and_then(|f| f.sprite(partida.prog.frames)).into_iter().collect();
+                partida.jug.fov = opciones.fov();
+                partida.dibujar(&mut fb, &mut tex, &mut part, &extra);
+                if let Some(f) = &fantasma {
+                    f.dibujar_en_minimapa(&mut fb, partida.prog.frames);
+                }
 
-                // ¿cayó en pozo?
-                if let Some(c) = celda(mapa, jug.x as i32, jug.y as i32) {
-                    if c == 'P' {
-                        estado = Estado::Perdio;
+                // fin del nivel: guardar la grabación para poder verla (y como
+                // fantasma si es el mejor tiempo)
+                if estado != Estado::Juego {
+                    if let Some(mut g) = grab.take() {
+                        g.huella = partida.huella();
+                        let mut res = g.guardar(&ruta_ultima_repeticion());
+                        if nuevo_mejor {
+                            res = res.and_then(|_| g.guardar(&ruta_fantasma(&g.nivel)));
+                        }
+                        if let Err(e) = res {
+                            aviso = (Mensaje::con("aviso.rep_no_guardada", &[&e]), 180);
+                        }
                     }
                 }
 
-                // ¿recogió moneda(s) en la celda?
-                let recogidas = spr.recolectar_monedas_en(jug.x, jug.y);
-                if recogidas > 0 { coins_taken += recogidas; }
+                // pausa: la simulación se congela y el último cuadro queda de fondo
+                if estado == Estado::Juego
+                    && (controles.pulsado(&d, &mandos.ids, Accion::Pausa) || d.is_key_pressed(KeyboardKey::KEY_ESCAPE)) {
+                    sel_pausa = 0;
+                    let mut f = fb.clone();
+                    f.oscurecer(0.35);
+                    congelado = Some(f);
+                    estado = Estado::Pausa;
+                }
+            }
 
-                // raycasting paredes + zbuffer
-                let mut zbuf = vec![f32::INFINITY; W as usize];
-                dibujar_escena(&mut fb, &jug, &mapa, &mut tex, &mut zbuf);
+            Estado::Repeticion => {
+                if let Some(r) = rep.as_mut() {
+                    if controles.pulsado(&d, &mandos.ids, Accion::Pausa) { r.pausa = !r.pausa; }
+                    // pausado: '.' (o derecha) avanza de a un frame; sin pausa, derecha adelanta
+                    let pasos = if r.fin.is_some() { 0 }
+                                else if r.pausa { (d.is_key_pressed(KeyboardKey::KEY_PERIOD) || nav.derecha) as usize }
+                                else if controles.abajo(&d, &mandos.ids, Accion::Derecha) { ADELANTO }
+                                else { 1 };
+                    for _ in 0..pasos {
+                        let Some(e) = r.siguiente() else {
+                            r.fin = Some(("rep.fin", true));
+                            break;
+                        };
+                        let clave = match partida.actualizar(&e, &niveles[idx_nivel].reglas, &mut part) {
+                            Resultado::Gano => "rep.salida",
+                            Resultado::Perdio(m) => m,
+                            _ => continue,
+                        };
+                        // la huella dice si la simulación dio exactamente lo mismo
+                        let igual = r.grab.huella == 0 || partida.huella() == r.grab.huella;
+                        r.fin = Some((clave, igual));
+                        break;
+                    }
+                }
+                partida.jug.fov = opciones.fov();
+                partida.dibujar(&mut fb, &mut tex, &mut part, &[]);
+                if nav.volver {
+                    rep = None;
+                    estado = Estado::Menu;
+                }
+            }
 
-                // sprites (antorcha/monedas/pozos)
-                spr.actualizar();
-                dibujar_sprites(&mut fb, &jug, &mapa, &spr, &zbuf);
+            Estado::Controles => {
+                let filas = controles.filas();
+                let fila = filas[sel_control];
+                if esperando_boton {
+                    if let Some(b) = boton_pulsado(&d, &mandos.ids) {
+                        match fila {
+                            Fila::Accion(a) if b != Boton::Tecla(KeyboardKey::KEY_BACKSPACE) => controles.asignar(a, b),
+                            _ => {}
+                        }
+                        esperando_boton = false;
+                    }
+                } else {
+                    if nav.abajo { sel_control = (sel_control + 1) % filas.len(); }
+                    if nav.arriba { sel_control = (sel_control + filas.len() - 1) % filas.len(); }
+                    if nav.derecha { controles.ajustar_fila(fila, 1); }
+                    if nav.izquierda { controles.ajustar_fila(fila, -1); }
+                    if nav.usar {
+                        match fila {
+                            Fila::Accion(_) => esperando_boton = true,
+                            _ => controles.ajustar_fila(fila, 1),
+                        }
+                    }
+                    if d.is_key_pressed(KeyboardKey::KEY_DELETE) { controles.restaurar(); }
+                    if nav.volver {
+                        controles.guardar();
+                        estado = controles_desde;
+                    }
+                }
+            }
 
-                // minimapa
-                dibujar_minimapa(&mut fb, &jug, &mapa);
+            Estado::Pausa => {
+                let n = OPCIONES_PAUSA.len();
+                if nav.abajo { sel_pausa = (sel_pausa + 1) % n; }
+                if nav.arriba { sel_pausa = (sel_pausa + n - 1) % n; }
+                let sobre = nav.fila(LISTA_Y, LISTA_ALTO, n);
+                if let Some(i) = sobre.filter(|_| nav.movio || nav.clic) { sel_pausa = i; }
+                let elegir = nav.usar || (nav.clic && sobre.is_some());
+                let seguir = nav.volver || controles.pulsado(&d, &mandos.ids, Accion::Pausa);
+
+                if seguir || (elegir && sel_pausa == 0) {
+                    congelado = None;
+                    prev_mouse_x = d.get_mouse_x(); // que el mouse no gire de golpe
+                    estado = Estado::Juego;
+                } else if elegir && sel_pausa == 1 {
+                    empezar_nivel = true;
+                } else if elegir && sel_pausa == 2 {
+                    sel_opcion = 0;
+                    opciones_desde = Estado::Pausa;
+                    estado = Estado::Opciones;
+                } else if elegir {
+                    grab = None; // la partida abandonada no queda grabada
+                    congelado = None;
+                    estado = if probando { Estado::Editor } else { Estado::Menu };
+                }
+            }
 
-                // éxito si toca 'E' (no depende de las monedas, pero podés exigir todas si querés)
-                if let Some(c) = celda(&mapa, jug.x as i32, jug.y as i32) {
-                    if c == 'E' {
-                        estado = Estado::Exito;
+            Estado::Opciones => {
+                let n = Opcion::TODAS.len();
+                if nav.abajo { sel_opcion = (sel_opcion + 1) % n; }
+                if nav.arriba { sel_opcion = (sel_opcion + n - 1) % n; }
+                let sobre = nav.fila(LISTA_Y, LISTA_ALTO, n);
+                if let Some(i) = sobre.filter(|_| nav.movio || nav.clic) { sel_opcion = i; }
+                let o = Opcion::TODAS[sel_opcion];
+                if nav.derecha { opciones.ajustar(o, 1, &mut controles); }
+                if nav.izquierda { opciones.ajustar(o, -1, &mut controles); }
+                if nav.usar || (nav.clic && sobre.is_some()) {
+                    if o == Opcion::Controles {
+                        sel_control = 0;
+                        esperando_boton = false;
+                        controles_desde = Estado::Opciones;
+                        estado = Estado::Controles;
+                    } else {
+                        opciones.ajustar(o, 1, &mut controles);
                     }
                 }
+                audio.set_master_volume(opciones.volumen);
+                if nav.volver {
+                    opciones.guardar();
+                    controles.guardar();
+                    estado = opciones_desde;
+                }
             }
 
-            Estado::Exito => { /* UI abajo */ }
-            Estado::Perdio => { /* UI abajo */ }
+            Estado::Exito | Estado::Perdio => {
+                if nav.usar {
+                    estado = if probando { Estado::Editor } else { Estado::Menu };
+                }
+            }
         }
 
-        // pintar framebuffer
-        fb.pintar(&mut d, SCALE);
+        // lo que pidieron los disparadores (jugando o en la repetición)
+        if let Some(t) = partida.eventos.mensajes.drain(..).next_back() {
+            aviso = (Mensaje::con("evento.mensaje", &[&t]), 150);
+        }
+        for nombre in partida.eventos.sonidos.drain(..) {
+            let s = sonidos.entry(nombre).or_insert_with_key(|n| {
+                let ruta = format!("sonidos/{n}.wav");
+                Sound::load_sound(&ruta).map_err(|e| eprintln!("No se pudo cargar '{ruta}': {e}")).ok()
+            });
+            if let Some(s) = s { audio.play_sound(s); }
+        }
 
-        // HUD/UI
+        // HUD y menús: se escriben en el framebuffer, a la resolución interna
+        let (w, h) = (W as i32, H as i32);
+        let centro = Estilo::default().centrado();
         match estado {
-            Estado::Menu => {
-                let centro_x = (W as i32 * SCALE) / 2;
-                let mut y = 70;
-                d.draw_text("RAYCASTER – BIENVENIDO", centro_x - 140, 20, 24, Color::RAYWHITE);
-                d.draw_text("Usa ↑/↓ para elegir nivel y ENTER para iniciar",
-                            centro_x - 190, 45, 12, Color::LIGHTGRAY);
-
-                for (i, n) in niveles.iter().enumerate() {
-                    let marca = if i == idx_nivel { "> " } else { "  " };
-                    let txt = format!("{marca}{}", n.nombre);
-                    d.draw_text(&txt, centro_x - 120, y, 20,
-                                if i==idx_nivel { Color::YELLOW } else { Color::GRAY });
-                    y += 22;
+            Estado::Juego | Estado::Repeticion => {
+                let arma = match partida.armas.actual {
+                    TipoArma::Pistola => l.t("hud.pistola"),
+                    TipoArma::Lanzador => l.t("hud.lanzador"),
+                };
+                let reloj = match partida.prog.tiempo_restante(&niveles[idx_nivel].reglas) {
+                    Some(s) => l.f("hud.quedan", &[&formato_tiempo(s * 60)]),
+                    None => l.f("hud.tiempo", &[&formato_tiempo(partida.prog.frames)]),
+                };
+                let mut lineas = vec![
+                    (l.f("hud.fps", &[&d.get_fps()]), Color::WHITE),
+                    (l.f("hud.monedas", &[&partida.prog.monedas, &partida.prog.monedas_total]), Color::YELLOW),
+                    (l.f("hud.vida", &[&partida.jug.salud.vida, &partida.jug.salud.vida_max]), Color::RED),
+                    (format!("{}: {}", arma, partida.armas.municion()), Color::SKYBLUE),
+                    (reloj, Color::WHITE),
+                ];
+                if let Some(f) = &fantasma {
+                    lineas.push((l.f("hud.fantasma", &[&formato_tiempo(f.frames())]), Color::SKYBLUE));
+                }
+                for (i, (txt, col)) in lineas.iter().enumerate() {
+                    fb.texto_con(w - 3, 3 + ALTO * i as i32, txt, *col, Estilo::default().a_la_derecha());
+                }
+                if parcial.2 > 0 {
+                    fb.texto_con(w / 2, 70, &parcial.0, parcial.1, centro);
+                }
+                if aviso.1 > 0 {
+                    fb.texto_con(w / 2, h / 2 + 14, &aviso.0.texto(l), Color::ORANGE, centro);
+                }
+                if let Some(r) = &rep {
+                    let total = r.grab.entradas.len();
+                    let modo = if r.pausa { l.t("rep.pausa") } else if controles.abajo(&d, &mandos.ids, Accion::Derecha) { "×8" } else { "" };
+                    fb.texto_con(w / 2, 3, &l.f("rep.titulo", &[&modo, &r.pos, &total]), Color::GOLD, centro);
+                    if let Some((clave, igual)) = r.fin {
+                        let nota = if igual { String::new() } else { format!("\n{}", l.t("rep.desvio")) };
+                        fb.texto_con(w / 2, h / 2 - 10, &format!("{}{nota}", l.t(clave)), Color::GOLD, centro);
+                    }
+                    fb.texto_con(w / 2, h - 34, &l.f("rep.ayuda", &[&controles.resumen(Accion::Pausa), &controles.resumen(Accion::Volver)]),
+                                 Color::LIGHTGRAY, centro);
                 }
-
-                d.draw_text("Mouse: mirar | WSAD: mover | Q/E: rotar | M: toggle mouse",
-                            10, H as i32*SCALE - 30, 12, Color::GRAY);
-                d.draw_text("Gamepad: stick izq mover, stick der rotar",
-                            10, H as i32*SCALE - 16, 12, Color::GRAY);
             }
-            Estado::Juego => {
-                d.draw_text(&format!("FPS: {}", d.get_fps()), 6, 6, 14, Color::WHITE);
-                d.draw_text(&format!("Coins: {}/{}", coins_taken, coins_total),
-                            6, 24, 14, Color::YELLOW);
+            Estado::Pausa => {
+                fb.texto_con(w / 2, 20, l.t("pausa.titulo"), Color::RAYWHITE, centro.escala(3));
+                for (i, txt) in OPCIONES_PAUSA.iter().enumerate() {
+                    let col = if i == sel_pausa { Color::YELLOW } else { Color::LIGHTGRAY };
+                    fb.texto_con(w / 2, LISTA_Y + LISTA_ALTO * i as i32, l.t(txt), col, centro.escala(2));
+                }
+                fb.texto_con(w / 2, h - 24, &l.f("pausa.ayuda", &[&controles.resumen(Accion::Usar), &controles.resumen(Accion::Volver)]),
+                             Color::GRAY, centro);
+            }
+            Estado::Opciones => {
+                fb.texto_con(w / 2, 20, l.t("opciones.titulo"), Color::RAYWHITE, centro.escala(2));
+                for (i, &o) in Opcion::TODAS.iter().enumerate() {
+                    let y = LISTA_Y + LISTA_ALTO * i as i32;
+                    let col = if i == sel_opcion { Color::YELLOW } else { Color::LIGHTGRAY };
+                    fb.texto(20, y, o.nombre(l), col);
+                    fb.texto_con(w - 20, y, &opciones.valor(o, &controles), col, Estilo::default().a_la_derecha());
+                }
+                if let Some(a) = &opciones.aviso {
+                    fb.texto_con(w / 2, h - 36, &a.texto(l), Color::ORANGE, centro);
+                }
+                fb.texto_con(w / 2, h - 24, &l.f("opciones.ayuda", &[&controles.resumen(Accion::Usar), &controles.resumen(Accion::Volver)]),
+                             Color::GRAY, centro);
             }
             Estado::Exito => {
-                let cx = (W as i32 * SCALE) / 2;
-                d.draw_text("¡ÉXITO!", cx - 60, 40, 30, Color::LIME);
-                d.draw_text("Has llegado a la salida.", cx - 120, 80, 20, Color::RAYWHITE);
-                d.draw_text("ENTER: volver al menú", cx - 120, 110, 18, Color::LIGHTGRAY);
-                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                    estado = Estado::Menu;
+                let (izq, der) = (w / 2 - 100, w / 2 + 100);
+                fb.texto_con(w / 2, 10, l.t("exito.titulo"), Color::LIME, centro.escala(3));
+                fb.texto_con(w / 2, 42, l.t("exito.salida"), Color::RAYWHITE, centro);
+                if let Some(p) = desglose {
+                    let filas = [
+                        (l.f("exito.monedas", &[&partida.prog.monedas, &partida.prog.monedas_total]), p.por_monedas),
+                        (l.t("exito.todas").to_string(), p.bonus_todas),
+                        (l.f("exito.tiempo", &[&formato_tiempo(partida.prog.frames)]), p.por_tiempo),
+                        (l.f("exito.enemigos", &[&partida.prog.enemigos_derrotados]), p.por_enemigos),
+                        (l.f("exito.muertes", &[&partida.prog.muertes]), p.por_muertes),
+                    ];
+                    let mut y = 60;
+                    for (txt, pts) in filas.iter() {
+                        fb.texto(izq, y, txt, Color::LIGHTGRAY);
+                        fb.texto_con(der, y, &format!("{pts:+}"), Color::LIGHTGRAY, Estilo::default().a_la_derecha());
+                        y += ALTO;
+                    }
+                    fb.texto_con(izq, y + 4, l.t("exito.total"), Color::GOLD, Estilo::default().escala(2));
+                    fb.texto_con(der, y + 4, &p.total.to_string(), Color::GOLD, Estilo::default().escala(2).a_la_derecha());
+                    if let Some(k) = puesto {
+                        let txt = if k == 0 { l.t("exito.record").to_string() } else { l.f("exito.puesto", &[&(k + 1)]) };
+                        fb.texto(izq, y + 28, &txt, Color::YELLOW);
+                    }
+                    if let Some(dif) = contra_fantasma {
+                        fb.texto(izq, y + 40, &l.f("exito.fantasma", &[&formato_diferencia(dif)]),
+                                 if dif <= 0 { Color::LIME } else { Color::RED });
+                    }
                 }
+                fb.texto_con(w / 2, h - 14, &l.f("fin.volver", &[&controles.resumen(Accion::Usar)]),
+                             Color::LIGHTGRAY, centro);
             }
             Estado::Perdio => {
-                let cx = (W as i32 * SCALE) / 2;
-                d.draw_text("¡PERDISTE!", cx - 80, 40, 30, Color::RED);
-                d.draw_text("Caíste en un pozo.", cx - 90, 80, 20, Color::RAYWHITE);
-                d.draw_text("ENTER: volver al menú", cx - 120, 110, 18, Color::LIGHTGRAY);
-                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                    estado = Estado::Menu;
+                fb.texto_con(w / 2, 30, l.t("perdio.titulo"), Color::RED, centro.escala(3));
+                fb.texto_con(w / 2, 64, l.t(motivo), Color::RAYWHITE, centro);
+                fb.texto_con(w / 2, 80, &l.f("fin.volver", &[&controles.resumen(Accion::Usar)]),
+                             Color::LIGHTGRAY, centro);
+            }
+            Estado::Menu => {
+                fb.texto_con(w / 2, 3, l.t("menu.titulo"), Color::RAYWHITE, centro.escala(2));
+                fb.texto_con(w / 2, 26, &l.f("menu.elegir", &[&controles.resumen(Accion::Usar)]), Color::LIGHTGRAY, centro);
+
+                // la lista se desplaza con la selección
+                let primera = idx_nivel.saturating_sub(NIVELES_VISIBLES - 1)
+                    .min(niveles.len().saturating_sub(NIVELES_VISIBLES));
+                let mut y = 38;
+                for (i, n) in niveles.iter().enumerate().skip(primera).take(NIVELES_VISIBLES) {
+                    let marca = if i == idx_nivel { "> " } else { "  " };
+                    let bloqueado = i > 0 && n.generado.is_none() && !guardado.completado(&niveles[i - 1].id);
+                    let estado_txt = if bloqueado { l.t("menu.bloqueado") }
+                                     else if guardado.completado(&n.id) { l.t("menu.completado") }
+                                     else { "" };
+                    let semilla_txt = if i == idx_nivel && n.generado.is_some_and(|g| g.tipo != diario::TIPO) { l.t("menu.semilla") } else { "" };
+                    let col = if bloqueado { Color::DARKGRAY }
+                              else if i == idx_nivel { Color::YELLOW }
+                              else { Color::GRAY };
+                    fb.texto(20, y, &format!("{marca}{}{estado_txt}{semilla_txt}", n.nombre(l)), col);
+                    y += ALTO;
+                }
+                let objetivo: Vec<String> = niveles[idx_nivel].reglas.iter().map(|r| r.descripcion(l)).collect();
+                if !objetivo.is_empty() {
+                    fb.texto(8, 90, &l.f("menu.objetivo", &[&objetivo.join(", ")]), Color::LIGHTGRAY);
                 }
+
+                // récords del nivel elegido; los puntajes en dos columnas
+                if let Some(r) = guardado.nivel(&niveles[idx_nivel].id) {
+                    let mejor = r.mejor_tiempo.map(formato_tiempo).unwrap_or_else(|| "-".to_string());
+                    fb.texto(8, 100, &l.f("menu.records", &[&mejor, &r.mejores_monedas]), Color::LIGHTGRAY);
+                    for (k, m) in r.puntajes.iter().enumerate() {
+                        let txt = l.f("menu.puntaje", &[&(k + 1), &format!("{:>6}", m.puntaje), &formato_tiempo(m.frames), &m.monedas]);
+                        fb.texto(8 + (k as i32 / 3) * w / 2, 110 + ALTO * (k as i32 % 3), &txt, Color::GRAY);
+                    }
+                }
+                let aviso_menu = if aviso.1 > 0 { Some(&aviso.0) } else { guardado.aviso.as_ref().or(controles.aviso.as_ref()) };
+                if let Some(a) = aviso_menu {
+                    fb.texto(3, h - 60, &a.texto(l), Color::ORANGE);
+                }
+
+                let mover: Vec<String> = [Accion::Avanzar, Accion::PasoIzq, Accion::Retroceder, Accion::PasoDer]
+                    .into_iter().map(|a| controles.resumen(a)).collect();
+                fb.texto(3, h - 50, &l.f("menu.mover", &[&mover.join(", "),
+                             &controles.resumen(Accion::GirarIzq), &controles.resumen(Accion::GirarDer)]), Color::GRAY);
+                fb.texto(3, h - 40, &l.f("menu.disparar", &[&controles.resumen(Accion::Disparar)]), Color::GRAY);
+                fb.texto(3, h - 30, &l.f("menu.opciones", &[&controles.resumen(Accion::Pausa)]), Color::GRAY);
+                fb.texto(3, h - 20, l.t("menu.partida"), Color::GRAY);
+            }
+            Estado::Controles => {
+                fb.texto_con(w / 2, 4, l.t("controles.titulo"), Color::RAYWHITE, centro.escala(2));
+                // no entran todas: la lista se desplaza con la selección
+                let filas = controles.filas();
+                let primera = sel_control.saturating_sub(CONTROLES_VISIBLES - 1)
+                    .min(filas.len().saturating_sub(CONTROLES_VISIBLES));
+                for (i, &f) in filas.iter().enumerate().skip(primera).take(CONTROLES_VISIBLES) {
+                    let y = 28 + ALTO * (i - primera) as i32;
+                    let col = if i == sel_control { Color::YELLOW } else { Color::LIGHTGRAY };
+                    let valor = if i == sel_control && esperando_boton { "...".to_string() } else { controles.valor_fila(f) };
+                    fb.texto(8, y, &controles.nombre_fila(f, l), col);
+                    fb.texto_con(w - 8, y, &valor, col, Estilo::default().a_la_derecha());
+                }
+                let ayuda = if esperando_boton { "controles.esperando" } else { "controles.ayuda" };
+                fb.texto_con(w / 2, h - 22, l.t(ayuda), Color::GRAY, centro);
             }
+            Estado::Editor => {}
         }
+
+        // pintar framebuffer
+        fb.pintar(&mut d, SCALE);
     }
 }
@@ -0,0 +1,113 @@
+//! Gamepads: se detectan en caliente (conectar y desconectar en cualquier
+//! momento) y se leen todos a la vez. También arma la navegación de menús
+//! con las acciones de `controles`, el stick de moverse (con repetición al
+//! mantenerlo inclinado) y el mouse.
+
+use crate::controles::{Accion, Controles};
+use crate::idiomas::Mensaje;
+use raylib::prelude::*;
+
+const MAX_MANDOS: i32 = 4;
+const UMBRAL_STICK: f32 = 0.5;
+const ESPERA_REPETIR: u32 = 18; // frames antes de empezar a repetir
+const CADA_REPETIR: u32 = 5;
+
+pub struct Mandos {
+    pub ids: Vec<i32>, // gamepads conectados
+    dir_prev: (i32, i32),
+    repetir: u32,
+    raton_prev: Vector2,
+}
+
+/// Navegación de menús en este frame
+pub struct Nav {
+    pub arriba: bool,
+    pub abajo: bool,
+    pub izquierda: bool,
+    pub derecha: bool,
+    pub usar: bool,
+    pub volver: bool,
+    pub raton: Vector2, // en coordenadas de la UI (sin el zoom de la ventana)
+    pub movio: bool,    // el mouse se movió en este frame
+    pub clic: bool,
+}
+
+impl Nav {
+    /// Fila de una lista vertical (empieza en `y0`, filas de `alto`) que
+    /// está bajo el mouse.
+    pub fn fila(&self, y0: i32, alto: i32, n: usize) -> Option<usize> {
+        let i = ((self.raton.y - y0 as f32) / alto as f32).floor();
+        (i >= 0.0 && (i as usize) < n).then_some(i as usize)
+    }
+}
+
+impl Mandos {
+    pub fn nuevo() -> Self {
+        Self { ids: Vec::new(), dir_prev: (0, 0), repetir: 0, raton_prev: Vector2::zero() }
+    }
+
+    /// Revisa qué gamepads hay; devuelve un mensaje por cada cambio.
+    pub fn actualizar(&mut self, d: &RaylibHandle) -> Vec<Mensaje> {
+        let mut avisos = Vec::new();
+        for id in 0..MAX_MANDOS {
+            let hay = d.is_gamepad_available(id);
+            let estaba = self.ids.contains(&id);
+            if hay && !estaba {
+                let nombre = d.get_gamepad_name(id).unwrap_or_else(|| "?".to_string());
+                avisos.push(Mensaje::con("aviso.gamepad_conectado", &[&(id + 1), &nombre.trim()]));
+                self.ids.push(id);
+            } else if !hay && estaba {
+                avisos.push(Mensaje::con("aviso.gamepad_desconectado", &[&(id + 1)]));
+                self.ids.retain(|&x| x != id);
+            }
+        }
+        self.ids.sort_unstable();
+        avisos
+    }
+
+    /// `zoom`: cuánto está agrandada la UI en la ventana
+    pub fn nav(&mut self, d: &RaylibHandle, c: &Controles, zoom: f32) -> Nav {
+        let raton = d.get_mouse_position() / zoom;
+        let movio = raton != self.raton_prev;
+        self.raton_prev = raton;
+        let p = |a| c.pulsado(d, &self.ids, a);
+        let mut n = Nav {
+            arriba: p(Accion::Arriba),
+            abajo: p(Accion::Abajo),
+            izquierda: p(Accion::Izquierda),
+            derecha: p(Accion::Derecha),
+            usar: p(Accion::Usar),
+            volver: p(Accion::Volver) || d.is_key_pressed(KeyboardKey::KEY_ESCAPE),
+            raton,
+            movio,
+            clic: d.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON),
+        };
+
+        // stick de moverse: un paso al inclinarlo y luego repite
+        let paso = |v: f32| if v > UMBRAL_STICK { 1 } else if v < -UMBRAL_STICK { -1 } else { 0 };
+        let dir = (
+            paso(c.eje(d, &self.ids, c.eje_lateral)),
+            paso(c.eje(d, &self.ids, c.eje_avance)),
+        );
+        let mover = if dir == (0, 0) {
+            false
+        } else if dir != self.dir_prev {
+            self.repetir = ESPERA_REPETIR;
+            true
+        } else if self.repetir == 0 {
+            self.repetir = CADA_REPETIR;
+            true
+        } else {
+            self.repetir -= 1;
+            false
+        };
+        self.dir_prev = dir;
+        if mover {
+            n.izquierda |= dir.0 < 0;
+            n.derecha |= dir.0 > 0;
+            n.arriba |= dir.1 < 0;
+            n.abajo |= dir.1 > 0;
+        }
+        n
+    }
+}
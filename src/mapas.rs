@@ -4,53 +4,448 @@
 //! 'E' = salida
 //! 'A' = antorcha (sprite)
 //! 'C' = moneda (sprite)
-//! 'P' = pozo (caminable pero si lo pisas, pierdes)
+//! 'P' = pozo (caminable, quita mucha vida y te devuelve al borde)
+//! 'S' = pinchos, 'L' = lava, 'V' = veneno (caminables, hacen daño)
+//! 'H' = botiquín (recupera vida)
+//! 'B' = caja de munición
+//! 'M' = enemigo (punto de aparición)
+//! 'D' = puerta (pared hasta que un disparador la abre; ver `eventos`)
+//! 'T' = teletransportador (ver `teletransportes`)
+//! 'X' = bloque que se empuja, '_' = placa de presión (ver `bloques`)
+//!
+//! Además de los niveles hechos a mano hay niveles generados con semilla
+//! (`laberintos`, `mazmorras`, `cuevas` y el `diario`); su id es
+//! "<tipo>-<semilla>" y con eso se vuelven a armar.
+//!
+//! Los niveles en archivo (`niveles/<id>.txt`, los escribe el editor) son
+//! texto plano versionado:
+//!   raycaster-nivel 1
+//!   nombre Mi nivel
+//!   inicio 2 1 90          (x, y, ángulo en grados)
+//!   regla minimo 3         (todas | minimo N | tiempo S | enemigos)
+//!   disparador 4 2 1 3 ... (x, y, ancho, alto en celdas y sus acciones)
+//!   apretar 6 2 2 1 ...    (igual, pero cuando todas sus placas tienen bloque;
+//!   soltar 6 2 2 1 ...      `soltar`, cuando alguna deja de tenerlo)
+//!   teletransporte 3 1 9 6 180 (de la 'T' en x, y a dx, dy; grados opcional)
+//!   fila |111111|          (una por fila del mapa)
+//!
+//! También se leen mapas de Tiled (`.tmx`, `.tmj`, `.json`; ver `tiled`).
 
-use super::motor::Mapa;
+use super::caminos::es_alcanzable;
+use super::idiomas::Idioma;
+use super::laberintos::{self, Laberinto};
+use super::mazmorras::{self, Mazmorra};
+use super::cuevas::{self, Cueva};
+use super::diario;
+use super::eventos;
+use super::teletransportes;
+use super::tiled;
+use super::motor::{celda, es_caminable, Mapa};
+use super::reglas::Regla;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VERSION: u32 = 1;
+const CABECERA: &str = "raycaster-nivel";
+
+/// Origen de un nivel generado: con el mismo tipo y semilla sale igual.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Generado {
+    pub tipo: &'static str,
+    pub semilla: u64,
+}
 
 pub struct Nivel {
-    pub nombre: &'static str,
+    pub id: String,              // clave estable para el guardado y los textos ("nivel.<id>")
+    pub titulo: Option<String>,  // nombre fijo (niveles en archivo); si no, sale de `idiomas`
+    pub generado: Option<Generado>,
     pub mapa: Mapa,
     pub inicio: (i32, i32, f32), // x, y, ang
+    pub reglas: Vec<Regla>,      // condiciones para poder salir por 'E'
+    pub disparadores: Vec<Disparador>,
+    pub teletransportes: Vec<Teletransporte>, // destinos explícitos de los 'T'
+}
+
+/// Zona de celdas que dispara acciones cuando el jugador entra (o cuando
+/// cambian sus placas de presión, según `cuando`)
+#[derive(Clone, PartialEq, Debug)]
+pub struct Disparador {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub cuando: Cuando,
+    pub acciones: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cuando {
+    Entrar,  // el jugador entra en la zona
+    Apretar, // todas las placas '_' de la zona quedan con un bloque encima
+    Soltar,  // alguna de ellas se queda sin bloque
+}
+
+impl Cuando {
+    pub const TODOS: [Cuando; 3] = [Cuando::Entrar, Cuando::Apretar, Cuando::Soltar];
+
+    /// Palabra en el archivo de nivel (y propiedad `cuando` en Tiled)
+    pub fn clave(self) -> &'static str {
+        match self {
+            Cuando::Entrar => "disparador",
+            Cuando::Apretar => "apretar",
+            Cuando::Soltar => "soltar",
+        }
+    }
+}
+
+/// Destino de un teletransportador: de la 'T' en (x, y) a la celda (dx, dy)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Teletransporte {
+    pub x: i32,
+    pub y: i32,
+    pub dx: i32,
+    pub dy: i32,
+    pub angulo: Option<f32>, // radianes; sin ángulo se conserva el del jugador
+}
+
+impl Nivel {
+    /// Los generados se llaman "nivel.<tipo>" con la semilla como argumento
+    pub fn nombre(&self, l: Idioma) -> String {
+        if let Some(t) = &self.titulo { return t.clone(); }
+        match self.generado {
+            Some(g) if g.tipo == diario::TIPO => l.f("nivel.diario", &[&diario::fecha_texto(g.semilla)]),
+            Some(g) => l.f(&format!("nivel.{}", g.tipo), &[&g.semilla]),
+            None => l.t(&format!("nivel.{}", self.id)).to_string(),
+        }
+    }
+
+    /// Validador: la salida y las monedas tienen que ser alcanzables desde el
+    /// inicio (o desde el destino de un teletransportador alcanzable).
+    /// Devuelve los problemas encontrados (vacío = nivel OK).
+    pub fn validar(&self) -> Vec<String> {
+        let mut problemas = Vec::new();
+        let inicio = (self.inicio.0, self.inicio.1);
+        let mut salidas = 0;
+        // las puertas cuentan como abiertas; se avisa si ningún disparador las abre
+        let ordenes: Vec<eventos::Orden> = self.disparadores.iter()
+            .flat_map(|d| eventos::parsear(&d.acciones).unwrap_or_default())
+            .collect();
+        // y los bloques como piso (se pueden correr)
+        let mut abierto = self.mapa.clone();
+        for (y, fila) in abierto.iter_mut().enumerate() {
+            for (x, c) in fila.iter_mut().enumerate().filter(|(_, c)| matches!(**c, 'D' | 'X')) {
+                if *c == 'D' && !ordenes.iter().any(|o| o.abre(x as i32, y as i32)) {
+                    problemas.push(format!("ningún disparador abre la puerta en ({x},{y})"));
+                }
+                *c = ' ';
+            }
+        }
+        for d in self.disparadores.iter().filter(|d| d.cuando != Cuando::Entrar) {
+            let placas = (d.y..d.y + d.h).flat_map(|y| (d.x..d.x + d.w).map(move |x| (x, y)))
+                .filter(|&(x, y)| celda(&self.mapa, x, y) == Some('_'))
+                .count();
+            if placas == 0 {
+                problemas.push(format!("'{}' en ({},{}) no tiene placas '_'", d.cuando.clave(), d.x, d.y));
+            }
+        }
+        let enlaces = teletransportes::enlazar(&self.mapa, &self.teletransportes);
+        for t in &self.teletransportes {
+            if celda(&self.mapa, t.x, t.y) != Some('T') {
+                problemas.push(format!("teletransporte desde ({},{}), que no es una 'T'", t.x, t.y));
+            }
+        }
+        for t in &enlaces {
+            if !celda(&abierto, t.dx, t.dy).is_some_and(es_caminable) {
+                problemas.push(format!("el teletransportador en ({},{}) lleva a una pared", t.x, t.y));
+            }
+        }
+        for y in 0..self.mapa.len() as i32 {
+            for x in 0..self.mapa[0].len() as i32 {
+                if self.mapa[y as usize][x as usize] == 'T' && !enlaces.iter().any(|t| (t.x, t.y) == (x, y)) {
+                    problemas.push(format!("teletransportador en ({x},{y}) sin destino"));
+                }
+            }
+        }
+        // desde dónde se puede arrancar a caminar: el inicio y cada destino
+        // de un teletransportador al que se llega
+        let mut origenes = vec![inicio];
+        loop {
+            let nuevos: Vec<(i32, i32)> = enlaces.iter()
+                .filter(|t| !origenes.contains(&(t.dx, t.dy)))
+                .filter(|t| origenes.iter().any(|&o| es_alcanzable(&abierto, o, (t.x, t.y), false)))
+                .map(|t| (t.dx, t.dy))
+                .collect();
+            if nuevos.is_empty() { break; }
+            for o in nuevos {
+                if !origenes.contains(&o) { origenes.push(o); }
+            }
+        }
+        let alcanzable = |hasta, evitar| origenes.iter().any(|&o| es_alcanzable(&abierto, o, hasta, evitar));
+        for y in 0..self.mapa.len() as i32 {
+            for x in 0..self.mapa[0].len() as i32 {
+                let c = celda(&self.mapa, x, y).unwrap_or('#');
+                let que = match c {
+                    'E' => { salidas += 1; "salida" }
+                    'C' => "moneda",
+                    _ => continue,
+                };
+                if !alcanzable((x, y), false) {
+                    problemas.push(format!("{que} en ({x},{y}) inalcanzable"));
+                } else if !alcanzable((x, y), true) {
+                    problemas.push(format!("{que} en ({x},{y}) solo se alcanza pisando peligros"));
+                }
+            }
+        }
+        if salidas == 0 { problemas.push("el nivel no tiene salida 'E'".to_string()); }
+        problemas
+    }
 }
 
 pub fn niveles() -> Vec<Nivel> {
     vec![
         Nivel {
-            nombre: "Nivel 1 – Pasillos",
+            id: "pasillos".to_string(),
+            titulo: None,
+            generado: None,
             mapa: parse_mapa(&[
                 "111111111111111111",
                 "1 C 2   P  3   C E1",
                 "1 111  33  3  1111",
                 "1   C 22   P  4  1",
-                "1  444   11   C  1",
-                "1 C 1   1   6    1",
+                "1  444   11   C H1",
+                "1 C 1   1S  6    1",
                 "1   1   1111111111",
-                "1   1     P      1",
-                "1 C 1   A   C    1",
+                "1   1     P   M  1",
+                "1 C 1 B A   C    1",
                 "111111111111111111",
             ]),
             inicio: (2, 1, 0.0),
+            reglas: vec![Regla::MinimoMonedas(3), Regla::SinEnemigos],
+            disparadores: Vec::new(),
+            teletransportes: Vec::new(),
         },
         Nivel {
-            nombre: "Nivel 2 – Patio",
+            id: "patio".to_string(),
+            titulo: None,
+            generado: None,
             mapa: parse_mapa(&[
                 "111111111111111111",
                 "1 C 2    P    A  1",
                 "1 1   1111  6  C 1",
                 "1 1   C    1   P E1",
-                "1 1  3333  1     1",
-                "1  C 444   1   C 1",
-                "1   6   P  1     1",
-                "1   C  A   1     1",
-                "1 P        2   C 1",
+                "1 1  3333  1  B  1",
+                "1  C 444   1 M C 1",
+                "1   6   P  1  L  1",
+                "1   C  A   1 VV  1",
+                "1 P    H   2   C 1",
                 "111111111111111111",
             ]),
             inicio: (2, 1, 0.0),
+            reglas: vec![Regla::TodasLasMonedas, Regla::TiempoLimite(240)],
+            disparadores: Vec::new(),
+            teletransportes: Vec::new(),
         },
     ]
 }
 
+/// Carpeta de los niveles en archivo (junto al juego, para versionarlos con él)
+pub fn carpeta_niveles() -> PathBuf {
+    PathBuf::from("niveles")
+}
+
+impl Nivel {
+    pub fn a_texto(&self) -> String {
+        let mut t = format!("{CABECERA} {VERSION}\n");
+        if let Some(n) = &self.titulo { t += &format!("nombre {n}\n"); }
+        let (x, y, ang) = self.inicio;
+        t += &format!("inicio {x} {y} {}\n", ang.to_degrees().round());
+        for r in &self.reglas {
+            t += &match r {
+                Regla::TodasLasMonedas => "regla todas\n".to_string(),
+                Regla::MinimoMonedas(n) => format!("regla minimo {n}\n"),
+                Regla::TiempoLimite(s) => format!("regla tiempo {s}\n"),
+                Regla::SinEnemigos => "regla enemigos\n".to_string(),
+            };
+        }
+        for d in &self.disparadores {
+            t += &format!("{} {} {} {} {} {}\n", d.cuando.clave(), d.x, d.y, d.w, d.h, d.acciones);
+        }
+        for e in &self.teletransportes {
+            t += &format!("teletransporte {} {} {} {}", e.x, e.y, e.dx, e.dy);
+            if let Some(a) = e.angulo { t += &format!(" {}", a.to_degrees().round()); }
+            t += "\n";
+        }
+        for fila in &self.mapa {
+            t += &format!("fila |{}|\n", fila.iter().collect::<String>());
+        }
+        t
+    }
+
+    /// Lee un nivel en archivo; el id es el nombre del archivo sin extensión.
+    /// Las acciones de los disparadores se revisan acá, no al jugar, y también
+    /// el tamaño: el editor y el motor cuentan con al menos 3×3 celdas.
+    pub fn cargar(ruta: &Path) -> Result<Self, String> {
+        let id = ruta.file_stem().and_then(|s| s.to_str()).ok_or("nombre de archivo inválido")?;
+        let n = if ruta.extension().is_some_and(|x| x != "txt") {
+            tiled::importar(ruta, id)?
+        } else {
+            Self::leer_texto(ruta, id)?
+        };
+        let (w, h) = (n.mapa.first().map_or(0, Vec::len), n.mapa.len());
+        if w < 3 || h < 3 {
+            return Err(format!("mapa de {w}×{h}, tiene que ser de al menos 3×3"));
+        }
+        for d in &n.disparadores {
+            eventos::parsear(&d.acciones).map_err(|e| format!("disparador en ({},{}): {e}", d.x, d.y))?;
+        }
+        Ok(n)
+    }
+
+    fn leer_texto(ruta: &Path, id: &str) -> Result<Self, String> {
+        let txt = fs::read_to_string(ruta).map_err(|e| e.to_string())?;
+        let mut lineas = txt.lines().enumerate();
+        let version: u32 = lineas.next()
+            .and_then(|(_, cab)| cab.strip_prefix(CABECERA))
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or("cabecera inválida")?;
+        if version > VERSION {
+            return Err(format!("versión {version} más nueva que la del juego ({VERSION})"));
+        }
+
+        let mut titulo = None;
+        let mut inicio = None;
+        let mut reglas = Vec::new();
+        let mut disparadores = Vec::new();
+        let mut teletransportes = Vec::new();
+        let mut filas = Vec::new();
+        for (i, l) in lineas {
+            if l.trim().is_empty() { continue; }
+            let (clave, resto) = l.split_once(' ').unwrap_or((l, ""));
+            let err = || format!("línea {}: '{}'", i + 1, l.trim());
+            let c: Vec<&str> = resto.split_whitespace().collect();
+            match clave {
+                "nombre" => titulo = Some(resto.trim().to_string()),
+                "inicio" => {
+                    let n = |k: usize| c.get(k).and_then(|t| t.parse::<f32>().ok()).ok_or_else(err);
+                    inicio = Some((n(0)? as i32, n(1)? as i32, n(2)?.to_radians()));
+                }
+                "regla" => reglas.push(parsear_regla(resto).ok_or_else(err)?),
+                "disparador" | "apretar" | "soltar" => {
+                    let n = |k: usize| c.get(k).and_then(|t| t.parse::<i32>().ok()).ok_or_else(err);
+                    let acciones = eventos::resto(resto, 4).trim_end().to_string();
+                    let cuando = Cuando::TODOS.into_iter().find(|k| k.clave() == clave).ok_or_else(err)?;
+                    disparadores.push(Disparador { x: n(0)?, y: n(1)?, w: n(2)?, h: n(3)?, cuando, acciones });
+                }
+                "teletransporte" => {
+                    let n = |k: usize| c.get(k).and_then(|t| t.parse::<i32>().ok()).ok_or_else(err);
+                    let angulo = match c.len() {
+                        4 => None,
+                        5 => Some((n(4)? as f32).to_radians()),
+                        _ => return Err(err()),
+                    };
+                    teletransportes.push(Teletransporte { x: n(0)?, y: n(1)?, dx: n(2)?, dy: n(3)?, angulo });
+                }
+                "fila" => filas.push(resto.strip_prefix('|').and_then(|r| r.strip_suffix('|')).ok_or_else(err)?),
+                _ => return Err(err()),
+            }
+        }
+        if filas.is_empty() { return Err("el nivel no tiene filas".to_string()); }
+        Ok(Nivel {
+            id: id.to_string(),
+            titulo,
+            generado: None,
+            mapa: parse_mapa(&filas),
+            inicio: inicio.ok_or("falta 'inicio'")?,
+            reglas,
+            disparadores,
+            teletransportes,
+        })
+    }
+
+    /// Escribe `niveles/<id>.txt` (a un temporal y renombra, como el guardado)
+    pub fn guardar(&self) -> std::io::Result<PathBuf> {
+        let dir = carpeta_niveles();
+        fs::create_dir_all(&dir)?;
+        let ruta = dir.join(format!("{}.txt", self.id));
+        let tmp = ruta.with_extension("tmp");
+        fs::write(&tmp, self.a_texto())?;
+        fs::rename(&tmp, &ruta)?;
+        Ok(ruta)
+    }
+}
+
+/// "todas", "minimo N", "tiempo S" o "enemigos" (como en los archivos de nivel)
+pub fn parsear_regla(s: &str) -> Option<Regla> {
+    let c: Vec<&str> = s.split_whitespace().collect();
+    let n = || c.get(1).and_then(|t| t.parse::<u32>().ok());
+    Some(match c.first().copied()? {
+        "todas" => Regla::TodasLasMonedas,
+        "minimo" => Regla::MinimoMonedas(n()? as usize),
+        "tiempo" => Regla::TiempoLimite(n()?),
+        "enemigos" => Regla::SinEnemigos,
+        _ => return None,
+    })
+}
+
+/// Suma los niveles de `niveles/`: el que tiene el id de uno que ya está lo
+/// reemplaza y los demás van antes de los generados. Devuelve los errores.
+pub fn cargar_archivos(niveles: &mut Vec<Nivel>) -> Vec<String> {
+    let mut errores = Vec::new();
+    for ruta in rutas_niveles() {
+        match Nivel::cargar(&ruta) {
+            Ok(n) => { poner_nivel(niveles, n); }
+            Err(e) => errores.push(format!("{}: {e}", ruta.display())),
+        }
+    }
+    errores
+}
+
+/// Archivos de nivel (propios y de Tiled) de la carpeta de niveles, ordenados
+pub fn rutas_niveles() -> Vec<PathBuf> {
+    let Ok(dir) = fs::read_dir(carpeta_niveles()) else { return Vec::new() };
+    let mut rutas: Vec<PathBuf> = dir.filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|x| x.to_str()).is_some_and(|x| ["txt", "tmx", "tmj", "json"].contains(&x)))
+        .collect();
+    rutas.sort();
+    rutas
+}
+
+/// Reemplaza al nivel (no generado) con el mismo id o lo agrega antes de los
+/// generados. Devuelve el índice y si es nuevo en la lista.
+pub fn poner_nivel(niveles: &mut Vec<Nivel>, n: Nivel) -> (usize, bool) {
+    if let Some(i) = niveles.iter().position(|v| v.id == n.id && v.generado.is_none()) {
+        niveles[i] = n;
+        return (i, false);
+    }
+    let i = niveles.iter().position(|v| v.generado.is_some()).unwrap_or(niveles.len());
+    niveles.insert(i, n);
+    (i, true)
+}
+
+/// Arma un nivel generado (`None` si el tipo no existe).
+pub fn generar(tipo: &str, semilla: u64) -> Option<Nivel> {
+    match tipo {
+        laberintos::TIPO => Some(Laberinto::default().generar(semilla)),
+        mazmorras::TIPO => Some(Mazmorra::default().generar(semilla)),
+        cuevas::TIPO => Some(Cueva::default().generar(semilla)),
+        diario::TIPO => Some(diario::generar(semilla)),
+        _ => None,
+    }
+}
+
+/// Índice del nivel con ese id. Si es un generado con otra semilla (de una
+/// partida o repetición guardada), se rehace y reemplaza al de su tipo.
+pub fn buscar_o_generar(niveles: &mut [Nivel], id: &str) -> Option<usize> {
+    if let Some(i) = niveles.iter().position(|n| n.id == id) {
+        return Some(i);
+    }
+    let (tipo, semilla) = id.rsplit_once('-')?;
+    let nuevo = generar(tipo, semilla.parse().ok()?)?;
+    let i = niveles.iter().position(|n| n.generado.is_some_and(|g| g.tipo == tipo))?;
+    niveles[i] = nuevo;
+    Some(i)
+}
+
 /// Parser robusto: usa el **máximo ancho** entre todas las filas y rellena con ' '
 /// cuando una fila es más corta. Si una fila es más larga, se trunca al máximo.
 fn parse_mapa(lines: &[&str]) -> Mapa {
@@ -66,10 +461,50 @@ fn parse_mapa(lines: &[&str]) -> Mapa {
             m[y][x] = match ch {
                 '0' | ' ' => ' ',                              // piso
                 '1' | '2' | '3' | '4' | '5' | '6' => ch,       // paredes
-                'E' | 'A' | 'C' | 'P' => ch,                   // especiales
+                'E' | 'A' | 'C' | 'P' | 'M' => ch,             // especiales
+                'S' | 'L' | 'V' | 'H' | 'B' => ch,             // peligros y recogibles
+                'D' => ch,                                     // puerta (la abren los disparadores)
+                'T' => ch,                                     // teletransportador
+                'X' | '_' => ch,                               // bloque y placa de presión
                 _ => '1',                                      // cualquier otro símbolo lo tratamos como pared
             };
         }
     }
     m
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leer(nombre: &str, txt: &str) -> Result<Nivel, String> {
+        let ruta = std::env::temp_dir().join(format!("raycaster-{}-{nombre}.txt", std::process::id()));
+        fs::write(&ruta, txt).unwrap();
+        let n = Nivel::cargar(&ruta);
+        fs::remove_file(&ruta).unwrap();
+        n
+    }
+
+    #[test]
+    fn disparadores_con_espacios_de_mas() {
+        let txt = "raycaster-nivel 1\ninicio 1 1 0\n\
+                   disparador  1 1  1 1   abrir 2 1; mensaje  hola  mundo \n\
+                   apretar 1 1 1 1\n\
+                   fila |111|\nfila |1 D|\nfila |111|\n";
+        let n = leer("espacios", txt).unwrap();
+        let d = &n.disparadores[0];
+        assert_eq!((d.x, d.y, d.w, d.h, d.cuando), (1, 1, 1, 1, Cuando::Entrar));
+        assert_eq!(d.acciones, "abrir 2 1; mensaje  hola  mundo");
+        assert_eq!(n.disparadores[1].acciones, "");
+
+        // y se vuelve a leer igual después de guardarlo
+        let otra = leer("espacios-2", &n.a_texto()).unwrap();
+        assert_eq!(otra.disparadores, n.disparadores);
+    }
+
+    #[test]
+    fn disparador_incompleto() {
+        let txt = "raycaster-nivel 1\ninicio 1 1 0\ndisparador 1 1 1\nfila |111|\nfila |1 1|\nfila |111|\n";
+        assert_eq!(leer("incompleto", txt).err().as_deref(), Some("línea 3: 'disparador 1 1 1'"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Mazmorras de salas y pasillos con semilla. El mapa se parte en dos una y
+//! otra vez (BSP); cada hoja recibe una sala y cada corte se cruza con un
+//! pasillo entre las dos mitades. Las salas tienen un tipo (tesoro, pozos,
+//! salón con antorchas o común) que decide su contenido y la textura de sus
+//! paredes; la salida va a la celda más lejana del inicio.
+//!
+//! Como en `laberintos`, el azar es ChaCha8 y no se sortean `usize`, para
+//! que una semilla dé la misma mazmorra en cualquier máquina.
+
+use crate::generacion::{mas_lejana, repartir};
+use crate::mapas::{Generado, Nivel};
+use crate::motor::Mapa;
+use crate::reglas::Regla;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub const TIPO: &str = "mazmorra";
+const PARED_PASILLO: char = '6';
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TipoSala {
+    Comun,
+    Tesoro, // llena de monedas
+    Pozos,  // hay que cruzarla esquivando pozos
+    Salon,  // antorchas contra las paredes y un botiquín
+}
+
+impl TipoSala {
+    fn pared(self) -> char {
+        match self {
+            TipoSala::Comun => '1',
+            TipoSala::Tesoro => '5',
+            TipoSala::Pozos => '2',
+            TipoSala::Salon => '4',
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Sala {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    tipo: TipoSala,
+}
+
+impl Sala {
+    fn centro(&self) -> (i32, i32) {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    fn celdas(&self) -> Vec<(i32, i32)> {
+        (self.y..self.y + self.h).flat_map(|y| (self.x..self.x + self.w).map(move |x| (x, y))).collect()
+    }
+}
+
+/// Configuración del generador
+#[derive(Clone, Copy)]
+pub struct Mazmorra {
+    pub ancho: i32,       // del mapa, en celdas
+    pub alto: i32,
+    pub profundidad: u32, // cortes del BSP (hasta 2^profundidad salas)
+    pub sala_min: i32,    // lado mínimo de una sala
+}
+
+impl Default for Mazmorra {
+    fn default() -> Self {
+        Self { ancho: 40, alto: 30, profundidad: 4, sala_min: 4 }
+    }
+}
+
+impl Mazmorra {
+    pub fn generar(&self, semilla: u64) -> Nivel {
+        // una configuración muy chica se agranda hasta que entre una sala
+        let sala_min = self.sala_min.max(1);
+        let c = Mazmorra { ancho: self.ancho.max(sala_min + 2), alto: self.alto.max(sala_min + 2), sala_min, ..*self };
+        let mut r = ChaCha8Rng::seed_from_u64(semilla);
+        let mut mapa = vec![vec![PARED_PASILLO; c.ancho as usize]; c.alto as usize];
+        let mut salas = Vec::new();
+        c.partir((0, 0, c.ancho, c.alto), c.profundidad, &mut r, &mut salas, &mut mapa);
+
+        // tipos: la primera sala es la del inicio y queda común
+        for s in salas.iter_mut().skip(1) {
+            s.tipo = *[TipoSala::Comun, TipoSala::Tesoro, TipoSala::Pozos, TipoSala::Salon].choose(&mut r).unwrap();
+        }
+        for s in &salas {
+            for y in s.y - 1..=s.y + s.h {
+                for x in s.x - 1..=s.x + s.w {
+                    let c = &mut mapa[y as usize][x as usize];
+                    if *c == PARED_PASILLO { *c = s.tipo.pared(); }
+                }
+            }
+        }
+
+        let inicio = salas[0].centro();
+        if let Some((x, y)) = mas_lejana(&mapa, inicio) {
+            mapa[y as usize][x as usize] = 'E';
+        }
+        // los pozos al final: `repartir` solo cuida lo que ya está puesto
+        let (pozos, resto): (Vec<Sala>, Vec<Sala>) = salas.iter().partition(|s| s.tipo == TipoSala::Pozos);
+        for s in resto.iter().chain(&pozos) {
+            amueblar(&mut mapa, s, inicio, &mut r);
+        }
+        let monedas = mapa.iter().flatten().filter(|&&c| c == 'C').count();
+
+        Nivel {
+            id: format!("{TIPO}-{semilla}"),
+            titulo: None,
+            generado: Some(Generado { tipo: TIPO, semilla }),
+            mapa,
+            inicio: (inicio.0, inicio.1, 0.0),
+            reglas: if monedas > 0 { vec![Regla::MinimoMonedas(monedas.div_ceil(2))] } else { Vec::new() },
+            disparadores: Vec::new(),
+            teletransportes: Vec::new(),
+        }
+    }
+
+    /// Parte el rectángulo (x, y, w, h) y devuelve una sala de ese lado del
+    /// árbol, para unirla con el otro lado por un pasillo.
+    fn partir(&self, (x, y, w, h): (i32, i32, i32, i32), prof: u32, r: &mut ChaCha8Rng,
+              salas: &mut Vec<Sala>, mapa: &mut Mapa) -> Sala {
+        let hoja_min = self.sala_min + 2; // sala + una pared de cada lado
+        let por_ancho = w >= 2 * hoja_min;
+        let por_alto = h >= 2 * hoja_min;
+        if prof == 0 || !(por_ancho || por_alto) {
+            let sw = r.gen_range(self.sala_min..=w - 2);
+            let sh = r.gen_range(self.sala_min..=h - 2);
+            let sala = Sala {
+                x: x + 1 + r.gen_range(0..=w - 2 - sw),
+                y: y + 1 + r.gen_range(0..=h - 2 - sh),
+                w: sw,
+                h: sh,
+                tipo: TipoSala::Comun,
+            };
+            for (cx, cy) in sala.celdas() {
+                mapa[cy as usize][cx as usize] = ' ';
+            }
+            salas.push(sala);
+            return sala;
+        }
+
+        // se corta a lo largo del lado más largo (si se puede)
+        let vertical = if por_ancho && por_alto { w >= h } else { por_ancho };
+        let (a, b) = if vertical {
+            let k = r.gen_range(hoja_min..=w - hoja_min);
+            ((x, y, k, h), (x + k, y, w - k, h))
+        } else {
+            let k = r.gen_range(hoja_min..=h - hoja_min);
+            ((x, y, w, k), (x, y + k, w, h - k))
+        };
+        let sa = self.partir(a, prof - 1, r, salas, mapa);
+        let sb = self.partir(b, prof - 1, r, salas, mapa);
+        pasillo(mapa, sa.centro(), sb.centro(), r.gen_bool(0.5));
+        if r.gen_bool(0.5) { sa } else { sb }
+    }
+}
+
+/// Pasillo en L entre dos puntos; `primero_x` elige el codo.
+fn pasillo(mapa: &mut Mapa, (ax, ay): (i32, i32), (bx, by): (i32, i32), primero_x: bool) {
+    let (fila, col) = if primero_x { (ay, bx) } else { (by, ax) };
+    for x in ax.min(bx)..=ax.max(bx) { mapa[fila as usize][x as usize] = ' '; }
+    for y in ay.min(by)..=ay.max(by) { mapa[y as usize][col as usize] = ' '; }
+}
+
+/// Contenido de la sala según su tipo
+fn amueblar(mapa: &mut Mapa, s: &Sala, inicio: (i32, i32), r: &mut ChaCha8Rng) {
+    let mut celdas = s.celdas();
+    match s.tipo {
+        TipoSala::Comun => {
+            celdas.shuffle(r);
+            repartir(mapa, inicio, &celdas, 'C', r.gen_range(0..=1u32) as usize);
+        }
+        TipoSala::Tesoro => {
+            let tablero: Vec<_> = celdas.into_iter().filter(|&(x, y)| (x + y) % 2 == 0).collect();
+            repartir(mapa, inicio, &tablero, 'C', tablero.len());
+        }
+        TipoSala::Pozos => {
+            celdas.shuffle(r);
+            let n = celdas.len() * 2 / 5;
+            repartir(mapa, inicio, &celdas, 'P', n);
+        }
+        TipoSala::Salon => {
+            // antorchas cada tres celdas a lo largo del borde interior
+            let borde: Vec<_> = celdas.iter().copied()
+                .filter(|&(x, y)| x == s.x || y == s.y || x == s.x + s.w - 1 || y == s.y + s.h - 1)
+                .filter(|&(x, y)| (x + y) % 3 == 0)
+                .collect();
+            repartir(mapa, inicio, &borde, 'A', borde.len());
+            celdas.shuffle(r);
+            repartir(mapa, inicio, &celdas, 'H', 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generacion::asegurar_ganable;
+
+    #[test]
+    fn todas_se_pueden_ganar() {
+        asegurar_ganable(|s| Mazmorra::default().generar(s));
+    }
+
+    /// La mazmorra de una semilla no puede cambiar: se comparte por id
+    const FOTO: [&str; 14] = [
+        "666666666666666555555666",
+        "1111166666666665EC C5666",
+        "1   166622222265C C 5666",
+        "1   16662PP  265 C C5666",
+        "1   16662 P  265C C 5666",
+        "1   16662PPP 265 C C5666",
+        "11 116662P   265C C 5666",
+        "66        PP 265555 5666",
+        "66 666662    266666 6666",
+        "66 666662P P 261111 1111",
+        "66 666662 P  261       1",
+        "66        PP        C  1",
+        "6666666622222261       1",
+        "666666666666666111111111",
+    ];
+
+    #[test]
+    fn foto() {
+        let n = Mazmorra { ancho: 24, alto: 14, profundidad: 2, sala_min: 3 }.generar(1234);
+        let filas: Vec<String> = n.mapa.iter().map(|f| f.iter().collect()).collect();
+        assert_eq!(filas, FOTO);
+    }
+
+    #[test]
+    fn configuracion_chica() {
+        for (ancho, alto, sala_min) in [(0, 0, 4), (5, 40, 4), (40, 3, 4), (8, 8, 0), (-3, 10, -2)] {
+            let n = Mazmorra { ancho, alto, profundidad: 4, sala_min }.generar(7);
+            assert!(n.mapa.len() >= 3 && n.mapa[0].len() >= 3);
+            assert_eq!(n.mapa[n.inicio.1 as usize][n.inicio.0 as usize], ' ');
+        }
+    }
+}
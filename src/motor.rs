@@ -1,7 +1,11 @@
 //! Módulo del “motor” con framebuffer, raycasting, colisiones, minimapa y texturas.
 
+use crate::fuente::{self, Alinear, Estilo};
+use crate::salud::Salud;
 use raylib::prelude::*;
+use std::path::{Path, PathBuf};
 
+#[derive(Clone)]
 pub struct Framebuffer {
     pub w: u32,
     pub h: u32,
@@ -20,14 +24,65 @@ impl Framebuffer {
             self.pix[(y as u32 * self.w + x as u32) as usize] = c;
         }
     }
+    /// Pinta `c` sobre lo que ya hay según su alfa (sprites translúcidos)
+    pub fn mezclar(&mut self, x: i32, y: i32, c: Color) {
+        if x >= 0 && y >= 0 && (x as u32) < self.w && (y as u32) < self.h {
+            let i = (y as u32 * self.w + x as u32) as usize;
+            let f = c.a as f32 / 255.0;
+            let o = self.pix[i];
+            let m = |a: u8, b: u8| (a as f32 * f + b as f32 * (1.0 - f)) as u8;
+            self.pix[i] = Color::new(m(c.r, o.r), m(c.g, o.g), m(c.b, o.b), 255);
+        }
+    }
     pub fn line_v(&mut self, x: i32, y0: i32, y1: i32, c: Color) {
         let a = y0.min(y1);
         let b = y0.max(y1);
         for y in a..=b { self.set(x, y, c); }
     }
-    /// Placeholder (el texto real lo dibujamos con Raylib encima del framebuffer)
-    pub fn texto(&mut self, _x: i32, _y: i32, _s: &str, _c: Color) {}
-    pub fn pintar(&self, d: &mut RaylibDrawHandle, scale: i32) {
+    /// Multiplica todos los colores por `f` (fondo de los menús de pausa)
+    pub fn oscurecer(&mut self, f: f32) {
+        for p in &mut self.pix {
+            *p = Color::new((p.r as f32 * f) as u8, (p.g as f32 * f) as u8, (p.b as f32 * f) as u8, p.a);
+        }
+    }
+    /// Texto con la fuente de mapa de bits, con sombra y alineado a la izquierda
+    pub fn texto(&mut self, x: i32, y: i32, s: &str, c: Color) {
+        self.texto_con(x, y, s, c, Estilo::default());
+    }
+    /// Texto con estilo; `\n` empieza otra línea con la misma alineación
+    pub fn texto_con(&mut self, x: i32, y: i32, s: &str, c: Color, e: Estilo) {
+        let k = e.escala.max(1);
+        for (n, linea) in s.lines().enumerate() {
+            let ancho = fuente::ancho(linea, k);
+            let x0 = match e.alinear {
+                Alinear::Izquierda => x,
+                Alinear::Centro => x - ancho / 2,
+                Alinear::Derecha => x - ancho,
+            };
+            let y0 = y + n as i32 * fuente::ALTO * k;
+            for (i, ch) in linea.chars().enumerate() {
+                let g = fuente::glifo(ch);
+                let cx = x0 + i as i32 * fuente::ANCHO * k;
+                if let Some(sombra) = e.sombra {
+                    self.glifo(cx + k, y0 + k, &g, k, sombra);
+                }
+                self.glifo(cx, y0, &g, k, c);
+            }
+        }
+    }
+    fn glifo(&mut self, x: i32, y: i32, g: &[u8; 9], k: i32, c: Color) {
+        for (fy, fila) in g.iter().enumerate() {
+            for fx in 0..5 {
+                if fila & (0x10 >> fx) == 0 { continue; }
+                for py in 0..k {
+                    for px in 0..k {
+                        self.set(x + fx * k + px, y + fy as i32 * k + py, c);
+                    }
+                }
+            }
+        }
+    }
+    pub fn pintar(&self, d: &mut impl RaylibDraw, scale: i32) {
         for y in 0..self.h as i32 {
             for x in 0..self.w as i32 {
                 let c = self.pix[(y as u32 * self.w + x as u32) as usize];
@@ -46,6 +101,8 @@ pub struct Jugador {
     pub ang: f32,
     pub vel: f32,
     pub rot: f32,
+    pub salud: Salud,
+    pub fov: f32, // campo de visión de la cámara (solo para dibujar)
 }
 
 pub type Mapa = Vec<Vec<char>>;
@@ -58,31 +115,86 @@ pub fn celda(m: &Mapa, x: i32, y: i32) -> Option<char> {
 
 /// intenta mover con colisiones (pared si != caminable)
 /// Caminable: ' ' (piso), 'E' (salida), 'A' (antorcha/sprite),
-/// 'C' (moneda), 'P' (pozo → se pierde, pero se puede pisar).
+/// 'C' (moneda), 'M' (enemigo), 'H' (botiquín), 'B' (munición), 'T' (teletransportador),
+/// '_' (placa) y los peligros que se pueden
+/// pisar aunque duelan: 'P' (pozo), 'S' (pinchos), 'L' (lava), 'V' (veneno).
 pub fn mover_con_colision(j: &mut Jugador, dx_dir: f32, dy_dir: f32, mapa: &Mapa) {
+    let (dir_x, dir_y) = desplazamiento(j, dx_dir, dy_dir);
+    mover_entidad(&mut j.x, &mut j.y, dir_x, dir_y, mapa);
+}
+
+/// Paso del jugador en coordenadas de mundo para la dirección pedida
+/// (adelante/atrás con respecto al ángulo + strafe)
+pub fn desplazamiento(j: &Jugador, dx_dir: f32, dy_dir: f32) -> (f32, f32) {
     let dx = dx_dir * j.vel;
     let dy = dy_dir * j.vel;
+    (j.ang.cos() * dy + j.ang.sin() * dx, j.ang.sin() * dy - j.ang.cos() * dx)
+}
 
-    // Adelante/atrás con respecto a ángulo + strafe
-    let dir_x = j.ang.cos() * dy + j.ang.sin() * dx;
-    let dir_y = j.ang.sin() * dy - j.ang.cos() * dx;
-
-    let nx = j.x + dir_x;
-    let ny = j.y + dir_y;
+/// Desplaza una entidad (jugador, enemigo...) en coordenadas de mundo,
+/// resolviendo X e Y por separado para poder deslizarse por las paredes.
+pub fn mover_entidad(x: &mut f32, y: &mut f32, dx: f32, dy: f32, mapa: &Mapa) {
+    let nx = *x + dx;
+    let ny = *y + dy;
 
     // X
-    if let Some(c) = celda(mapa, nx.floor() as i32, j.y.floor() as i32) {
-        if es_caminable(c) { j.x = nx; }
+    if let Some(c) = celda(mapa, nx.floor() as i32, y.floor() as i32) {
+        if es_caminable(c) { *x = nx; }
     }
     // Y
-    if let Some(c) = celda(mapa, j.x.floor() as i32, ny.floor() as i32) {
-        if es_caminable(c) { j.y = ny; }
+    if let Some(c) = celda(mapa, x.floor() as i32, ny.floor() as i32) {
+        if es_caminable(c) { *y = ny; }
     }
 }
 
 #[inline]
-fn es_caminable(c: char) -> bool {
-    matches!(c, ' ' | 'E' | 'A' | 'C' | 'P')
+pub fn es_caminable(c: char) -> bool {
+    matches!(c, ' ' | 'E' | 'A' | 'C' | 'P' | 'M' | 'S' | 'L' | 'V' | 'H' | 'B' | 'T' | '_')
+}
+
+/// Línea de visión entre dos puntos: mismo DDA que `dibujar_escena`, avanzando
+/// celda a celda hasta llegar a la celda destino o chocar con una pared.
+pub fn linea_de_vision(mapa: &Mapa, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+    let dist = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+    if dist < 0.0001 { return true; }
+    let ray_dx = (x1 - x0) / dist;
+    let ray_dy = (y1 - y0) / dist;
+
+    let mut map_x = x0.floor() as i32;
+    let mut map_y = y0.floor() as i32;
+    let dest_x = x1.floor() as i32;
+    let dest_y = y1.floor() as i32;
+
+    let delta_x = if ray_dx == 0.0 { 1e30 } else { (1.0 / ray_dx).abs() };
+    let delta_y = if ray_dy == 0.0 { 1e30 } else { (1.0 / ray_dy).abs() };
+
+    let (step_x, mut side_dist_x) = if ray_dx < 0.0 {
+        (-1, (x0 - map_x as f32) * delta_x)
+    } else {
+        (1, ((map_x as f32 + 1.0) - x0) * delta_x)
+    };
+    let (step_y, mut side_dist_y) = if ray_dy < 0.0 {
+        (-1, (y0 - map_y as f32) * delta_y)
+    } else {
+        (1, ((map_y as f32 + 1.0) - y0) * delta_y)
+    };
+
+    while map_x != dest_x || map_y != dest_y {
+        // el siguiente borde queda más allá del destino: no hay nada en medio
+        if side_dist_x.min(side_dist_y) > dist { return true; }
+        if side_dist_x < side_dist_y {
+            side_dist_x += delta_x;
+            map_x += step_x;
+        } else {
+            side_dist_y += delta_y;
+            map_y += step_y;
+        }
+        match celda(mapa, map_x, map_y) {
+            Some(c) if es_caminable(c) => {}
+            _ => return false,
+        }
+    }
+    true
 }
 
 pub struct Texturas {
@@ -91,9 +203,10 @@ pub struct Texturas {
 
 impl Texturas {
     pub fn nuevo() -> Self {
-        // generamos 7 “slots” (0..6); usamos 1..6 para paredes distintas
+        // generamos 8 “slots” (0..7); 1..6 para paredes distintas, 0 para la
+        // puerta 'D' y 7 para el bloque 'X'
         let mut v: Vec<[Color; 64 * 64]> = Vec::new();
-        for i in 0..7 {
+        for i in 0..8 {
             let mut arr = [Color::BLACK; 64 * 64];
             for y in 0..64 {
                 for x in 0..64 {
@@ -104,7 +217,12 @@ impl Texturas {
                         4 => if ((x ^ y) & 16) == 0 { Color::BROWN } else { Color::BEIGE },
                         5 => if (x + y) % 10 < 5 { Color::PURPLE } else { Color::VIOLET },
                         6 => if (x * 3 + y * 5) % 37 < 18 { Color::GRAY } else { Color::LIGHTGRAY },
-                        _ => Color::ORANGE,
+                        // cajón: borde, tablas y una cruz
+                        7 if !(4..60).contains(&x) || !(4..60).contains(&y) => Color::DARKBROWN,
+                        7 if (x as i32 - y as i32).abs() < 4 || (x as i32 + y as i32 - 63).abs() < 4 => Color::BROWN,
+                        7 => if (y / 8) % 2 == 0 { Color::BEIGE } else { Color::GOLD },
+                        _ if !(4..60).contains(&x) || y < 4 => Color::DARKBROWN, // marco
+                        _ => if (x / 8) % 2 == 0 { Color::BROWN } else { Color::ORANGE }, // tablas
                     };
                     arr[y * 64 + x] = c;
                 }
@@ -113,6 +231,25 @@ impl Texturas {
         }
         Self { tex: v }
     }
+    /// Reemplaza las paredes 1..6 con las del atlas: una fila de cuadros de
+    /// 64×64, el primero para '1'; un séptimo cuadro es el bloque 'X'. Si hay
+    /// menos cuadros, el resto queda igual.
+    pub fn cargar_atlas(&mut self, ruta: &Path) -> Result<(), String> {
+        let img = Image::load_image(&ruta.to_string_lossy())?;
+        let (w, h) = (img.width() as usize, img.height() as usize);
+        if h < 64 || w < 64 {
+            return Err(format!("el atlas mide {w}×{h}; cada textura es de 64×64"));
+        }
+        let pix = img.get_image_data();
+        for (k, t) in self.tex.iter_mut().skip(1).take(w / 64).enumerate() {
+            for y in 0..64 {
+                for x in 0..64 {
+                    t[y * 64 + x] = pix[y * w + k * 64 + x];
+                }
+            }
+        }
+        Ok(())
+    }
     pub fn sample(&self, id: usize, u: f32, v: f32) -> Color {
         let tid = id.min(self.tex.len() - 1);
         // asegurar u,v en [0,1)
@@ -130,77 +267,154 @@ impl Texturas {
     }
 }
 
-/// Raycasting de muros con textura y cielo/piso simples, devuelve zbuffer por columna
-pub fn dibujar_escena(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa, tex: &mut Texturas, z: &mut [f32]) {
-    let w = fb.w as i32;
-    let h = fb.h as i32;
+/// Atlas de texturas opcional, junto al juego (si no está se usan las generadas)
+pub fn ruta_atlas() -> PathBuf {
+    PathBuf::from("texturas.png")
+}
 
-    // cielo y piso
-    for y in 0..h {
-        let c = if y < h / 2 { Color::SKYBLUE } else { Color::BROWN };
-        for x in 0..w { fb.set(x, y, c); }
-    }
+/// Campo de visión horizontal de la cámara, por defecto (se cambia en opciones)
+pub const FOV: f32 = 60.0 * std::f32::consts::PI / 180.0;
 
-    let fov = 60.0_f32.to_radians();
-    for x in 0..w {
-        let cam_x = 2.0 * (x as f32 / w as f32) - 1.0;
-        let ray_ang = j.ang + (fov / 2.0) * cam_x;
+/// Proyecta un punto del mundo a la pantalla con la misma cámara que
+/// `dibujar_escena` (columna ∝ ángulo relativo). Devuelve (columna, distancia)
+/// con la distancia comparable al zbuffer, o `None` si queda detrás.
+pub fn a_pantalla(j: &Jugador, x: f32, y: f32, w: i32) -> Option<(i32, f32)> {
+    let dx = x - j.x;
+    let dy = y - j.y;
+    let adelante = j.ang.cos() * dx + j.ang.sin() * dy;
+    if adelante <= 0.01 { return None; }
+    let lateral = j.ang.cos() * dy - j.ang.sin() * dx;
+    let rel = lateral.atan2(adelante);
+    let col = ((w as f32 / 2.0) * (1.0 + rel / (j.fov / 2.0))) as i32;
+    Some((col, (dx * dx + dy * dy).sqrt()))
+}
 
-        let mut map_x = j.x.floor() as i32;
-        let mut map_y = j.y.floor() as i32;
+/// Resultado de lanzar un rayo contra las paredes del mapa
+#[derive(Clone, Copy)]
+pub struct ImpactoRayo {
+    pub dist: f32,   // distancia a lo largo del rayo
+    pub lado: i32,   // 0: cara X, 1: cara Y
+    pub celda: char, // pared golpeada
+    pub wall_x: f32, // coordenada u sobre la pared, en [0,1)
+}
 
-        let ray_dx = ray_ang.cos();
-        let ray_dy = ray_ang.sin();
+/// DDA desde (ox, oy) con ángulo `ang` hasta la primera celda no caminable.
+/// `None` si el rayo sale del mapa sin tocar pared.
+pub fn lanzar_rayo(mapa: &Mapa, ox: f32, oy: f32, ang: f32) -> Option<ImpactoRayo> {
+    let mut map_x = ox.floor() as i32;
+    let mut map_y = oy.floor() as i32;
 
-        let delta_x = if ray_dx == 0.0 { 1e30 } else { (1.0 / ray_dx).abs() };
-        let delta_y = if ray_dy == 0.0 { 1e30 } else { (1.0 / ray_dy).abs() };
+    let ray_dx = ang.cos();
+    let ray_dy = ang.sin();
 
-        let step_x: i32;
-        let step_y: i32;
-        let mut side_dist_x: f32;
-        let mut side_dist_y: f32;
+    let delta_x = if ray_dx == 0.0 { 1e30 } else { (1.0 / ray_dx).abs() };
+    let delta_y = if ray_dy == 0.0 { 1e30 } else { (1.0 / ray_dy).abs() };
 
-        if ray_dx < 0.0 {
-            step_x = -1;
-            side_dist_x = (j.x - map_x as f32) * delta_x;
-        } else {
-            step_x = 1;
-            side_dist_x = ((map_x as f32 + 1.0) - j.x) * delta_x;
-        }
-        if ray_dy < 0.0 {
-            step_y = -1;
-            side_dist_y = (j.y - map_y as f32) * delta_y;
+    let step_x: i32;
+    let step_y: i32;
+    let mut side_dist_x: f32;
+    let mut side_dist_y: f32;
+
+    if ray_dx < 0.0 {
+        step_x = -1;
+        side_dist_x = (ox - map_x as f32) * delta_x;
+    } else {
+        step_x = 1;
+        side_dist_x = ((map_x as f32 + 1.0) - ox) * delta_x;
+    }
+    if ray_dy < 0.0 {
+        step_y = -1;
+        side_dist_y = (oy - map_y as f32) * delta_y;
+    } else {
+        step_y = 1;
+        side_dist_y = ((map_y as f32 + 1.0) - oy) * delta_y;
+    }
+
+    let mut side; // 0:x, 1:y
+    let cell = loop {
+        if side_dist_x < side_dist_y {
+            side_dist_x += delta_x;
+            map_x += step_x;
+            side = 0;
         } else {
-            step_y = 1;
-            side_dist_y = ((map_y as f32 + 1.0) - j.y) * delta_y;
+            side_dist_y += delta_y;
+            map_y += step_y;
+            side = 1;
         }
+        // golpea si NO es caminable (o sea, es pared: 1..6); fuera del mapa no hay impacto
+        let c = celda(mapa, map_x, map_y)?;
+        if !es_caminable(c) { break c; }
+    };
 
-        let mut hit = false;
-        let mut side = 0; // 0:x, 1:y
-        let mut cell = ' ';
-        while !hit {
-            if side_dist_x < side_dist_y {
-                side_dist_x += delta_x;
-                map_x += step_x;
-                side = 0;
-            } else {
-                side_dist_y += delta_y;
-                map_y += step_y;
-                side = 1;
-            }
-            if let Some(c) = celda(mapa, map_x, map_y) {
-                // golpea si NO es caminable (o sea, es pared: 1..6)
-                if !es_caminable(c) { hit = true; cell = c; }
-            } else { break; }
+    let mut perp_dist = if side == 0 {
+        (map_x as f32 - ox + (1 - step_x) as f32 / 2.0) / ray_dx
+    } else {
+        (map_y as f32 - oy + (1 - step_y) as f32 / 2.0) / ray_dy
+    };
+    if perp_dist < 0.001 { perp_dist = 0.001; }
+
+    // coordenada de textura (u)
+    let mut wall_x = if side == 0 {
+        oy + perp_dist * ray_dy
+    } else {
+        ox + perp_dist * ray_dx
+    };
+    wall_x -= wall_x.floor();
+
+    Some(ImpactoRayo { dist: perp_dist, lado: side, celda: cell, wall_x })
+}
+
+/// Rayo contra un bloque de una celda con la esquina en (`bx`, `by`), que no
+/// está alineado a la grilla (se está deslizando). `None` si no lo toca o
+/// si el origen está adentro.
+pub fn rayo_contra_caja(ox: f32, oy: f32, ang: f32, bx: f32, by: f32) -> Option<ImpactoRayo> {
+    let (ray_dx, ray_dy) = (ang.cos(), ang.sin());
+    // método de las losas: entrada y salida en cada eje
+    let losa = |o: f32, d: f32, b: f32| {
+        if d == 0.0 {
+            return if o >= b && o <= b + 1.0 { (f32::NEG_INFINITY, f32::INFINITY) } else { (f32::INFINITY, f32::NEG_INFINITY) };
         }
+        let (t0, t1) = ((b - o) / d, (b + 1.0 - o) / d);
+        (t0.min(t1), t0.max(t1))
+    };
+    let (x0, x1) = losa(ox, ray_dx, bx);
+    let (y0, y1) = losa(oy, ray_dy, by);
+    let (entra, sale) = (x0.max(y0), x1.min(y1));
+    if entra > sale || entra < 0.001 { return None; }
 
-        if !hit { continue; }
-        let mut perp_dist = if side == 0 {
-            (map_x as f32 - j.x + (1 - step_x) as f32 / 2.0) / ray_dx
-        } else {
-            (map_y as f32 - j.y + (1 - step_y) as f32 / 2.0) / ray_dy
-        };
-        if perp_dist < 0.001 { perp_dist = 0.001; }
+    let lado = if x0 > y0 { 0 } else { 1 };
+    let mut wall_x = if lado == 0 { oy + entra * ray_dy } else { ox + entra * ray_dx };
+    wall_x -= wall_x.floor();
+    Some(ImpactoRayo { dist: entra, lado, celda: 'X', wall_x })
+}
+
+/// Raycasting de muros con textura y cielo/piso simples, devuelve zbuffer por columna.
+/// `destello` (0..1) ilumina las paredes cercanas (fogonazo del arma, explosiones).
+/// `cajas` son bloques fuera de la grilla (esquina en mundo), que tapan igual que una pared.
+pub fn dibujar_escena(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa, cajas: &[(f32, f32)], tex: &mut Texturas,
+                      z: &mut [f32], destello: f32) {
+    let w = fb.w as i32;
+    let h = fb.h as i32;
+
+    // cielo y piso
+    for y in 0..h {
+        let c = if y < h / 2 { Color::SKYBLUE } else { Color::BROWN };
+        for x in 0..w { fb.set(x, y, c); }
+    }
+
+    for x in 0..w {
+        let cam_x = 2.0 * (x as f32 / w as f32) - 1.0;
+        let ray_ang = j.ang + (j.fov / 2.0) * cam_x;
+
+        let hit = cajas.iter()
+            .filter_map(|&(bx, by)| rayo_contra_caja(j.x, j.y, ray_ang, bx, by))
+            .chain(lanzar_rayo(mapa, j.x, j.y, ray_ang))
+            .min_by(|a, b| a.dist.total_cmp(&b.dist));
+        let Some(hit) = hit else { continue; };
+        let perp_dist = hit.dist;
+        let wall_x = hit.wall_x;
+        let cell = hit.celda;
+        let side = hit.lado;
         z[x as usize] = perp_dist;
 
         // altura de pared
@@ -208,50 +422,55 @@ pub fn dibujar_escena(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa, tex: &mut
         let draw_start = (-line_h / 2 + h / 2).max(0);
         let draw_end   = ( line_h / 2 + h / 2).min(h - 1);
 
-        // coordenada de textura (u)
-        let mut wall_x = if side == 0 {
-            j.y + perp_dist * ray_dy
-        } else {
-            j.x + perp_dist * ray_dx
-        };
-        wall_x -= wall_x.floor();
-
         // id de textura por tipo
         let id = match cell {
-            '1' => 1, '2' => 2, '3' => 3, '4' => 4, '5' => 5, '6' => 6, _ => 1
+            '1' => 1, '2' => 2, '3' => 3, '4' => 4, '5' => 5, '6' => 6, 'D' => 0, 'X' => 7, _ => 1
         };
 
-        // sombreado leve en caras Y
+        // sombreado leve en caras Y; el destello aclara más cuanto más cerca
         let shade = if side == 1 { 0.8 } else { 1.0 };
+        let shade = shade * (1.0 + destello * 1.5 / (1.0 + perp_dist));
 
         // pintar columna texturizada
         for y in draw_start..=draw_end {
             let v = (y - draw_start) as f32 / (draw_end - draw_start).max(1) as f32;
             let mut col = tex.sample(id, wall_x, v);
-            col.r = ((col.r as f32) * shade) as u8;
-            col.g = ((col.g as f32) * shade) as u8;
-            col.b = ((col.b as f32) * shade) as u8;
+            col.r = ((col.r as f32) * shade).min(255.0) as u8;
+            col.g = ((col.g as f32) * shade).min(255.0) as u8;
+            col.b = ((col.b as f32) * shade).min(255.0) as u8;
             fb.set(x, y, col);
         }
     }
 }
 
-/// Minimap 2D en la esquina (escala 4 px por celda)
-pub fn dibujar_minimapa(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa) {
-    let s = 4; // px por celda
-    let offx = 6;
-    let offy = 6;
+/// Color de cada celda en la vista desde arriba
+pub fn color_plano(c: char) -> Color {
+    match c {
+        ' ' => Color::DARKGREEN, // piso
+        'E' => Color::GOLD,      // salida
+        'C' => Color::YELLOW,    // moneda
+        'P' => Color::BLACK,     // pozo
+        'A' => Color::ORANGE,    // antorcha/sprite
+        'M' => Color::DARKGREEN, // spawn de enemigo (se marca aparte)
+        'S' => Color::LIGHTGRAY, // pinchos
+        'L' => Color::RED,       // lava
+        'V' => Color::LIME,      // veneno
+        'H' => Color::PINK,      // botiquín
+        'B' => Color::SKYBLUE,   // munición
+        'D' => Color::BROWN,     // puerta
+        'T' => Color::VIOLET,    // teletransportador
+        'X' => Color::BEIGE,     // bloque
+        '_' => Color::DARKPURPLE, // placa de presión
+        _   => Color::DARKGRAY,  // pared
+    }
+}
+
+/// Mapa visto desde arriba: cada celda es un cuadrado de `s` px a partir de
+/// (`offx`, `offy`). Lo usan el minimapa y el editor (con otros colores).
+pub fn dibujar_plano(fb: &mut Framebuffer, mapa: &Mapa, offx: i32, offy: i32, s: i32, color: impl Fn(char) -> Color) {
     for y in 0..mapa.len() as i32 {
         for x in 0..mapa[0].len() as i32 {
-            let c = celda(mapa, x, y).unwrap_or('#');
-            let col = match c {
-                ' ' => Color::DARKGREEN, // piso
-                'E' => Color::GOLD,      // salida
-                'C' => Color::YELLOW,    // moneda
-                'P' => Color::BLACK,     // pozo
-                'A' => Color::ORANGE,    // antorcha/sprite
-                _   => Color::DARKGRAY,  // pared
-            };
+            let col = color(celda(mapa, x, y).unwrap_or('#'));
             for yy in 0..s {
                 for xx in 0..s {
                     fb.set(offx + x * s + xx, offy + y * s + yy, col);
@@ -259,6 +478,14 @@ pub fn dibujar_minimapa(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa) {
             }
         }
     }
+}
+
+/// Minimap 2D en la esquina (escala 4 px por celda)
+pub fn dibujar_minimapa(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa) {
+    let s = 4; // px por celda
+    let offx = 6;
+    let offy = 6;
+    dibujar_plano(fb, mapa, offx, offy, s, color_plano);
     // jugador
     let px = offx + (j.x as i32) * s + s / 2;
     let py = offy + (j.y as i32) * s + s / 2;
@@ -273,7 +500,19 @@ pub fn dibujar_minimapa(fb: &mut Framebuffer, j: &Jugador, mapa: &Mapa) {
     linea_bresenham(fb, px, py, fx, fy, Color::YELLOW);
 }
 
-fn linea_bresenham(fb: &mut Framebuffer, x0: i32, y0: i32, x1: i32, y1: i32, c: Color) {
+/// Marca una entidad móvil (enemigo, etc.) sobre el minimapa
+pub fn marcar_minimapa(fb: &mut Framebuffer, x: f32, y: f32, c: Color) {
+    let s = 4;
+    let px = 6 + (x as i32) * s + s / 2;
+    let py = 6 + (y as i32) * s + s / 2;
+    for yy in -1..=0 {
+        for xx in -1..=0 {
+            fb.set(px + xx, py + yy, c);
+        }
+    }
+}
+
+pub fn linea_bresenham(fb: &mut Framebuffer, x0: i32, y0: i32, x1: i32, y1: i32, c: Color) {
     let dx = (x1 - x0).abs();
     let sx = if x0 < x1 { 1 } else { -1 };
     let dy = -(y1 - y0).abs();
@@ -0,0 +1,145 @@
+//! Opciones del juego: volumen, campo de visión, tamaño de la ventana e idioma. Se
+//! guardan en `opciones.txt` dentro del directorio de datos, igual que los
+//! controles; la pantalla de opciones también ajusta las sensibilidades de
+//! `Controles` y lleva a la pantalla de controles.
+
+use crate::controles::Controles;
+use crate::guardado::directorio_datos;
+use crate::idiomas::{Idioma, Mensaje};
+use std::fs;
+use std::path::PathBuf;
+
+const VERSION: u32 = 1;
+const CABECERA: &str = "raycaster-opciones";
+
+pub const FOV_MIN: f32 = 50.0;
+pub const FOV_MAX: f32 = 110.0;
+pub const ESCALA_MAX: i32 = 4;
+
+/// Filas de la pantalla de opciones
+#[derive(Clone, Copy, PartialEq)]
+pub enum Opcion {
+    Volumen,
+    SensMouse,
+    SensPad,
+    Fov,
+    Escala,
+    Idioma,
+    Controles,
+}
+
+impl Opcion {
+    pub const TODAS: [Opcion; 7] = [
+        Opcion::Volumen, Opcion::SensMouse, Opcion::SensPad,
+        Opcion::Fov, Opcion::Escala, Opcion::Idioma, Opcion::Controles,
+    ];
+
+    pub fn nombre(self, l: Idioma) -> &'static str {
+        l.t(match self {
+            Opcion::Volumen => "opcion.volumen",
+            Opcion::SensMouse => "opcion.sens_mouse",
+            Opcion::SensPad => "opcion.sens_pad",
+            Opcion::Fov => "opcion.fov",
+            Opcion::Escala => "opcion.escala",
+            Opcion::Idioma => "opcion.idioma",
+            Opcion::Controles => "opcion.controles",
+        })
+    }
+}
+
+pub struct Opciones {
+    pub volumen: f32,     // 0..1
+    pub fov_grados: f32,  // FOV_MIN..FOV_MAX
+    pub escala: i32,      // ventana = resolución lógica × escala
+    pub idioma: Idioma,
+    ruta: PathBuf,
+    pub aviso: Option<Mensaje>,
+}
+
+impl Opciones {
+    pub fn por_defecto(escala: i32) -> Self {
+        Self {
+            volumen: 0.8,
+            fov_grados: 60.0,
+            escala,
+            idioma: Idioma::POR_DEFECTO,
+            ruta: directorio_datos().join("opciones.txt"),
+            aviso: None,
+        }
+    }
+
+    /// Lee `opciones.txt`; si falta o tiene valores raros quedan los de siempre.
+    pub fn cargar(escala: i32) -> Self {
+        let mut o = Self::por_defecto(escala);
+        let txt = match fs::read_to_string(&o.ruta) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return o,
+            Err(e) => {
+                o.aviso = Some(Mensaje::con("aviso.opciones_no_leidas", &[&e]));
+                return o;
+            }
+        };
+        let mut lineas = txt.lines();
+        let version = lineas.next()
+            .and_then(|cab| cab.strip_prefix(CABECERA))
+            .and_then(|v| v.trim().parse::<u32>().ok());
+        if version.is_none_or(|v| v > VERSION) {
+            o.aviso = Some(Mensaje::nuevo("aviso.opciones_invalidas"));
+            return o;
+        }
+        for l in lineas {
+            let (clave, valor) = l.trim().split_once(' ').unwrap_or((l, ""));
+            match clave {
+                "volumen" => if let Ok(v) = valor.parse::<f32>() { o.volumen = v.clamp(0.0, 1.0) },
+                "fov" => if let Ok(v) = valor.parse::<f32>() { o.fov_grados = v.clamp(FOV_MIN, FOV_MAX) },
+                "escala" => if let Ok(v) = valor.parse::<i32>() { o.escala = v.clamp(1, ESCALA_MAX) },
+                "idioma" => if let Some(i) = Idioma::desde_codigo(valor) { o.idioma = i },
+                _ => {}
+            }
+        }
+        o
+    }
+
+    pub fn guardar(&mut self) {
+        let s = format!("{CABECERA} {VERSION}\nvolumen {}\nfov {}\nescala {}\nidioma {}\n",
+                        self.volumen, self.fov_grados, self.escala, self.idioma.codigo());
+        let res = (|| -> std::io::Result<()> {
+            if let Some(dir) = self.ruta.parent() { fs::create_dir_all(dir)?; }
+            fs::write(&self.ruta, s)
+        })();
+        if let Err(e) = res {
+            self.aviso = Some(Mensaje::con("aviso.opciones_no_guardadas", &[&e]));
+        }
+    }
+
+    /// Campo de visión en radianes, para la cámara
+    pub fn fov(&self) -> f32 {
+        self.fov_grados.to_radians()
+    }
+
+    pub fn valor(&self, o: Opcion, c: &Controles) -> String {
+        match o {
+            Opcion::Volumen => format!("{:.0}%", self.volumen * 100.0),
+            Opcion::SensMouse => format!("{:.4}", c.sens_mouse),
+            Opcion::SensPad => format!("{:.3}", c.sens_pad),
+            Opcion::Fov => format!("{:.0}°", self.fov_grados),
+            Opcion::Escala => format!("×{}", self.escala),
+            Opcion::Idioma => self.idioma.nombre().to_string(),
+            Opcion::Controles => String::new(),
+        }
+    }
+
+    /// ←/→ sobre una opción (`paso` = -1 o 1)
+    pub fn ajustar(&mut self, o: Opcion, paso: i32, c: &mut Controles) {
+        let factor = if paso > 0 { 1.1 } else { 1.0 / 1.1 };
+        match o {
+            Opcion::Volumen => self.volumen = (self.volumen + 0.1 * paso as f32).clamp(0.0, 1.0),
+            Opcion::SensMouse => c.sens_mouse *= factor,
+            Opcion::SensPad => c.sens_pad *= factor,
+            Opcion::Fov => self.fov_grados = (self.fov_grados + 5.0 * paso as f32).clamp(FOV_MIN, FOV_MAX),
+            Opcion::Escala => self.escala = (self.escala + paso).clamp(1, ESCALA_MAX),
+            Opcion::Idioma => self.idioma = self.idioma.otro(paso),
+            Opcion::Controles => {}
+        }
+    }
+}
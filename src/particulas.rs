@@ -0,0 +1,214 @@
+//! Partículas en espacio de mundo: brillo al juntar monedas, brasas de
+//! antorchas, polvo de los disparos contra la pared, escombros en los pozos
+//! y chispas en los teletransportadores.
+//! Se dibujan con prueba de profundidad contra el zbuffer por columna, igual
+//! que `dibujar_sprites`. El pool es fijo: nunca se reserva memoria por frame.
+
+use crate::motor::*;
+use crate::sprites::{SpriteKind, Sprites};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+
+const MAX_PARTICULAS: usize = 384;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Efecto {
+    Brillo,     // moneda recogida
+    Brasa,      // antorcha
+    Polvo,      // impacto contra pared
+    Escombros,  // caída en pozo
+    Explosion,  // cohete
+    Portal,     // teletransportador (y el salto)
+}
+
+/// A qué está pegado un emisor
+#[derive(Clone, Copy)]
+pub enum Anclaje {
+    Celda(i32, i32),
+    /// Sprite de ese tipo en esa celda; el emisor se apaga cuando el sprite ya no está
+    Sprite(SpriteKind, i32, i32),
+}
+
+#[derive(Clone, Copy)]
+pub struct Emisor {
+    pub anclaje: Anclaje,
+    pub efecto: Efecto,
+    pub cada: u32, // frames entre emisiones
+    pub alto: f32, // altura a la que nacen las partículas
+}
+
+#[derive(Clone, Copy)]
+struct Particula {
+    x: f32,
+    y: f32,
+    z: f32, // altura: 0 = piso, 1 = techo
+    vx: f32,
+    vy: f32,
+    vz: f32,
+    gravedad: f32,
+    vida: u16, // frames restantes (0 = libre)
+    cae: bool, // atraviesa el piso (escombros en el pozo)
+    color: Color,
+}
+
+const LIBRE: Particula = Particula {
+    x: 0.0, y: 0.0, z: 0.0, vx: 0.0, vy: 0.0, vz: 0.0, gravedad: 0.0, vida: 0, cae: false, color: Color::BLANK,
+};
+
+pub struct Particulas {
+    pool: Box<[Particula; MAX_PARTICULAS]>,
+    siguiente: usize, // índice round-robin: si no hay libres se pisa la más vieja
+    pub emisores: Vec<Emisor>,
+    rng: StdRng,
+    t: u32,
+}
+
+impl Particulas {
+    pub fn nuevo() -> Self {
+        Self {
+            pool: Box::new([LIBRE; MAX_PARTICULAS]),
+            siguiente: 0,
+            emisores: Vec::new(),
+            rng: StdRng::seed_from_u64(0x5eed),
+            t: 0,
+        }
+    }
+
+    /// Vacía el pool y crea los emisores del nivel: brasas en cada antorcha,
+    /// polvo cayendo en cada pozo y chispas subiendo en cada
+    /// teletransportador. `semilla` es la de la partida, para que una
+    /// repetición muestre las mismas partículas.
+    pub fn rellenar_desde_mapa(&mut self, mapa: &Mapa, semilla: u64) {
+        self.pool.fill(LIBRE);
+        self.emisores.clear();
+        self.rng = StdRng::seed_from_u64(semilla);
+        self.t = 0;
+        for y in 0..mapa.len() as i32 {
+            for x in 0..mapa[0].len() as i32 {
+                match celda(mapa, x, y) {
+                    Some('A') => self.emisores.push(Emisor {
+                        anclaje: Anclaje::Sprite(SpriteKind::Torch, x, y), efecto: Efecto::Brasa, cada: 6, alto: 0.6,
+                    }),
+                    // un bloque puede tapar el pozo: el polvo se corta con el sprite
+                    Some('P') => self.emisores.push(Emisor {
+                        anclaje: Anclaje::Sprite(SpriteKind::Pit, x, y), efecto: Efecto::Escombros, cada: 40, alto: 0.05,
+                    }),
+                    Some('T') => self.emisores.push(Emisor {
+                        anclaje: Anclaje::Celda(x, y), efecto: Efecto::Portal, cada: 5, alto: 0.05,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn emitir(&mut self, efecto: Efecto, x: f32, y: f32, z: f32) {
+        let r = &mut self.rng;
+        let p = match efecto {
+            Efecto::Brillo => Particula {
+                x, y, z,
+                vx: r.gen_range(-0.02..0.02), vy: r.gen_range(-0.02..0.02), vz: r.gen_range(0.01..0.04),
+                gravedad: 0.001, vida: r.gen_range(20..40),
+                cae: false,
+                color: if r.gen_bool(0.5) { Color::YELLOW } else { Color::WHITE },
+            },
+            Efecto::Brasa => Particula {
+                x: x + r.gen_range(-0.08..0.08), y: y + r.gen_range(-0.08..0.08), z,
+                vx: r.gen_range(-0.004..0.004), vy: r.gen_range(-0.004..0.004), vz: r.gen_range(0.005..0.012),
+                gravedad: 0.0, vida: r.gen_range(30..60),
+                cae: false,
+                color: if r.gen_bool(0.3) { Color::YELLOW } else { Color::ORANGE },
+            },
+            Efecto::Polvo => Particula {
+                x, y, z: z + r.gen_range(-0.05..0.05),
+                vx: r.gen_range(-0.015..0.015), vy: r.gen_range(-0.015..0.015), vz: r.gen_range(0.0..0.02),
+                gravedad: 0.0015, vida: r.gen_range(15..30),
+                cae: false,
+                color: if r.gen_bool(0.5) { Color::LIGHTGRAY } else { Color::BEIGE },
+            },
+            Efecto::Escombros => Particula {
+                x: x + r.gen_range(-0.4..0.4), y: y + r.gen_range(-0.4..0.4), z: z + r.gen_range(0.0..0.1),
+                vx: 0.0, vy: 0.0, vz: r.gen_range(-0.01..0.0),
+                gravedad: 0.002, vida: r.gen_range(30..50),
+                cae: true,
+                color: if r.gen_bool(0.5) { Color::DARKBROWN } else { Color::GRAY },
+            },
+            Efecto::Explosion => Particula {
+                x, y, z,
+                vx: r.gen_range(-0.05..0.05), vy: r.gen_range(-0.05..0.05), vz: r.gen_range(-0.03..0.05),
+                gravedad: 0.001, vida: r.gen_range(10..25),
+                cae: false,
+                color: if r.gen_bool(0.5) { Color::ORANGE } else { Color::YELLOW },
+            },
+            Efecto::Portal => {
+                // en un anillo alrededor del centro, subiendo en espiral
+                let a = r.gen_range(0.0..std::f32::consts::TAU);
+                Particula {
+                    x: x + a.cos() * 0.3, y: y + a.sin() * 0.3, z,
+                    vx: -a.sin() * 0.01, vy: a.cos() * 0.01, vz: r.gen_range(0.008..0.02),
+                    gravedad: 0.0, vida: r.gen_range(25..45),
+                    cae: false,
+                    color: if r.gen_bool(0.5) { Color::MAGENTA } else { Color::VIOLET },
+                }
+            }
+        };
+        // buscar un hueco libre a partir de `siguiente`; si no hay, pisar ese
+        let libre = (0..MAX_PARTICULAS)
+            .map(|i| (self.siguiente + i) % MAX_PARTICULAS)
+            .find(|&i| self.pool[i].vida == 0)
+            .unwrap_or(self.siguiente);
+        self.pool[libre] = p;
+        self.siguiente = (libre + 1) % MAX_PARTICULAS;
+    }
+
+    /// Ráfaga de `n` partículas en un punto del mundo
+    pub fn rafaga(&mut self, efecto: Efecto, x: f32, y: f32, z: f32, n: usize) {
+        for _ in 0..n { self.emitir(efecto, x, y, z); }
+    }
+
+    pub fn actualizar(&mut self, spr: &Sprites) {
+        self.t += 1;
+        for i in 0..self.emisores.len() {
+            let e = self.emisores[i];
+            if !self.t.is_multiple_of(e.cada) { continue; }
+            match e.anclaje {
+                Anclaje::Celda(cx, cy) => {
+                    self.emitir(e.efecto, cx as f32 + 0.5, cy as f32 + 0.5, e.alto);
+                }
+                Anclaje::Sprite(kind, cx, cy) => {
+                    let vivo = spr.lista.iter()
+                        .any(|s| s.kind == kind && s.x.floor() as i32 == cx && s.y.floor() as i32 == cy);
+                    if vivo { self.emitir(e.efecto, cx as f32 + 0.5, cy as f32 + 0.5, e.alto); }
+                }
+            }
+        }
+
+        for p in self.pool.iter_mut().filter(|p| p.vida > 0) {
+            p.x += p.vx;
+            p.y += p.vy;
+            p.z += p.vz;
+            p.vz -= p.gravedad;
+            p.vida -= 1;
+            // los escombros siguen de largo dentro del pozo; el resto se apaga al tocar el piso
+            if p.z < -0.5 || (p.z < 0.0 && !p.cae) { p.vida = 0; }
+        }
+    }
+
+    /// Dibuja las partículas vivas con prueba de profundidad contra `z`.
+    pub fn dibujar(&self, fb: &mut Framebuffer, j: &Jugador, z: &[f32]) {
+        let w = fb.w as i32;
+        let h = fb.h as i32;
+        for p in self.pool.iter().filter(|p| p.vida > 0) {
+            let Some((sx, prof)) = a_pantalla(j, p.x, p.y, w) else { continue; };
+            let sy = h / 2 + ((0.5 - p.z) * h as f32 / prof) as i32;
+            let tam = (2.0 / prof).clamp(1.0, 3.0) as i32;
+            for xx in sx..sx + tam {
+                if xx < 0 || xx >= w || prof >= z[xx as usize] { continue; }
+                for yy in sy..sy + tam {
+                    fb.set(xx, yy, p.color);
+                }
+            }
+        }
+    }
+}
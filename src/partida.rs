@@ -0,0 +1,612 @@
+//! Estado completo de una partida en curso: nivel, mapa (copia propia, para
+//! lo que cambie durante el juego), jugador, sprites que quedan, enemigos,
+//! armas, disparadores, teletransportadores, bloques y progreso. Se guarda
+//! y restaura tal cual en texto plano: F5/F9 en el juego, o
+//! `--partida <archivo>` para reproducir un reporte de error.
+//! Las partículas y animaciones no se guardan (son solo decorado).
+//!
+//! La simulación no lee raylib: cada frame recibe una `Entrada` ya armada
+//! por `main`, así que alcanza con grabar las entradas para repetir la
+//! partida exacta (ver `repeticion`).
+
+use crate::armas::*;
+use crate::bloques::{Bloques, Empuje};
+use crate::caminos::PELIGROS;
+use crate::enemigos::*;
+use crate::eventos::{Eventos, Orden};
+use crate::guardado::directorio_datos;
+use crate::mapas::{buscar_o_generar, Nivel};
+use crate::motor::*;
+use crate::particulas::{Efecto, Particulas};
+use crate::reglas::*;
+use crate::salud::*;
+use crate::sprites::*;
+use crate::teletransportes::Teletransportes;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VERSION: u32 = 1;
+const CABECERA: &str = "raycaster-partida";
+pub const VIDA_MAX: i32 = 100;
+const BOTIQUIN: i32 = 30; // vida que recupera cada 'H'
+
+/// Lo que el jugador pide en un frame. La dirección va cuantizada a `i8`
+/// para que lo que se simula sea exactamente lo que se graba.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct Entrada {
+    pub mov_x: i8,  // lateral, -127..=127
+    pub mov_y: i8,  // adelante/atrás
+    pub giro: f32,  // radianes a girar en este frame
+    pub disparo: bool,
+    pub arma: Option<TipoArma>,
+}
+
+impl Entrada {
+    /// Normaliza (dir_x, dir_y) si pasa de 1 y la cuantiza.
+    pub fn nueva(dir_x: f32, dir_y: f32, giro: f32, disparo: bool, arma: Option<TipoArma>) -> Self {
+        let len = (dir_x * dir_x + dir_y * dir_y).sqrt().max(1.0);
+        let q = |v: f32| (v / len * 127.0).round() as i8;
+        Self { mov_x: q(dir_x), mov_y: q(dir_y), giro, disparo, arma }
+    }
+
+    fn direccion(&self) -> (f32, f32) {
+        (self.mov_x as f32 / 127.0, self.mov_y as f32 / 127.0)
+    }
+}
+
+/// Cómo quedó el nivel después de un frame
+pub enum Resultado {
+    Sigue,
+    Falta(Vec<Regla>), // está en la salida pero faltan reglas
+    Gano,
+    Perdio(&'static str),
+}
+
+pub struct Partida {
+    pub nivel: usize, // índice en `niveles()`
+    pub semilla: u64, // para lo aleatorio (partículas); se graba con la repetición
+    pub mapa: Mapa,
+    pub jug: Jugador,
+    pub seguro: (f32, f32), // última posición fuera de peligro (para salir del pozo)
+    pub spr: Sprites,
+    pub enemigos: Enemigos,
+    pub armas: Armas,
+    pub eventos: Eventos,
+    pub tele: Teletransportes,
+    pub bloques: Bloques,
+    pub prog: Progreso,
+}
+
+// --- helpers de spawn seguro ---
+fn es_walkable(mapa: &Mapa, x: i32, y: i32) -> bool {
+    if let Some(c) = celda(mapa, x, y) {
+        // Piso libre o celdas caminables (incluye monedas y pozos)
+        c == ' ' || c == 'E' || c == 'A' || c == 'C' || c == 'P' || c == 'H' || c == 'B'
+    } else {
+        false
+    }
+}
+
+/// Se puede pisar sin lastimarse (para reubicar al jugador en una recarga)
+fn es_segura(mapa: &Mapa, x: i32, y: i32) -> bool {
+    celda(mapa, x, y).is_some_and(|c| es_caminable(c) && !PELIGROS.contains(&c))
+}
+
+fn spawn_mas_cercano(mapa: &Mapa, sx: i32, sy: i32) -> (i32, i32) {
+    celda_mas_cercana(mapa, sx, sy, es_walkable)
+}
+
+/// La celda más cercana a (`sx`, `sy`) que cumple `ok`, buscando en anillos
+fn celda_mas_cercana(mapa: &Mapa, sx: i32, sy: i32, ok: impl Fn(&Mapa, i32, i32) -> bool) -> (i32, i32) {
+    if ok(mapa, sx, sy) {
+        return (sx, sy);
+    }
+    let max_r = (mapa.len() + mapa[0].len()) as i32;
+    for r in 1..=max_r {
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let nx = sx + dx;
+                let ny = sy + dy;
+                if ok(mapa, nx, ny) {
+                    return (nx, ny);
+                }
+            }
+        }
+    }
+    (1, 1)
+}
+
+/// Jugador con los valores de arranque
+fn jugador_en(x: f32, y: f32, ang: f32) -> Jugador {
+    Jugador { x, y, ang, vel: 2.0 / 60.0, rot: 2.2 / 60.0, salud: Salud::nueva(VIDA_MAX), fov: FOV }
+}
+
+/// Semilla para una partida nueva (distinta cada vez)
+pub fn semilla_nueva() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Archivo de la partida rápida (F5/F9)
+pub fn ruta_partida_rapida() -> PathBuf {
+    directorio_datos().join("partida_rapida.txt")
+}
+
+impl Partida {
+    /// Empieza el nivel `idx` desde su punto de inicio.
+    pub fn nueva(niveles: &[Nivel], idx: usize, muertes: u32, semilla: u64) -> Self {
+        let n = &niveles[idx];
+        let mapa = n.mapa.clone();
+        let (sx, sy, ang0) = n.inicio;
+        let (fx, fy) = spawn_mas_cercano(&mapa, sx, sy);
+        let jug = jugador_en(fx as f32 + 0.5, fy as f32 + 0.5, ang0);
+
+        // Rellenar sprites desde mapa (A = antorcha, C = coin, P = pozo)
+        let mut spr = Sprites::nuevo();
+        let coins_total = spr.rellenar_desde_mapa(&mapa);
+        let mut enemigos = Enemigos::nuevo();
+        enemigos.rellenar_desde_mapa(&mapa);
+        let prog = Progreso::nuevo(coins_total, enemigos.lista.len(), muertes);
+
+        let eventos = Eventos::nuevo(&n.disparadores);
+        let tele = Teletransportes::nuevo(&mapa, &n.teletransportes, fx, fy);
+        Self {
+            nivel: idx, semilla, mapa, seguro: (jug.x, jug.y), jug, spr, enemigos, armas: Armas::nuevo(),
+            eventos, tele, bloques: Bloques::nuevo(&n.mapa), prog,
+        }
+    }
+
+    /// Cambia el mapa por el de `n` (recarga en caliente) sin cortar la
+    /// partida: el jugador sigue donde estaba si ahí se puede estar sin
+    /// lastimarse y si no va a la celda segura más cercana. Sprites,
+    /// enemigos, disparadores, teletransportadores y bloques salen del nivel
+    /// nuevo, así que el progreso de monedas y enemigos vuelve a cero; el
+    /// tiempo, la vida y las armas se mantienen.
+    pub fn recargar(&mut self, n: &Nivel) {
+        self.mapa = n.mapa.clone();
+        let (x, y) = celda_mas_cercana(&self.mapa, self.jug.x as i32, self.jug.y as i32, es_segura);
+        if (x, y) != (self.jug.x as i32, self.jug.y as i32) {
+            self.jug.x = x as f32 + 0.5;
+            self.jug.y = y as f32 + 0.5;
+        }
+        self.seguro = (self.jug.x, self.jug.y);
+        self.spr = Sprites::nuevo();
+        let coins_total = self.spr.rellenar_desde_mapa(&self.mapa);
+        self.enemigos.rellenar_desde_mapa(&self.mapa);
+        self.eventos = Eventos::nuevo(&n.disparadores);
+        self.tele = Teletransportes::nuevo(&self.mapa, &n.teletransportes, self.jug.x as i32, self.jug.y as i32);
+        self.bloques = Bloques::nuevo(&n.mapa);
+        let frames = self.prog.frames;
+        self.prog = Progreso::nuevo(coins_total, self.enemigos.lista.len(), self.prog.muertes);
+        self.prog.frames = frames;
+    }
+
+    /// Un frame de simulación: bloques, movimiento, teletransportadores,
+    /// peligros, enemigos, armas, recogibles, disparadores y reglas del
+    /// nivel. `part` solo recibe efectos visuales.
+    pub fn actualizar(&mut self, e: &Entrada, reglas: &[Regla], part: &mut Particulas) -> Resultado {
+        let Partida { mapa, jug, seguro, spr, enemigos, armas, tele, bloques, prog, .. } = self;
+        prog.frames += 1;
+
+        if let Some(a) = e.arma { armas.actual = a; }
+        jug.ang += e.giro;
+
+        // mover con colisiones (C y P son caminables)
+        let (dir_x, dir_y) = e.direccion();
+        let caminando = dir_x != 0.0 || dir_y != 0.0;
+
+        // bloques: caminando contra uno se lo empuja; va antes del movimiento
+        // para que el jugador ya entre en la celda que quedó libre
+        let (mx, my) = desplazamiento(jug, dir_x, dir_y);
+        let (cx, cy) = (jug.x.floor() as i32, jug.y.floor() as i32);
+        let pasos = [((jug.x + mx).floor() as i32 - cx, 0), (0, (jug.y + my).floor() as i32 - cy)];
+        for (dx, dy) in pasos.into_iter().filter(|&p| p != (0, 0)) {
+            let ocupada = |x: i32, y: i32| enemigos.lista.iter().any(|e| e.x.floor() as i32 == x && e.y.floor() as i32 == y);
+            if let Some(Empuje::Pozo(x, y)) = bloques.empujar(mapa, cx + dx, cy + dy, dx, dy, ocupada) {
+                // el pozo queda tapado: se va su sprite (y con él el polvo que caía)
+                spr.recolectar_en(x as f32 + 0.5, y as f32 + 0.5, SpriteKind::Pit);
+                part.rafaga(Efecto::Escombros, x as f32 + 0.5, y as f32 + 0.5, 0.1, 16);
+            }
+        }
+        mover_con_colision(jug, dir_x, dir_y, mapa);
+
+        // teletransportador: el salto es antes de peligros, recogibles y
+        // salida, así que todo eso ya mira la celda de llegada
+        // (un bloque puede haber quedado en el destino)
+        let libre = |x: i32, y: i32| celda(mapa, x, y).is_some_and(es_caminable);
+        if let Some(t) = tele.actualizar(jug.x as i32, jug.y as i32, libre) {
+            part.rafaga(Efecto::Portal, jug.x, jug.y, 0.4, 24);
+            jug.x = t.dx as f32 + 0.5;
+            jug.y = t.dy as f32 + 0.5;
+            if let Some(a) = t.angulo { jug.ang = a; }
+            part.rafaga(Efecto::Portal, jug.x, jug.y, 0.4, 24);
+        }
+
+        // peligros del suelo (pozo, pinchos, lava, veneno)
+        jug.salud.actualizar();
+        match celda(mapa, jug.x as i32, jug.y as i32).and_then(peligro_de_celda) {
+            Some(CausaDanio::Pozo) => {
+                // cae, se lastima y trepa de vuelta al último lugar seguro
+                jug.salud.danio(CausaDanio::Pozo);
+                part.rafaga(Efecto::Escombros, jug.x.floor() + 0.5, jug.y.floor() + 0.5, 0.1, 16);
+                jug.x = seguro.0;
+                jug.y = seguro.1;
+                tele.llegar(jug.x as i32, jug.y as i32);
+            }
+            Some(causa) => { jug.salud.danio(causa); }
+            None => *seguro = (jug.x, jug.y),
+        }
+
+        // enemigos: IA + golpes
+        if enemigos.actualizar(jug, mapa) > 0 {
+            jug.salud.danio(CausaDanio::Enemigo);
+        }
+
+        // armas: disparo, proyectiles y bajas
+        if e.disparo {
+            if let Some((ix, iy)) = armas.disparar(jug, mapa, enemigos) {
+                part.rafaga(Efecto::Polvo, ix, iy, 0.5, 8);
+            }
+        }
+        for (ex, ey) in armas.actualizar(mapa, enemigos, caminando) {
+            part.rafaga(Efecto::Explosion, ex, ey, 0.5, 24);
+        }
+        prog.enemigos_derrotados += enemigos.quitar_muertos();
+
+        // cajas de munición
+        let cajas = spr.recolectar_en(jug.x, jug.y, SpriteKind::Ammo);
+        if cajas > 0 { armas.recoger_municion(cajas); }
+
+        // botiquines (solo si falta vida)
+        if jug.salud.vida < jug.salud.vida_max
+            && spr.recolectar_en(jug.x, jug.y, SpriteKind::Health) > 0
+        {
+            jug.salud.curar(BOTIQUIN);
+        }
+
+        // ¿recogió moneda(s) en la celda?
+        let recogidas = spr.recolectar_monedas_en(jug.x, jug.y);
+        if recogidas > 0 {
+            prog.monedas += recogidas;
+            part.rafaga(Efecto::Brillo, jug.x.floor() + 0.5, jug.y.floor() + 0.5, 0.4, 12);
+        }
+
+        // disparadores en los que acaba de entrar
+        let fin = self.disparar();
+        let Partida { mapa, jug, prog, .. } = self;
+
+        // salida: solo si se cumplen las reglas del nivel
+        let mut res = Resultado::Sigue;
+        if celda(mapa, jug.x as i32, jug.y as i32) == Some('E') {
+            let faltan = prog.pendientes(reglas);
+            res = if faltan.is_empty() { Resultado::Gano } else { Resultado::Falta(faltan) };
+        }
+        if reglas.iter().any(|r| matches!(r, Regla::TiempoLimite(_)) && !r.cumplida(prog)) {
+            res = Resultado::Perdio("muerte.tiempo");
+        }
+        if let Some(r) = fin {
+            res = r; // 'ganar' y 'perder' no miran las reglas
+        }
+        if jug.salud.muerto() {
+            res = Resultado::Perdio(jug.salud.ultima_causa.map(|c| c.mensaje()).unwrap_or("muerte.sin_vida"));
+        }
+        res
+    }
+
+    /// Ejecuta las acciones de los disparadores en los que entró el jugador.
+    /// Devuelve el final del nivel si alguna lo pide.
+    fn disparar(&mut self) -> Option<Resultado> {
+        let (px, py) = (self.jug.x as i32, self.jug.y as i32);
+        let mut fin = None;
+        let (mapa, bloques) = (&self.mapa, &self.bloques);
+        let ordenes = self.eventos.revisar(px, py, |x, y, w, h| bloques.apretadas(mapa, x, y, w, h));
+        for (zona, o) in ordenes {
+            let Some(o) = self.eventos.resolver(zona, o) else { continue };
+            match o {
+                Orden::Abrir(x, y) if celda(&self.mapa, x, y) == Some('D') => self.mapa[y as usize][x as usize] = ' ',
+                // no se cierra sobre el jugador
+                Orden::Cerrar(x, y) if celda(&self.mapa, x, y) == Some(' ') && (x, y) != (px, py) => {
+                    self.mapa[y as usize][x as usize] = 'D';
+                }
+                Orden::Enemigo(x, y) if celda(&self.mapa, x, y).is_some_and(es_caminable) => {
+                    self.enemigos.lista.push(Enemigo::nuevo(x as f32 + 0.5, y as f32 + 0.5));
+                    self.prog.enemigos_total += 1;
+                }
+                Orden::Teletransportar(x, y, ang) if celda(&self.mapa, x, y).is_some_and(es_caminable) => {
+                    self.jug.x = x as f32 + 0.5;
+                    self.jug.y = y as f32 + 0.5;
+                    if let Some(a) = ang { self.jug.ang = a; }
+                    self.seguro = (self.jug.x, self.jug.y);
+                    self.tele.llegar(x, y);
+                }
+                Orden::Ganar => fin = fin.or(Some(Resultado::Gano)),
+                Orden::Perder => fin = fin.or(Some(Resultado::Perdio("muerte.evento"))),
+                _ => {} // celda equivocada: la acción no hace nada
+            }
+        }
+        fin
+    }
+
+    /// Dibuja escena, sprites (más los `extra`, como el fantasma), partículas,
+    /// arma, minimapa y vida. También avanza animaciones y partículas, que no
+    /// afectan la simulación.
+    pub fn dibujar(&mut self, fb: &mut Framebuffer, tex: &mut Texturas, part: &mut Particulas, extra: &[Sprite]) {
+        let Partida { mapa, jug, spr, enemigos, armas, tele, bloques, .. } = self;
+
+        // raycasting paredes + zbuffer; los bloques que se deslizan van
+        // aparte y su celda de llegada se ve vacía hasta que llegan
+        bloques.avanzar();
+        let cajas = bloques.cajas();
+        let mut vista = None;
+        if !cajas.is_empty() {
+            let mut m = mapa.clone();
+            for (x, y) in bloques.en_camino() {
+                if m[y as usize][x as usize] == 'X' { m[y as usize][x as usize] = ' '; }
+            }
+            vista = Some(m);
+        }
+        let mut zbuf = vec![f32::INFINITY; fb.w as usize];
+        dibujar_escena(fb, jug, vista.as_ref().unwrap_or(mapa), &cajas, tex, &mut zbuf, armas.destello().max(tele.destello()));
+
+        // sprites (antorcha/monedas/pozos + enemigos y cohetes)
+        spr.actualizar();
+        let mut moviles = enemigos.sprites();
+        moviles.extend(armas.sprites());
+        moviles.extend_from_slice(extra);
+        dibujar_sprites(fb, jug, mapa, spr, &moviles, &zbuf);
+
+        // partículas (mismo zbuffer)
+        part.actualizar(spr);
+        part.dibujar(fb, jug, &zbuf);
+
+        // arma en primera persona
+        armas.dibujar(fb);
+
+        // minimapa
+        dibujar_minimapa(fb, jug, mapa);
+        tele.dibujar_en_minimapa(fb);
+        enemigos.dibujar_en_minimapa(fb);
+
+        // barra de vida + destello de daño
+        dibujar_vida(fb, &jug.salud);
+    }
+
+    /// Resumen del estado completo, para comprobar que una repetición
+    /// terminó igual que la partida grabada. Es FNV-1a de 64 bits escrito acá
+    /// (no el hasher de la biblioteca, que puede cambiar entre versiones) para
+    /// que las repeticiones guardadas sigan sirviendo.
+    pub fn huella(&self) -> u64 {
+        self.serializar("").bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    /// Escribe a un temporal y renombra, igual que el guardado de progreso.
+    pub fn guardar(&self, ruta: &Path, niveles: &[Nivel]) -> std::io::Result<()> {
+        if let Some(dir) = ruta.parent() { fs::create_dir_all(dir)?; }
+        let tmp = ruta.with_extension("tmp");
+        fs::write(&tmp, self.serializar(&niveles[self.nivel].id))?;
+        fs::rename(&tmp, ruta)
+    }
+
+    /// Un nivel generado con otra semilla se rehace en `niveles` (ver `buscar_o_generar`).
+    pub fn cargar(ruta: &Path, niveles: &mut [Nivel]) -> Result<Self, String> {
+        let txt = fs::read_to_string(ruta).map_err(|e| e.to_string())?;
+        parsear(&txt, niveles)
+    }
+
+    fn serializar(&self, id: &str) -> String {
+        let j = &self.jug;
+        let s = &j.salud;
+        let p = &self.prog;
+        let causa = s.ultima_causa.map_or("-".to_string(), |c| format!("{c:?}"));
+
+        let mut t = format!("{CABECERA} {VERSION}\n");
+        t += &format!("nivel {id}\n");
+        t += &format!("semilla {}\n", self.semilla);
+        for fila in &self.mapa {
+            // entre barras para no perder los espacios del final
+            t += &format!("fila |{}|\n", fila.iter().collect::<String>());
+        }
+        t += &format!("jugador {} {} {}\n", j.x, j.y, j.ang);
+        t += &format!("salud {} {} {} {causa}\n", s.vida, s.vida_max, s.invuln);
+        t += &format!("seguro {} {}\n", self.seguro.0, self.seguro.1);
+        t += &format!("progreso {} {} {} {} {} {}\n",
+                      p.monedas, p.monedas_total, p.enemigos_derrotados, p.enemigos_total, p.frames, p.muertes);
+        t += &format!("armas {}\n", self.armas.a_texto());
+        for pr in &self.armas.proyectiles {
+            t += &format!("proyectil {} {} {} {} {}\n", pr.x, pr.y, pr.dx, pr.dy, pr.vida);
+        }
+        for sp in &self.spr.lista {
+            t += &format!("sprite {} {} {}\n", sp.kind.simbolo(), sp.x, sp.y);
+        }
+        for e in &self.enemigos.lista {
+            t += &format!("enemigo {}\n", e.a_texto());
+        }
+        for (i, z) in self.eventos.zonas.iter().enumerate() {
+            t += &format!("zona {i} {} {}\n", z.dentro as u8, z.activa as u8);
+        }
+        for (v, n) in &self.eventos.vars {
+            t += &format!("variable {v} {n}\n");
+        }
+        let llegada = self.tele.llegada.map_or("-".to_string(), |(x, y)| format!("{x} {y}"));
+        t += &format!("teletransporte {} {llegada}\n", self.tele.espera);
+        // las placas no se ven en el mapa si tienen un bloque encima, y el
+        // archivo del nivel puede haber cambiado desde que se guardó
+        t += "placas";
+        for (x, y) in self.bloques.placas() {
+            t += &format!(" {x} {y}");
+        }
+        t + "\n"
+    }
+}
+
+fn parsear(txt: &str, niveles: &mut [Nivel]) -> Result<Partida, String> {
+    let mut lineas = txt.lines().enumerate();
+    let (_, cab) = lineas.next().ok_or("archivo vacío")?;
+    let version: u32 = cab.strip_prefix(CABECERA)
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or("cabecera inválida")?;
+    if version > VERSION {
+        return Err(format!("versión {version} más nueva que la del juego ({VERSION})"));
+    }
+
+    let mut nivel = None;
+    let mut semilla = 0;
+    let mut mapa: Mapa = Vec::new();
+    let mut jug = None;
+    let mut salud = None;
+    let mut seguro = None;
+    let mut prog = None;
+    let mut armas: Option<Armas> = None;
+    let mut proyectiles = Vec::new();
+    let mut spr = Sprites::nuevo();
+    let mut enemigos = Enemigos::nuevo();
+    let mut zonas = Vec::new();
+    let mut vars = Vec::new();
+    let mut salto = None;
+    let mut placas = None;
+
+    for (i, l) in lineas {
+        if l.trim().is_empty() { continue; }
+        let (clave, resto) = l.split_once(' ').unwrap_or((l, ""));
+        let err = || format!("línea {}: '{}'", i + 1, l.trim());
+        let c: Vec<&str> = resto.split_whitespace().collect();
+        let f = |k: usize| c.get(k).and_then(|t| t.parse::<f32>().ok()).ok_or_else(err);
+        let n = |k: usize| c.get(k).and_then(|t| t.parse::<u32>().ok()).ok_or_else(err);
+        let e = |k: usize| c.get(k).and_then(|t| t.parse::<i32>().ok()).ok_or_else(err);
+
+        match clave {
+            "nivel" => {
+                let idx = buscar_o_generar(niveles, resto.trim())
+                    .ok_or_else(|| format!("nivel desconocido '{}'", resto.trim()))?;
+                nivel = Some(idx);
+            }
+            "semilla" => semilla = resto.trim().parse().map_err(|_| err())?,
+            "fila" => {
+                let fila = resto.strip_prefix('|').and_then(|r| r.strip_suffix('|')).ok_or_else(err)?;
+                mapa.push(fila.chars().collect());
+            }
+            "jugador" => jug = Some((f(0)?, f(1)?, f(2)?)),
+            "salud" => {
+                let causa = match c.get(3) {
+                    Some(&"-") => None,
+                    Some(t) => Some(*CausaDanio::TODAS.iter().find(|k| format!("{k:?}") == *t).ok_or_else(err)?),
+                    None => return Err(err()),
+                };
+                salud = Some(Salud { vida: e(0)?, vida_max: e(1)?, invuln: n(2)?, ultima_causa: causa });
+            }
+            "seguro" => seguro = Some((f(0)?, f(1)?)),
+            "progreso" => prog = Some(Progreso {
+                monedas: n(0)? as usize,
+                monedas_total: n(1)? as usize,
+                enemigos_derrotados: n(2)? as usize,
+                enemigos_total: n(3)? as usize,
+                frames: n(4)?,
+                muertes: n(5)?,
+            }),
+            "armas" => armas = Some(Armas::desde_texto(resto).ok_or_else(err)?),
+            "proyectil" => proyectiles.push(Proyectil { x: f(0)?, y: f(1)?, dx: f(2)?, dy: f(3)?, vida: n(4)? }),
+            "sprite" => {
+                let kind = c.first().and_then(|t| t.chars().next()).and_then(SpriteKind::desde_simbolo).ok_or_else(err)?;
+                spr.lista.push(Sprite { x: f(1)?, y: f(2)?, kind });
+            }
+            "enemigo" => enemigos.lista.push(Enemigo::desde_texto(resto).ok_or_else(err)?),
+            "zona" => zonas.push((n(0)? as usize, n(1)? != 0, n(2)? != 0)),
+            "variable" => vars.push((c.first().ok_or_else(err)?.to_string(), e(1)?)),
+            "teletransporte" => salto = Some((n(0)?, if c.get(1) == Some(&"-") { None } else { Some((e(1)?, e(2)?)) })),
+            "placas" => placas = Some(c.chunks(2)
+                .map(|par| match par {
+                    [x, y] => Some((x.parse().ok()?, y.parse().ok()?)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(err)?),
+            _ => return Err(err()),
+        }
+    }
+
+    let nivel = nivel.ok_or("falta 'nivel'")?;
+    if mapa.is_empty() || mapa.iter().any(|f| f.len() != mapa[0].len()) {
+        return Err("mapa vacío o con filas de distinto largo".to_string());
+    }
+    let (x, y, ang) = jug.ok_or("falta 'jugador'")?;
+    let mut jug = jugador_en(x, y, ang);
+    jug.salud = salud.ok_or("falta 'salud'")?;
+    let mut armas = armas.ok_or("falta 'armas'")?;
+    armas.proyectiles = proyectiles;
+    let mut eventos = Eventos::nuevo(&niveles[nivel].disparadores);
+    for (i, dentro, activa) in zonas {
+        let z = eventos.zonas.get_mut(i).ok_or_else(|| format!("zona {i} no existe en el nivel"))?;
+        z.dentro = dentro;
+        z.activa = activa;
+    }
+    eventos.vars.extend(vars);
+    // sin la línea (partidas de antes) se arranca como en el nivel nuevo
+    let mut tele = Teletransportes::nuevo(&mapa, &niveles[nivel].teletransportes, jug.x as i32, jug.y as i32);
+    if let Some((espera, llegada)) = salto {
+        tele.espera = espera;
+        tele.llegada = llegada;
+    }
+
+    // sin la línea (partidas de antes) salen del nivel, como al empezarlo
+    let bloques = match placas {
+        Some(p) => Bloques::con_placas(p),
+        None => Bloques::nuevo(&niveles[nivel].mapa),
+    };
+
+    Ok(Partida {
+        nivel,
+        semilla,
+        mapa,
+        jug,
+        seguro: seguro.ok_or("falta 'seguro'")?,
+        spr,
+        enemigos,
+        armas,
+        eventos,
+        tele,
+        bloques,
+        prog: prog.ok_or("falta 'progreso'")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapas::{Cuando, Disparador};
+
+    fn nivel() -> Nivel {
+        let filas = ["1111111111", "1   X_  E1", "1 C   T T1", "1  M     1", "1111111111"];
+        Nivel {
+            id: "prueba".to_string(),
+            titulo: None,
+            generado: None,
+            mapa: filas.iter().map(|f| f.chars().collect()).collect(),
+            inicio: (1, 1, 0.0),
+            reglas: Vec::new(),
+            disparadores: vec![Disparador { x: 5, y: 1, w: 1, h: 1, cuando: Cuando::Apretar, acciones: "sumar n 1".to_string() }],
+            teletransportes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn guardar_y_cargar() {
+        let mut niveles = vec![nivel()];
+        let mut p = Partida::nueva(&niveles, 0, 2, 99);
+        let mut part = Particulas::nuevo();
+        // hacia la derecha: empuja el bloque a la placa, con un disparo de paso
+        for k in 0..90 {
+            p.actualizar(&Entrada::nueva(0.0, 1.0, 0.0, k == 10, None), &[], &mut part);
+        }
+        assert_eq!(p.mapa[1][5], 'X');
+        assert_eq!(p.eventos.vars.get("n"), Some(&1));
+
+        // el nivel cambió en el disco: la placa ya no está en su archivo
+        niveles[0].mapa[1][5] = ' ';
+        let q = parsear(&p.serializar("prueba"), &mut niveles).unwrap();
+        assert_eq!(q.huella(), p.huella());
+        assert!(q.bloques.apretadas(&q.mapa, 5, 1, 1, 1));
+    }
+}
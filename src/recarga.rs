@@ -0,0 +1,49 @@
+//! Recarga en caliente: cada tanto se mira la fecha de modificación de los
+//! archivos de niveles y del atlas de texturas y se avisa cuáles cambiaron
+//! (aparecieron, se modificaron o se borraron). Es solo sondeo del disco.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+const CADA: u32 = 30; // frames entre revisiones
+
+pub struct Vigilante {
+    vistos: HashMap<PathBuf, SystemTime>,
+    espera: u32,
+}
+
+impl Vigilante {
+    /// Arranca con el estado actual de `rutas` (lo que ya está no cuenta como cambio)
+    pub fn nuevo(rutas: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self { vistos: fechas(rutas), espera: CADA }
+    }
+
+    /// Llamar una vez por frame; cada `CADA` frames arma la lista con
+    /// `rutas` (que puede leer la carpeta: solo se llama entonces), la
+    /// compara con lo visto la vez anterior y devuelve las que cambiaron.
+    pub fn revisar<I: IntoIterator<Item = PathBuf>>(&mut self, rutas: impl FnOnce() -> I) -> Vec<PathBuf> {
+        if self.espera > 0 {
+            self.espera -= 1;
+            return Vec::new();
+        }
+        self.espera = CADA;
+        let ahora = fechas(rutas());
+        let mut cambiadas: Vec<PathBuf> = ahora.keys().chain(self.vistos.keys())
+            .filter(|r| ahora.get(*r) != self.vistos.get(*r))
+            .cloned()
+            .collect();
+        cambiadas.sort();
+        cambiadas.dedup();
+        self.vistos = ahora;
+        cambiadas
+    }
+}
+
+/// Fecha de modificación de las rutas que existen
+fn fechas(rutas: impl IntoIterator<Item = PathBuf>) -> HashMap<PathBuf, SystemTime> {
+    rutas.into_iter()
+        .filter_map(|r| fs::metadata(&r).and_then(|m| m.modified()).ok().map(|t| (r, t)))
+        .collect()
+}
@@ -0,0 +1,154 @@
+//! Reglas para completar un nivel, progreso de la partida y puntaje.
+//! Llegar a 'E' solo termina el nivel si se cumplen todas sus reglas;
+//! el límite de tiempo, en cambio, hace perder al agotarse.
+
+use crate::idiomas::Idioma;
+
+const FPS: u32 = 60;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Regla {
+    TodasLasMonedas,
+    MinimoMonedas(usize),
+    TiempoLimite(u32), // segundos
+    SinEnemigos,       // derrotar a todos
+}
+
+impl Regla {
+    /// Texto corto para el menú/HUD
+    pub fn descripcion(&self, l: Idioma) -> String {
+        match self {
+            Regla::TodasLasMonedas => l.t("regla.todas").to_string(),
+            Regla::MinimoMonedas(n) => l.f("regla.minimo", &[n]),
+            Regla::TiempoLimite(s) => l.f("regla.tiempo", &[&formato_tiempo(s * FPS)]),
+            Regla::SinEnemigos => l.t("regla.enemigos").to_string(),
+        }
+    }
+
+    /// ¿Se cumple con el progreso actual? (el tiempo se cumple mientras no se agote)
+    pub fn cumplida(&self, p: &Progreso) -> bool {
+        match *self {
+            Regla::TodasLasMonedas => p.monedas >= p.monedas_total,
+            Regla::MinimoMonedas(n) => p.monedas >= n,
+            Regla::TiempoLimite(s) => p.frames <= s * FPS,
+            Regla::SinEnemigos => p.enemigos_derrotados >= p.enemigos_total,
+        }
+    }
+}
+
+/// Lo que lleva hecho el jugador en el nivel actual
+#[derive(Clone, Copy, Default)]
+pub struct Progreso {
+    pub monedas: usize,
+    pub monedas_total: usize,
+    pub enemigos_derrotados: usize,
+    pub enemigos_total: usize,
+    pub frames: u32,
+    pub muertes: u32, // intentos fallidos antes de este
+}
+
+impl Progreso {
+    pub fn nuevo(monedas_total: usize, enemigos_total: usize, muertes: u32) -> Self {
+        Self { monedas_total, enemigos_total, muertes, ..Default::default() }
+    }
+
+    /// Reglas que todavía faltan para poder salir
+    pub fn pendientes(&self, reglas: &[Regla]) -> Vec<Regla> {
+        reglas.iter().copied().filter(|r| !r.cumplida(self)).collect()
+    }
+
+    /// Segundos restantes si el nivel tiene límite de tiempo
+    pub fn tiempo_restante(&self, reglas: &[Regla]) -> Option<u32> {
+        reglas.iter().find_map(|r| match r {
+            Regla::TiempoLimite(s) => Some((s * FPS).saturating_sub(self.frames) / FPS),
+            _ => None,
+        })
+    }
+}
+
+/// m:ss a partir de frames
+pub fn formato_tiempo(frames: u32) -> String {
+    let s = frames / FPS;
+    format!("{}:{:02}", s / 60, s % 60)
+}
+
+/// ±s.d de diferencia entre dos tiempos (parciales contra el fantasma)
+pub fn formato_diferencia(frames: i64) -> String {
+    let signo = if frames < 0 { '-' } else { '+' };
+    let d = frames.unsigned_abs() * 10 / FPS as u64;
+    format!("{signo}{}.{}", d / 10, d % 10)
+}
+
+/// Desglose del puntaje para la pantalla de resultados
+#[derive(Clone, Copy)]
+pub struct Desglose {
+    pub por_monedas: i32,
+    pub bonus_todas: i32,
+    pub por_tiempo: i32,
+    pub por_enemigos: i32,
+    pub por_muertes: i32, // negativo
+    pub total: i32,
+}
+
+/// Puntaje = 100 por moneda (+500 si están todas) + bonus por tiempo
+/// (3000 menos 10 por segundo) + 150 por enemigo − 250 por muerte.
+pub fn puntaje(p: &Progreso) -> Desglose {
+    let por_monedas = 100 * p.monedas as i32;
+    let bonus_todas = if p.monedas >= p.monedas_total && p.monedas_total > 0 { 500 } else { 0 };
+    let por_tiempo = (3000 - 10 * (p.frames / FPS) as i32).max(0);
+    let por_enemigos = 150 * p.enemigos_derrotados as i32;
+    let por_muertes = -250 * p.muertes as i32;
+    let total = (por_monedas + bonus_todas + por_tiempo + por_enemigos + por_muertes).max(0);
+    Desglose { por_monedas, bonus_todas, por_tiempo, por_enemigos, por_muertes, total }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reglas_cumplidas() {
+        let mut p = Progreso::nuevo(5, 2, 0);
+        let reglas = [Regla::TodasLasMonedas, Regla::MinimoMonedas(3), Regla::TiempoLimite(10), Regla::SinEnemigos];
+        assert_eq!(p.pendientes(&reglas), [Regla::TodasLasMonedas, Regla::MinimoMonedas(3), Regla::SinEnemigos]);
+
+        p.monedas = 3;
+        p.enemigos_derrotados = 2;
+        p.frames = 10 * FPS;
+        assert_eq!(p.pendientes(&reglas), [Regla::TodasLasMonedas]);
+        assert_eq!(p.tiempo_restante(&reglas), Some(0));
+        p.frames += 1;
+        assert_eq!(p.pendientes(&reglas), [Regla::TodasLasMonedas, Regla::TiempoLimite(10)]);
+        assert_eq!(p.tiempo_restante(&reglas), Some(0));
+
+        p.frames = 3 * FPS + 1;
+        assert_eq!(p.tiempo_restante(&reglas), Some(6));
+        assert_eq!(p.tiempo_restante(&[Regla::SinEnemigos]), None);
+    }
+
+    #[test]
+    fn puntaje_desglosado() {
+        let p = Progreso { monedas: 4, monedas_total: 4, enemigos_derrotados: 3, enemigos_total: 5, frames: 65 * FPS + 30, muertes: 1 };
+        let d = puntaje(&p);
+        assert_eq!((d.por_monedas, d.bonus_todas, d.por_tiempo, d.por_enemigos, d.por_muertes), (400, 500, 2350, 450, -250));
+        assert_eq!(d.total, 3450);
+
+        // sin monedas en el nivel no hay bonus, y el total no baja de cero
+        let p = Progreso { frames: 400 * FPS, muertes: 3, ..Default::default() };
+        let d = puntaje(&p);
+        assert_eq!((d.bonus_todas, d.por_tiempo, d.por_muertes, d.total), (0, 0, -750, 0));
+    }
+
+    #[test]
+    fn formatos() {
+        assert_eq!(formato_tiempo(0), "0:00");
+        assert_eq!(formato_tiempo(59 * FPS + 59), "0:59");
+        assert_eq!(formato_tiempo(61 * FPS), "1:01");
+        assert_eq!(formato_tiempo(600 * FPS), "10:00");
+
+        assert_eq!(formato_diferencia(0), "+0.0");
+        assert_eq!(formato_diferencia(90), "+1.5");
+        assert_eq!(formato_diferencia(-6), "-0.1");
+        assert_eq!(formato_diferencia(-125 * FPS as i64), "-125.0");
+    }
+}
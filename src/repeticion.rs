@@ -0,0 +1,190 @@
+//! Grabación y repetición de partidas. Se graba la `Entrada` de cada frame
+//! junto con el id del nivel, la semilla y las muertes previas (afectan el
+//! puntaje); al repetir se pasan por la misma `Partida::actualizar`, así que
+//! la corrida sale idéntica. Al final se compara la `huella` del estado.
+//!
+//! Formato binario (little endian): "RCREP", versión u8, largo u8 + id,
+//! semilla u64, muertes u32, huella u64, frames u32 y luego tramos de
+//! entradas iguales: repeticiones u16, flags u8, mov_x i8, mov_y i8, giro f32.
+
+use crate::armas::TipoArma;
+use crate::guardado::directorio_datos;
+use crate::partida::Entrada;
+use std::cell::Cell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MAGIA: &[u8] = b"RCREP";
+const VERSION: u8 = 1;
+
+// flags de cada tramo
+const F_DISPARO: u8 = 1;
+const F_PISTOLA: u8 = 2;
+const F_LANZADOR: u8 = 4;
+
+pub struct Grabacion {
+    pub nivel: String,
+    pub semilla: u64,
+    pub muertes: u32,
+    pub huella: u64, // `Partida::huella` al terminar (0 = no terminó)
+    pub entradas: Vec<Entrada>,
+}
+
+/// Archivo donde queda la última partida jugada
+pub fn ruta_ultima_repeticion() -> PathBuf {
+    directorio_datos().join("ultima.rep")
+}
+
+impl Grabacion {
+    pub fn nueva(nivel: &str, semilla: u64, muertes: u32) -> Self {
+        Self { nivel: nivel.to_string(), semilla, muertes, huella: 0, entradas: Vec::new() }
+    }
+
+    pub fn guardar(&self, ruta: &Path) -> std::io::Result<()> {
+        if let Some(dir) = ruta.parent() { fs::create_dir_all(dir)?; }
+        fs::write(ruta, self.a_bytes())
+    }
+
+    pub fn cargar(ruta: &Path) -> Result<Self, String> {
+        let datos = fs::read(ruta).map_err(|e| e.to_string())?;
+        Self::desde_bytes(&datos)
+    }
+
+    fn a_bytes(&self) -> Vec<u8> {
+        let mut b = MAGIA.to_vec();
+        b.push(VERSION);
+        // hasta 255 bytes, sin partir un carácter
+        let corte = (0..=self.nivel.len().min(255)).rev().find(|&i| self.nivel.is_char_boundary(i)).unwrap_or(0);
+        let id = &self.nivel.as_bytes()[..corte];
+        b.push(id.len() as u8);
+        b.extend_from_slice(id);
+        b.extend_from_slice(&self.semilla.to_le_bytes());
+        b.extend_from_slice(&self.muertes.to_le_bytes());
+        b.extend_from_slice(&self.huella.to_le_bytes());
+        b.extend_from_slice(&(self.entradas.len() as u32).to_le_bytes());
+
+        let mut i = 0;
+        while i < self.entradas.len() {
+            let e = self.entradas[i];
+            let mut n = 1;
+            while n < u16::MAX as usize && i + n < self.entradas.len() && self.entradas[i + n] == e {
+                n += 1;
+            }
+            let flags = if e.disparo { F_DISPARO } else { 0 }
+                | match e.arma {
+                    Some(TipoArma::Pistola) => F_PISTOLA,
+                    Some(TipoArma::Lanzador) => F_LANZADOR,
+                    None => 0,
+                };
+            b.extend_from_slice(&(n as u16).to_le_bytes());
+            b.push(flags);
+            b.push(e.mov_x as u8);
+            b.push(e.mov_y as u8);
+            b.extend_from_slice(&e.giro.to_le_bytes());
+            i += n;
+        }
+        b
+    }
+
+    fn desde_bytes(datos: &[u8]) -> Result<Self, String> {
+        let pos = Cell::new(0);
+        let tomar = |n: usize| -> Result<&[u8], String> {
+            let t = datos.get(pos.get()..pos.get() + n).ok_or("archivo cortado")?;
+            pos.set(pos.get() + n);
+            Ok(t)
+        };
+        if tomar(MAGIA.len())? != MAGIA { return Err("no es una repetición".to_string()); }
+        let version = tomar(1)?[0];
+        if version > VERSION {
+            return Err(format!("versión {version} más nueva que la del juego ({VERSION})"));
+        }
+        let largo = tomar(1)?[0] as usize;
+        let nivel = String::from_utf8(tomar(largo)?.to_vec()).map_err(|_| "id de nivel inválido")?;
+        let semilla = u64::from_le_bytes(tomar(8)?.try_into().unwrap());
+        let muertes = u32::from_le_bytes(tomar(4)?.try_into().unwrap());
+        let huella = u64::from_le_bytes(tomar(8)?.try_into().unwrap());
+        let frames = u32::from_le_bytes(tomar(4)?.try_into().unwrap()) as usize;
+        // cada tramo de 9 bytes trae a lo sumo u16::MAX frames: un archivo
+        // roto no puede pedir más memoria que eso
+        if frames > (datos.len() - pos.get()) / 9 * u16::MAX as usize {
+            return Err("archivo cortado".to_string());
+        }
+
+        let mut entradas = Vec::new();
+        while entradas.len() < frames {
+            let t = tomar(9)?;
+            let n = u16::from_le_bytes([t[0], t[1]]) as usize;
+            let arma = match t[2] & (F_PISTOLA | F_LANZADOR) {
+                0 => None,
+                F_PISTOLA => Some(TipoArma::Pistola),
+                F_LANZADOR => Some(TipoArma::Lanzador),
+                _ => return Err("tramo con dos armas".to_string()),
+            };
+            let e = Entrada {
+                mov_x: t[3] as i8,
+                mov_y: t[4] as i8,
+                giro: f32::from_le_bytes([t[5], t[6], t[7], t[8]]),
+                disparo: t[2] & F_DISPARO != 0,
+                arma,
+            };
+            if n == 0 || entradas.len() + n > frames { return Err("tramo inválido".to_string()); }
+            entradas.extend(std::iter::repeat_n(e, n));
+        }
+        Ok(Self { nivel, semilla, muertes, huella, entradas })
+    }
+}
+
+/// Repetición en curso: posición, pausa y cómo terminó
+pub struct Reproductor {
+    pub grab: Grabacion,
+    pub pos: usize,
+    pub pausa: bool,
+    pub fin: Option<(&'static str, bool)>, // clave del resultado y si coincidió la huella
+}
+
+impl Reproductor {
+    pub fn nuevo(grab: Grabacion) -> Self {
+        Self { grab, pos: 0, pausa: false, fin: None }
+    }
+
+    /// Próxima entrada a simular, si queda alguna
+    pub fn siguiente(&mut self) -> Option<Entrada> {
+        let e = self.grab.entradas.get(self.pos).copied()?;
+        self.pos += 1;
+        Some(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn id_largo_se_corta_entero() {
+        // 'ñ' ocupa dos bytes: los 255 caerían en la mitad de una
+        let id = "x".repeat(200) + &"ñ".repeat(60);
+        let g = Grabacion::desde_bytes(&Grabacion::nueva(&id, 7, 3).a_bytes()).unwrap();
+        assert_eq!(g.nivel, "x".repeat(200) + &"ñ".repeat(27));
+        assert_eq!((g.semilla, g.muertes), (7, 3));
+    }
+
+    #[test]
+    fn cabecera_rota() {
+        let mut g = Grabacion::nueva("laberinto-1", 1, 0);
+        g.entradas = vec![Entrada::default(); 10];
+        let b = g.a_bytes();
+        assert_eq!(Grabacion::desde_bytes(&b).unwrap().entradas.len(), 10);
+
+        // frames en u32::MAX sin los tramos que harían falta
+        let fin = b.len() - 9 - 4;
+        let mut rota = b.clone();
+        rota[fin..fin + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(Grabacion::desde_bytes(&rota).is_err());
+        // cortado a la mitad de un tramo, y con un tramo de más frames que el total
+        assert!(Grabacion::desde_bytes(&b[..b.len() - 3]).is_err());
+        let mut rota = b.clone();
+        rota[fin..fin + 4].copy_from_slice(&5u32.to_le_bytes());
+        assert!(Grabacion::desde_bytes(&rota).is_err());
+        assert!(Grabacion::desde_bytes(b"RCREP").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Vida del jugador: daño con causa, frames de invulnerabilidad y curación.
+//! Peligros del mapa: 'P' pozo, 'S' pinchos, 'L' lava, 'V' veneno.
+
+use crate::motor::Framebuffer;
+use raylib::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CausaDanio {
+    Pozo,
+    Enemigo,
+    Pinchos,
+    Lava,
+    Veneno,
+}
+
+impl CausaDanio {
+    pub const TODAS: [CausaDanio; 5] =
+        [CausaDanio::Pozo, CausaDanio::Enemigo, CausaDanio::Pinchos, CausaDanio::Lava, CausaDanio::Veneno];
+
+    /// Clave del texto para la pantalla de derrota (ver `idiomas`)
+    pub fn mensaje(&self) -> &'static str {
+        match self {
+            CausaDanio::Pozo => "muerte.pozo",
+            CausaDanio::Enemigo => "muerte.enemigo",
+            CausaDanio::Pinchos => "muerte.pinchos",
+            CausaDanio::Lava => "muerte.lava",
+            CausaDanio::Veneno => "muerte.veneno",
+        }
+    }
+}
+
+/// Daño y frames de invulnerabilidad que deja cada golpe, por causa.
+pub fn danio_de(causa: CausaDanio) -> (i32, u32) {
+    match causa {
+        CausaDanio::Pozo => (40, 60),
+        CausaDanio::Enemigo => (25, 45),
+        CausaDanio::Pinchos => (15, 45),
+        CausaDanio::Lava => (10, 20),
+        CausaDanio::Veneno => (3, 20),
+    }
+}
+
+/// Peligro asociado a una celda del mapa (si lo hay)
+pub fn peligro_de_celda(c: char) -> Option<CausaDanio> {
+    match c {
+        'P' => Some(CausaDanio::Pozo),
+        'S' => Some(CausaDanio::Pinchos),
+        'L' => Some(CausaDanio::Lava),
+        'V' => Some(CausaDanio::Veneno),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Salud {
+    pub vida: i32,
+    pub vida_max: i32,
+    pub invuln: u32,                    // frames restantes sin recibir daño
+    pub ultima_causa: Option<CausaDanio>,
+}
+
+impl Salud {
+    pub fn nueva(vida_max: i32) -> Self {
+        Self { vida: vida_max, vida_max, invuln: 0, ultima_causa: None }
+    }
+
+    /// Aplica el daño de `causa` salvo que esté en frames de invulnerabilidad.
+    /// Devuelve true si el golpe entró.
+    pub fn danio(&mut self, causa: CausaDanio) -> bool {
+        if self.invuln > 0 || self.muerto() { return false; }
+        let (cant, frames) = danio_de(causa);
+        self.vida = (self.vida - cant).max(0);
+        self.invuln = frames;
+        self.ultima_causa = Some(causa);
+        true
+    }
+
+    /// Cura hasta `vida_max`. Devuelve false si ya estaba llena.
+    pub fn curar(&mut self, cant: i32) -> bool {
+        if self.vida >= self.vida_max { return false; }
+        self.vida = (self.vida + cant).min(self.vida_max);
+        true
+    }
+
+    pub fn actualizar(&mut self) {
+        self.invuln = self.invuln.saturating_sub(1);
+    }
+
+    pub fn muerto(&self) -> bool {
+        self.vida <= 0
+    }
+}
+
+/// Barra de vida abajo a la izquierda y borde rojo parpadeante tras un golpe.
+pub fn dibujar_vida(fb: &mut Framebuffer, s: &Salud) {
+    let (w, h) = (fb.w as i32, fb.h as i32);
+    let ancho = 60;
+    let lleno = ancho * s.vida.max(0) / s.vida_max.max(1);
+    for y in h - 10..h - 5 {
+        for x in 0..ancho {
+            let c = if x < lleno { Color::RED } else { Color::DARKGRAY };
+            fb.set(6 + x, y, c);
+        }
+    }
+    if s.invuln > 0 && (s.invuln / 4).is_multiple_of(2) {
+        for x in 0..w {
+            fb.set(x, 0, Color::RED);
+            fb.set(x, h - 1, Color::RED);
+        }
+        for y in 0..h {
+            fb.set(0, y, Color::RED);
+            fb.set(w - 1, y, Color::RED);
+        }
+    }
+}
@@ -1,10 +1,42 @@
-//! Sprites: Antorcha (A), Moneda (C), Pozo (P)
+//! Sprites: Antorcha (A), Moneda (C), Pozo (P), Enemigo (M, se mueve desde `enemigos`),
+//! Pinchos (S), Lava (L), Veneno (V), Botiquín (H), Munición (B), Proyectiles (desde `armas`)
+//! y el Fantasma del mejor recorrido (translúcido, desde `fantasma`)
 
 use crate::motor::*;
 use raylib::prelude::*;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub enum SpriteKind { Torch, Coin, Pit }
+pub enum SpriteKind { Torch, Coin, Pit, Enemy, Spikes, Lava, Poison, Health, Ammo, Projectile, Ghost, Plate }
+
+impl SpriteKind {
+    const TODOS: [SpriteKind; 12] = [
+        SpriteKind::Torch, SpriteKind::Coin, SpriteKind::Pit, SpriteKind::Enemy, SpriteKind::Spikes,
+        SpriteKind::Lava, SpriteKind::Poison, SpriteKind::Health, SpriteKind::Ammo, SpriteKind::Projectile,
+        SpriteKind::Ghost, SpriteKind::Plate,
+    ];
+
+    /// Símbolo del mapa de cada tipo ('*' y '@' para cohetes y fantasma, que no están en el mapa)
+    pub fn simbolo(self) -> char {
+        match self {
+            SpriteKind::Torch => 'A',
+            SpriteKind::Coin => 'C',
+            SpriteKind::Pit => 'P',
+            SpriteKind::Enemy => 'M',
+            SpriteKind::Spikes => 'S',
+            SpriteKind::Lava => 'L',
+            SpriteKind::Poison => 'V',
+            SpriteKind::Health => 'H',
+            SpriteKind::Ammo => 'B',
+            SpriteKind::Projectile => '*',
+            SpriteKind::Ghost => '@',
+            SpriteKind::Plate => '_',
+        }
+    }
+
+    pub fn desde_simbolo(c: char) -> Option<Self> {
+        Self::TODOS.into_iter().find(|k| k.simbolo() == c)
+    }
+}
 
 #[derive(Clone, Copy)]
 pub struct Sprite {
@@ -18,8 +50,19 @@ pub struct Sprites {
     anim_torch: Vec<[Color; 32*32]>, // frames 32x32
     anim_coin:  Vec<[Color; 32*32]>, // frames 32x32
     img_pit:    [Color; 32*32],      // estático
+    anim_enemy: Vec<[Color; 32*32]>, // frames 32x32
+    anim_lava:  Vec<[Color; 32*32]>, // frames 32x32 (burbujea)
+    img_spikes: [Color; 32*32],      // estático
+    img_poison: [Color; 32*32],      // estático
+    img_health: [Color; 32*32],      // estático
+    img_ammo:   [Color; 32*32],      // estático
+    img_rocket: [Color; 32*32],      // estático
+    img_ghost:  [Color; 32*32],      // estático, semitransparente
+    img_plate:  [Color; 32*32],      // estático
     f_torch: usize,
     f_coin:  usize,
+    f_enemy: usize,
+    f_lava:  usize,
     t: usize,
 }
 
@@ -82,13 +125,140 @@ impl Sprites {
             }
         }
 
+        // enemigo (fantasma rojo con ojos, flota arriba/abajo)
+        let mut enemy:Vec<[Color; 32*32]> = Vec::new();
+        for f in 0..2 {
+            let mut img = [Color::BLANK; 32*32];
+            let off = f as f32 * 2.0;
+            for y in 0..32 {
+                for x in 0..32 {
+                    let dx = x as f32 - 16.0;
+                    let dy = y as f32 - 12.0 - off;
+                    let cabeza = dx*dx + dy*dy <= 11.0*11.0 && dy <= 0.0;
+                    let cuerpo = dx.abs() <= 11.0 && dy > 0.0 && dy <= 14.0 - ((x / 4) % 2) as f32 * 3.0;
+                    if cabeza || cuerpo {
+                        let ojo = (dy + 2.0).abs() < 2.5 && ((dx + 5.0).abs() < 2.5 || (dx - 5.0).abs() < 2.5);
+                        img[y*32 + x] = if ojo { Color::WHITE } else { Color::MAROON };
+                    }
+                }
+            }
+            enemy.push(img);
+        }
+
+        // pinchos (triángulos grises en la mitad de abajo)
+        let mut spikes = [Color::BLANK; 32*32];
+        for y in 16..32 {
+            for x in 0..32 {
+                let punta = (x % 8) as i32 - 4;      // -4..3 dentro de cada diente
+                if (y as i32 - 16) * 4 >= punta.abs() * 16 - 8 {
+                    spikes[y*32 + x] = if punta.abs() < 2 { Color::LIGHTGRAY } else { Color::GRAY };
+                }
+            }
+        }
+
+        // lava (charco naranja que burbujea)
+        let mut lava_anim:Vec<[Color; 32*32]> = Vec::new();
+        for f in 0..3 {
+            let mut img = [Color::BLANK; 32*32];
+            for y in 20..32 {
+                for x in 0..32 {
+                    let v = (x*7 + y*3 + f*5) % 11;
+                    img[y*32 + x] = if v < 3 { Color::YELLOW } else if v < 7 { Color::ORANGE } else { Color::RED };
+                }
+            }
+            lava_anim.push(img);
+        }
+
+        // veneno (nube verde semitransparente)
+        let mut poison = [Color::BLANK; 32*32];
+        for y in 0..32 {
+            for x in 0..32 {
+                let dx = x as f32 - 16.0;
+                let dy = (y as f32 - 20.0) * 1.6;
+                if dx*dx + dy*dy <= 13.0*13.0 && (x + y*3) % 4 != 0 {
+                    poison[y*32 + x] = if (x ^ y) & 4 == 0 { Color::LIME } else { Color::DARKGREEN };
+                }
+            }
+        }
+
+        // botiquín (caja blanca con cruz roja)
+        let mut health = [Color::BLANK; 32*32];
+        for y in 14..30 {
+            for x in 8..24 {
+                let cruz = (14..18).contains(&x) || (20..24).contains(&y);
+                health[y*32 + x] = if cruz { Color::RED } else { Color::RAYWHITE };
+            }
+        }
+
+        // caja de munición (verde oliva con balas doradas arriba)
+        let mut ammo = [Color::BLANK; 32*32];
+        for y in 16..30 {
+            for x in 6..26 {
+                ammo[y*32 + x] = if y == 16 || y == 29 || x == 6 || x == 25 { Color::DARKGREEN } else { Color::GREEN };
+            }
+        }
+        for b in 0..4 {
+            for y in 10..16 {
+                for x in 9 + b*4..11 + b*4 {
+                    ammo[y*32 + x] = if y < 12 { Color::ORANGE } else { Color::GOLD };
+                }
+            }
+        }
+
+        // cohete (bola de fuego chica)
+        let mut rocket = [Color::BLANK; 32*32];
+        for y in 0..32 {
+            for x in 0..32 {
+                let dx = x as f32 - 16.0;
+                let dy = y as f32 - 16.0;
+                let r2 = dx*dx + dy*dy;
+                if r2 <= 3.0*3.0 { rocket[y*32 + x] = Color::WHITE; }
+                else if r2 <= 6.0*6.0 { rocket[y*32 + x] = Color::YELLOW; }
+                else if r2 <= 8.0*8.0 { rocket[y*32 + x] = Color::ORANGE; }
+            }
+        }
+
+        // fantasma (silueta celeste semitransparente, ojos oscuros)
+        let mut ghost = [Color::BLANK; 32*32];
+        for y in 4..30 {
+            for x in 6..26 {
+                let dx = x as f32 - 16.0;
+                let dy = y as f32 - 13.0;
+                let cabeza = dx*dx + dy*dy <= 10.0*10.0 && y <= 13;
+                let cuerpo = y > 13 && dx.abs() <= 10.0 && (y < 27 || (x / 3) % 2 == 0);
+                if cabeza || cuerpo {
+                    let ojo = (9..13).contains(&y) && ((11..14).contains(&x) || (18..21).contains(&x));
+                    ghost[y*32 + x] = if ojo { Color::new(20, 30, 60, 160) } else { Color::new(170, 220, 255, 110) };
+                }
+            }
+        }
+
+        // placa de presión (losa gris chata sobre el piso)
+        let mut plate = [Color::BLANK; 32*32];
+        for y in 27..32 {
+            for x in 3..29 {
+                plate[y*32 + x] = if y == 27 { Color::LIGHTGRAY } else if x == 3 || x == 28 { Color::DARKGRAY } else { Color::GRAY };
+            }
+        }
+
         Self {
             lista: Vec::new(),
             anim_torch: torch,
             anim_coin: coin_anim,
             img_pit: pit,
+            anim_enemy: enemy,
+            anim_lava: lava_anim,
+            img_spikes: spikes,
+            img_poison: poison,
+            img_health: health,
+            img_ammo: ammo,
+            img_rocket: rocket,
+            img_ghost: ghost,
+            img_plate: plate,
             f_torch: 0,
             f_coin: 0,
+            f_enemy: 0,
+            f_lava: 0,
             t: 0,
         }
     }
@@ -103,6 +273,12 @@ impl Sprites {
                     'A' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Torch }),
                     'C' => { self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Coin }); coins += 1; }
                     'P' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Pit }),
+                    'S' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Spikes }),
+                    'L' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Lava }),
+                    'V' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Poison }),
+                    'H' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Health }),
+                    'B' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Ammo }),
+                    '_' => self.lista.push(Sprite { x: x as f32 + 0.5, y: y as f32 + 0.5, kind: SpriteKind::Plate }),
                     _ => {}
                 }
             }
@@ -112,6 +288,11 @@ impl Sprites {
 
     /// Elimina monedas en la celda actual del jugador. Devuelve cuántas recogió.
     pub fn recolectar_monedas_en(&mut self, px: f32, py: f32) -> usize {
+        self.recolectar_en(px, py, SpriteKind::Coin)
+    }
+
+    /// Elimina los sprites `kind` de la celda (px, py). Devuelve cuántos quitó.
+    pub fn recolectar_en(&mut self, px: f32, py: f32, kind: SpriteKind) -> usize {
         let cx = px.floor() as i32;
         let cy = py.floor() as i32;
         let mut count = 0usize;
@@ -119,7 +300,7 @@ impl Sprites {
             let scx = s.x.floor() as i32;
             let scy = s.y.floor() as i32;
             let same_cell = scx == cx && scy == cy;
-            if same_cell && matches!(s.kind, SpriteKind::Coin) {
+            if same_cell && s.kind == kind {
                 count += 1;
                 false // quitar
             } else {
@@ -133,43 +314,34 @@ impl Sprites {
         self.t += 1;
         if self.t % 12 == 0 { self.f_torch = (self.f_torch + 1) % self.anim_torch.len(); }
         if self.t % 10 == 0 { self.f_coin  = (self.f_coin  + 1) % self.anim_coin.len(); }
+        if self.t % 15 == 0 { self.f_enemy = (self.f_enemy + 1) % self.anim_enemy.len(); }
+        if self.t % 20 == 0 { self.f_lava  = (self.f_lava  + 1) % self.anim_lava.len(); }
     }
 }
 
-pub fn dibujar_sprites(fb:&mut Framebuffer, j:&Jugador, mapa:&Mapa, spr:&Sprites, z:&[f32]) {
+/// Dibuja los sprites del mapa más los `extra` (entidades móviles como enemigos)
+pub fn dibujar_sprites(fb:&mut Framebuffer, j:&Jugador, mapa:&Mapa, spr:&Sprites, extra:&[Sprite], z:&[f32]) {
     let w = fb.w as i32;
     let h = fb.h as i32;
 
     // ordenar por distancia (lejos->cerca)
-    let mut orden:Vec<(usize, f32)> = spr.lista.iter()
-        .enumerate()
-        .map(|(i,s)| (i, ((s.x - j.x).powi(2) + (s.y - j.y).powi(2)).sqrt()))
+    let mut orden:Vec<(Sprite, f32)> = spr.lista.iter()
+        .chain(extra.iter())
+        .map(|s| (*s, ((s.x - j.x).powi(2) + (s.y - j.y).powi(2)).sqrt()))
         .collect();
     orden.sort_by(|a,b| b.1.partial_cmp(&a.1).unwrap());
 
-    for (idx, _dist) in orden {
-        let s = spr.lista[idx];
-
+    for (s, _dist) in orden {
         // seguridad: no dentro de pared
         if let Some(c) = super::motor::celda(mapa, s.x as i32, s.y as i32) {
-            if c != ' ' && c != 'A' && c != 'C' && c != 'P' { continue; }
+            if !es_caminable(c) { continue; }
         }
 
-        let dx = s.x - j.x;
-        let dy = s.y - j.y;
-
-        // Transformación a espacio de cámara (sin vector 'plane', usando ortogonal al 'look')
-        let inv_det = 1.0 / (j.ang.cos() * (j.ang + std::f32::consts::FRAC_PI_2).sin()
-                           - j.ang.sin() * (j.ang + std::f32::consts::FRAC_PI_2).cos());
-
-        let trans_x = inv_det * ((j.ang + std::f32::consts::FRAC_PI_2).sin()*dx - (j.ang + std::f32::consts::FRAC_PI_2).cos()*dy);
-        let trans_y = inv_det * (-j.ang.sin()*dx + j.ang.cos()*dy);
-        if trans_y <= 0.01 { continue; }
-
-        let sprite_screen_x = ((w as f32 / 2.0) * (1.0 + trans_x / trans_y)) as i32;
+        // a pantalla con la misma cámara que las paredes
+        let Some((sprite_screen_x, prof)) = a_pantalla(j, s.x, s.y, w) else { continue; };
 
         // Tamaño proporcional a distancia, pero con mínimo para que se vean mejor
-        let sprite_h = ((h as f32 / trans_y) as i32).max(14);
+        let sprite_h = ((h as f32 / prof) as i32).max(14);
         let sprite_w = sprite_h;
 
         let draw_start_y = (-sprite_h/2 + h/2).max(0);
@@ -182,11 +354,20 @@ pub fn dibujar_sprites(fb:&mut Framebuffer, j:&Jugador, mapa:&Mapa, spr:&Sprites
             SpriteKind::Torch => (Some(&spr.anim_torch[spr.f_torch]), None),
             SpriteKind::Coin  => (Some(&spr.anim_coin[spr.f_coin]), None),
             SpriteKind::Pit   => (None, Some(&spr.img_pit)),
+            SpriteKind::Enemy => (Some(&spr.anim_enemy[spr.f_enemy]), None),
+            SpriteKind::Lava  => (Some(&spr.anim_lava[spr.f_lava]), None),
+            SpriteKind::Spikes => (None, Some(&spr.img_spikes)),
+            SpriteKind::Poison => (None, Some(&spr.img_poison)),
+            SpriteKind::Health => (None, Some(&spr.img_health)),
+            SpriteKind::Ammo   => (None, Some(&spr.img_ammo)),
+            SpriteKind::Projectile => (None, Some(&spr.img_rocket)),
+            SpriteKind::Ghost      => (None, Some(&spr.img_ghost)),
+            SpriteKind::Plate      => (None, Some(&spr.img_plate)),
         };
 
         for stripe in draw_start_x..=draw_end_x {
             let tex_x = ((stripe - (-sprite_w/2 + sprite_screen_x)) * 32 / sprite_w).clamp(0,31);
-            if (trans_y as f32) < z[stripe as usize] {
+            if prof < z[stripe as usize] {
                 for y in draw_start_y..=draw_end_y {
                     let tex_y = ((y - (-sprite_h/2 + h/2)) * 32 / sprite_h).clamp(0,31);
                     let col = if let Some(fr) = frame_opt {
@@ -194,7 +375,8 @@ pub fn dibujar_sprites(fb:&mut Framebuffer, j:&Jugador, mapa:&Mapa, spr:&Sprites
                     } else {
                         one_img.unwrap()[tex_y as usize * 32 + tex_x as usize]
                     };
-                    if col.a > 0 { fb.set(stripe, y, col); }
+                    if col.a == 255 { fb.set(stripe, y, col); }
+                    else if col.a > 0 { fb.mezclar(stripe, y, col); }
                 }
             }
         }
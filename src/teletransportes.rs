@@ -0,0 +1,124 @@
+//! Teletransportadores ('T'). Al pisar uno el jugador aparece en su destino,
+//! mirando al ángulo del enlace si lo tiene (si no, hacia donde iba).
+//!
+//! Los destinos pueden venir en el nivel (`teletransporte x y dx dy [grados]`);
+//! los 'T' sin destino se emparejan de a dos en orden de lectura (fila por
+//! fila), cada uno lleva al otro. Para que no rebote de uno al otro, después
+//! de un salto hay una espera y además la celda de llegada no dispara hasta
+//! que el jugador sale de ella.
+
+use crate::mapas::Teletransporte;
+use crate::motor::*;
+use raylib::prelude::Color;
+
+pub const ESPERA: u32 = 45; // frames entre saltos
+const DESTELLO: u32 = 15;   // frames que dura el destello de la pantalla
+
+/// Enlaces del nivel: primero los explícitos (si salen de un 'T') y después
+/// los 'T' que quedaron sin destino, emparejados en orden de lectura.
+pub fn enlazar(mapa: &Mapa, explicitos: &[Teletransporte]) -> Vec<Teletransporte> {
+    let mut enlaces: Vec<Teletransporte> = explicitos.iter()
+        .filter(|t| celda(mapa, t.x, t.y) == Some('T'))
+        .copied()
+        .collect();
+    let sueltos: Vec<(i32, i32)> = (0..mapa.len() as i32)
+        .flat_map(|y| (0..mapa[0].len() as i32).map(move |x| (x, y)))
+        .filter(|&(x, y)| celda(mapa, x, y) == Some('T') && !enlaces.iter().any(|t| (t.x, t.y) == (x, y)))
+        .collect();
+    for par in sueltos.chunks_exact(2) {
+        let ((ax, ay), (bx, by)) = (par[0], par[1]);
+        enlaces.push(Teletransporte { x: ax, y: ay, dx: bx, dy: by, angulo: None });
+        enlaces.push(Teletransporte { x: bx, y: by, dx: ax, dy: ay, angulo: None });
+    }
+    enlaces
+}
+
+/// Los teletransportadores en juego
+#[derive(Clone)]
+pub struct Teletransportes {
+    enlaces: Vec<Teletransporte>,
+    pub espera: u32,                    // frames hasta poder saltar otra vez
+    pub llegada: Option<(i32, i32)>,    // celda a la que llegó; no dispara hasta salir de ella
+}
+
+impl Teletransportes {
+    /// `x`, `y` es la celda donde arranca el jugador (que tampoco dispara).
+    pub fn nuevo(mapa: &Mapa, explicitos: &[Teletransporte], x: i32, y: i32) -> Self {
+        Self { enlaces: enlazar(mapa, explicitos), espera: 0, llegada: Some((x, y)) }
+    }
+
+    /// El jugador apareció en esa celda sin caminar (salto, disparador, pozo)
+    pub fn llegar(&mut self, x: i32, y: i32) {
+        self.llegada = Some((x, y));
+    }
+
+    /// Un frame con el jugador en la celda `x`, `y`: devuelve el enlace si
+    /// hay que saltar. `libre` dice si se puede llegar a una celda (puede
+    /// haber un bloque o una puerta cerrada); si no, no hay salto ni espera.
+    pub fn actualizar(&mut self, x: i32, y: i32, libre: impl Fn(i32, i32) -> bool) -> Option<Teletransporte> {
+        self.espera = self.espera.saturating_sub(1);
+        if self.llegada.is_some_and(|c| c != (x, y)) { self.llegada = None; }
+        if self.espera > 0 || self.llegada.is_some() { return None; }
+        let t = *self.enlaces.iter().find(|t| (t.x, t.y) == (x, y) && libre(t.dx, t.dy))?;
+        self.espera = ESPERA;
+        self.llegada = Some((t.dx, t.dy));
+        Some(t)
+    }
+
+    /// Aclarado de la pantalla justo después de un salto (0..1)
+    pub fn destello(&self) -> f32 {
+        self.espera.saturating_sub(ESPERA - DESTELLO) as f32 / DESTELLO as f32
+    }
+
+    /// Cada destino marcado sobre el minimapa (los 'T' ya tienen su color)
+    pub fn dibujar_en_minimapa(&self, fb: &mut Framebuffer) {
+        for t in &self.enlaces {
+            marcar_minimapa(fb, t.dx as f32 + 0.5, t.dy as f32 + 0.5, Color::WHITE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapa(filas: &[&str]) -> Mapa {
+        filas.iter().map(|f| f.chars().collect()).collect()
+    }
+
+    #[test]
+    fn espera_y_llegada() {
+        // los dos 'T' se emparejan; se arranca entre los dos
+        let m = mapa(&["11111", "1T T1", "11111"]);
+        let mut t = Teletransportes::nuevo(&m, &[], 2, 1);
+        let libre = |_, _| true;
+        assert!(t.actualizar(2, 1, libre).is_none());
+
+        let s = t.actualizar(1, 1, libre).expect("salta al pisar el 'T'");
+        assert_eq!((s.dx, s.dy), (3, 1));
+        assert_eq!(t.destello(), 1.0);
+        // en la llegada no rebota, aunque pase la espera
+        for _ in 0..ESPERA * 2 {
+            assert!(t.actualizar(3, 1, libre).is_none());
+        }
+        // al salir y volver ya puede saltar otra vez
+        assert!(t.actualizar(2, 1, libre).is_none());
+        assert!(t.actualizar(3, 1, libre).is_some());
+
+        // volviendo antes de que pase la espera no salta
+        t.actualizar(2, 1, libre);
+        assert!(t.actualizar(3, 1, libre).is_none());
+    }
+
+    #[test]
+    fn destino_ocupado() {
+        let m = mapa(&["11111", "1T T1", "11111"]);
+        let mut t = Teletransportes::nuevo(&m, &[], 2, 1);
+        t.actualizar(2, 1, |_, _| true);
+        // con un bloque en el destino no hay salto, ni espera ni destello
+        assert!(t.actualizar(1, 1, |_, _| false).is_none());
+        assert_eq!((t.espera, t.llegada, t.destello()), (0, None, 0.0));
+        // y cuando se libera salta sin tener que salir del 'T'
+        assert!(t.actualizar(1, 1, |_, _| true).is_some());
+    }
+}
@@ -0,0 +1,851 @@
+//! Importador de mapas de Tiled (TMX o JSON). Lo que se entiende:
+//!
+//! - Capas de tiles (CSV o `<tile gid>` en TMX, arreglo o CSV en JSON): cada
+//!   tile se traduce por sus propiedades en el tileset: `textura` (1..6) es
+//!   una pared con esa textura y `tipo` (o la clase del tile) es una celda
+//!   especial: piso, salida, antorcha, moneda, pozo, pinchos, lava, veneno,
+//!   botiquin, municion, enemigo, puerta, teletransporte, bloque, placa.
+//!   Las capas de más arriba tapan a las de abajo.
+//! - Capas de objetos (puntos, rectángulos o tiles sueltos), por su clase o
+//!   propiedad `tipo`: `inicio` (con `angulo` en grados), `disparador` (el
+//!   rectángulo con sus `acciones` y `cuando`: entrar, apretar o soltar) o
+//!   cualquiera de los tipos de celda de arriba, que se pone en la celda del
+//!   objeto. Un `teletransporte` puede llevar `destino` ("x y" en celdas) y
+//!   `angulo`; sin destino se empareja con otro (ver `teletransportes`).
+//! - Propiedades del mapa: `nombre` y `reglas` ("minimo 3; enemigos").
+//!
+//! Tilesets embebidos o externos (`.tsx`, `.tsj`). Lo demás (mapas infinitos,
+//! otras orientaciones, datos en base64 o comprimidos, capas de imagen,
+//! polígonos, plantillas…) da un error que dice qué cambiar.
+
+use crate::mapas::{parsear_regla, Cuando, Disparador, Nivel, Teletransporte};
+use crate::motor::Mapa;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const SIN_GIRO: u32 = 0x0FFF_FFFF; // los 4 bits altos del gid son espejados/rotaciones
+
+type Props = Vec<(String, String)>;
+
+/// Lo que importa de un mapa de Tiled, venga del formato que venga
+struct Plano {
+    ancho: usize,
+    alto: usize,
+    tile_w: f32,
+    tile_h: f32,
+    props: Props,
+    tiles: HashMap<u32, (String, Props)>, // gid → clase y propiedades
+    capas: Vec<Capa>,
+}
+
+enum Capa {
+    Tiles(String, Vec<u32>),
+    Objetos(Vec<Objeto>),
+}
+
+struct Objeto {
+    id: u32,
+    tipo: String,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    gid: Option<u32>,
+    props: Props,
+}
+
+/// Lee `ruta` (.tmx, .tmj o .json) como el nivel `id`
+pub fn importar(ruta: &Path, id: &str) -> Result<Nivel, String> {
+    let txt = fs::read_to_string(ruta).map_err(|e| e.to_string())?;
+    let dir = ruta.parent().unwrap_or(Path::new("."));
+    let plano = match ruta.extension().and_then(|x| x.to_str()) {
+        Some("tmx") => plano_tmx(&Xml::leer(&txt)?, dir)?,
+        Some("tmj" | "json") => plano_json(&Json::leer(&txt)?, dir)?,
+        _ => return Err("formato desconocido (se esperaba .tmx, .tmj o .json)".to_string()),
+    };
+    convertir(plano, id)
+}
+
+fn prop<'a>(props: &'a Props, nombre: &str) -> Option<&'a str> {
+    props.iter().find(|(n, _)| n == nombre).map(|(_, v)| v.as_str())
+}
+
+/// Tipo de celda → carácter del `Mapa`
+fn simbolo(tipo: &str) -> Option<char> {
+    Some(match tipo {
+        "piso" => ' ',
+        "salida" => 'E',
+        "antorcha" => 'A',
+        "moneda" => 'C',
+        "pozo" => 'P',
+        "pinchos" => 'S',
+        "lava" => 'L',
+        "veneno" => 'V',
+        "botiquin" => 'H',
+        "municion" => 'B',
+        "enemigo" => 'M',
+        "puerta" => 'D',
+        "teletransporte" => 'T',
+        "bloque" => 'X',
+        "placa" => '_',
+        _ => return None,
+    })
+}
+
+fn celda_de_tile(p: &Plano, gid: u32) -> Result<char, String> {
+    let Some((clase, props)) = p.tiles.get(&gid) else {
+        return Err(format!("el tile {gid} no tiene las propiedades 'textura' ni 'tipo'"));
+    };
+    if let Some(t) = prop(props, "textura") {
+        return match t.trim().parse::<u32>() {
+            Ok(k @ 1..=6) => Ok(char::from_digit(k, 10).unwrap()),
+            _ => Err(format!("tile {gid}: textura '{t}' inválida (va de 1 a 6)")),
+        };
+    }
+    let tipo = prop(props, "tipo").unwrap_or(clase);
+    simbolo(tipo).ok_or_else(|| format!("tile {gid}: tipo desconocido '{tipo}'"))
+}
+
+fn convertir(p: Plano, id: &str) -> Result<Nivel, String> {
+    let mut mapa: Mapa = vec![vec![' '; p.ancho]; p.alto];
+    let mut inicio = None;
+    let mut disparadores = Vec::new();
+    let mut teletransportes = Vec::new();
+    for capa in &p.capas {
+        match capa {
+            Capa::Tiles(nombre, datos) => {
+                if datos.len() != p.ancho * p.alto {
+                    return Err(format!("capa '{nombre}': {} tiles para un mapa de {}×{}", datos.len(), p.ancho, p.alto));
+                }
+                for (i, &gid) in datos.iter().enumerate() {
+                    let gid = gid & SIN_GIRO;
+                    if gid != 0 { mapa[i / p.ancho][i % p.ancho] = celda_de_tile(&p, gid)?; }
+                }
+            }
+            Capa::Objetos(objetos) => {
+                for o in objetos {
+                    // la clase, si no la propiedad `tipo` del objeto y si no la de su tile
+                    let tipo = match (o.tipo.as_str(), prop(&o.props, "tipo"), o.gid) {
+                        ("", Some(t), _) => t.to_string(),
+                        ("", None, Some(g)) => p.tiles.get(&(g & SIN_GIRO))
+                            .map(|(c, pr)| prop(pr, "tipo").unwrap_or(c).to_string())
+                            .unwrap_or_default(),
+                        (t, _, _) => t.to_string(),
+                    };
+                    // los objetos con tile se anclan abajo a la izquierda
+                    let y = if o.gid.is_some() { o.y - o.h } else { o.y };
+                    let (cx, cy) = ((o.x / p.tile_w).floor() as i32, (y / p.tile_h).floor() as i32);
+                    if cx < 0 || cy < 0 || cx >= p.ancho as i32 || cy >= p.alto as i32 {
+                        return Err(format!("objeto {} fuera del mapa", o.id));
+                    }
+                    match tipo.as_str() {
+                        "inicio" => {
+                            let grados = prop(&o.props, "angulo").unwrap_or("0").trim().parse::<f32>()
+                                .map_err(|_| format!("objeto {}: 'angulo' no es un número", o.id))?;
+                            inicio = Some((cx, cy, grados.to_radians()));
+                        }
+                        "disparador" => {
+                            let acciones = prop(&o.props, "acciones")
+                                .ok_or_else(|| format!("disparador {} sin la propiedad 'acciones'", o.id))?;
+                            let x1 = ((o.x + o.w) / p.tile_w).ceil() as i32;
+                            let y1 = ((y + o.h) / p.tile_h).ceil() as i32;
+                            let cuando = match prop(&o.props, "cuando").unwrap_or("entrar") {
+                                "entrar" => Cuando::Entrar,
+                                "apretar" => Cuando::Apretar,
+                                "soltar" => Cuando::Soltar,
+                                c => return Err(format!("disparador {}: 'cuando' desconocido '{c}' (entrar, apretar o soltar)", o.id)),
+                            };
+                            disparadores.push(Disparador {
+                                x: cx,
+                                y: cy,
+                                w: (x1 - cx).max(1),
+                                h: (y1 - cy).max(1),
+                                cuando,
+                                acciones: acciones.replace('\n', "; "),
+                            });
+                        }
+                        "teletransporte" => {
+                            mapa[cy as usize][cx as usize] = 'T';
+                            if let Some(d) = prop(&o.props, "destino") {
+                                let err = || format!("teletransporte {}: 'destino' tiene que ser \"x y\"", o.id);
+                                let c: Vec<i32> = d.split_whitespace().map(|t| t.parse().map_err(|_| err())).collect::<Result<_, _>>()?;
+                                let [dx, dy] = c[..] else { return Err(err()) };
+                                let angulo = match prop(&o.props, "angulo") {
+                                    Some(a) => Some(a.trim().parse::<f32>()
+                                        .map_err(|_| format!("objeto {}: 'angulo' no es un número", o.id))?.to_radians()),
+                                    None => None,
+                                };
+                                teletransportes.push(Teletransporte { x: cx, y: cy, dx, dy, angulo });
+                            }
+                        }
+                        t => match simbolo(t) {
+                            Some(c) => mapa[cy as usize][cx as usize] = c,
+                            None if t.is_empty() => return Err(format!("objeto {} sin tipo (clase o propiedad 'tipo')", o.id)),
+                            None => return Err(format!("objeto {}: tipo desconocido '{t}'", o.id)),
+                        },
+                    }
+                }
+            }
+        }
+    }
+
+    let mut reglas = Vec::new();
+    for r in prop(&p.props, "reglas").unwrap_or("").split(';').filter(|r| !r.trim().is_empty()) {
+        reglas.push(parsear_regla(r).ok_or_else(|| format!("regla inválida '{}'", r.trim()))?);
+    }
+    Ok(Nivel {
+        id: id.to_string(),
+        titulo: prop(&p.props, "nombre").map(str::to_string),
+        generado: None,
+        mapa,
+        inicio: inicio.ok_or("falta un objeto de tipo 'inicio'")?,
+        reglas,
+        disparadores,
+        teletransportes,
+    })
+}
+
+/// Tiles de un tileset externo, según su extensión
+fn tileset_externo(dir: &Path, fuente: &str, primero: u32, tiles: &mut HashMap<u32, (String, Props)>) -> Result<(), String> {
+    let ruta = dir.join(fuente);
+    let txt = fs::read_to_string(&ruta).map_err(|e| format!("tileset '{fuente}': {e}"))?;
+    if fuente.ends_with(".tsx") {
+        tiles_xml(&Xml::leer(&txt)?, primero, tiles);
+        Ok(())
+    } else {
+        tiles_json(&Json::leer(&txt)?, primero, tiles)
+    }
+}
+
+// --- TMX ---
+
+fn plano_tmx(raiz: &Xml, dir: &Path) -> Result<Plano, String> {
+    if raiz.nombre != "map" { return Err("no es un mapa TMX".to_string()); }
+    if raiz.attr("orientation").is_some_and(|o| o != "orthogonal") {
+        return Err("solo se importan mapas ortogonales".to_string());
+    }
+    if raiz.attr("infinite") == Some("1") {
+        return Err("los mapas infinitos no se importan (desmarcar 'Infinito' en Tiled)".to_string());
+    }
+    let num = |e: &Xml, a: &str| e.attr(a).and_then(|v| v.parse::<f32>().ok());
+    let mut tiles = HashMap::new();
+    for ts in raiz.hijos("tileset") {
+        let primero = num(ts, "firstgid").unwrap_or(1.0) as u32;
+        match ts.attr("source") {
+            Some(f) => tileset_externo(dir, f, primero, &mut tiles)?,
+            None => tiles_xml(ts, primero, &mut tiles),
+        }
+    }
+    let mut capas = Vec::new();
+    capas_tmx(raiz, &mut capas)?;
+    Ok(Plano {
+        ancho: num(raiz, "width").ok_or("falta 'width'")? as usize,
+        alto: num(raiz, "height").ok_or("falta 'height'")? as usize,
+        tile_w: num(raiz, "tilewidth").ok_or("falta 'tilewidth'")?,
+        tile_h: num(raiz, "tileheight").ok_or("falta 'tileheight'")?,
+        props: props_xml(raiz),
+        tiles,
+        capas,
+    })
+}
+
+fn capas_tmx(e: &Xml, capas: &mut Vec<Capa>) -> Result<(), String> {
+    for c in &e.hijos {
+        let nombre = c.attr("name").unwrap_or("").to_string();
+        match c.nombre.as_str() {
+            "layer" => {
+                let data = c.hijos("data").next().ok_or_else(|| format!("capa '{nombre}' sin datos"))?;
+                let gids = match data.attr("encoding") {
+                    Some("csv") => csv(&data.texto).map_err(|e| format!("capa '{nombre}': {e}"))?,
+                    None => data.hijos("tile").map(|t| t.attr("gid").and_then(|g| g.parse().ok()).unwrap_or(0)).collect(),
+                    Some(x) => return Err(format!("capa '{nombre}': codificación '{x}' no soportada (guardar las capas como CSV)")),
+                };
+                capas.push(Capa::Tiles(nombre, gids));
+            }
+            "objectgroup" => {
+                let mut objetos = Vec::new();
+                for o in c.hijos("object") {
+                    let id = o.attr("id").and_then(|v| v.parse().ok()).unwrap_or(0);
+                    if o.attr("template").is_some() {
+                        return Err(format!("objeto {id}: las plantillas no se soportan (desvincularlo en Tiled)"));
+                    }
+                    if let Some(f) = o.hijos.iter().find(|h| h.nombre != "properties" && h.nombre != "point") {
+                        return Err(format!("objeto {id}: la forma '{}' no se soporta (usar puntos o rectángulos)", f.nombre));
+                    }
+                    let n = |a: &str| o.attr(a).and_then(|v| v.parse::<f32>().ok()).unwrap_or(0.0);
+                    objetos.push(Objeto {
+                        id,
+                        tipo: o.attr("class").or(o.attr("type")).unwrap_or("").to_string(),
+                        x: n("x"),
+                        y: n("y"),
+                        w: n("width"),
+                        h: n("height"),
+                        gid: o.attr("gid").and_then(|v| v.parse().ok()),
+                        props: props_xml(o),
+                    });
+                }
+                capas.push(Capa::Objetos(objetos));
+            }
+            "group" => capas_tmx(c, capas)?,
+            "imagelayer" => return Err(format!("la capa de imagen '{nombre}' no se soporta")),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn tiles_xml(ts: &Xml, primero: u32, tiles: &mut HashMap<u32, (String, Props)>) {
+    for t in ts.hijos("tile") {
+        let Some(id) = t.attr("id").and_then(|v| v.parse::<u32>().ok()) else { continue };
+        let clase = t.attr("class").or(t.attr("type")).unwrap_or("").to_string();
+        tiles.insert(primero + id, (clase, props_xml(t)));
+    }
+}
+
+fn props_xml(e: &Xml) -> Props {
+    e.hijos("properties").flat_map(|p| p.hijos("property"))
+        .map(|p| {
+            let valor = p.attr("value").map(str::to_string).unwrap_or_else(|| p.texto.clone());
+            (p.attr("name").unwrap_or("").to_string(), valor)
+        })
+        .collect()
+}
+
+fn csv(s: &str) -> Result<Vec<u32>, String> {
+    s.split(',').map(str::trim).filter(|v| !v.is_empty())
+        .map(|v| v.parse().map_err(|_| format!("'{v}' no es un tile")))
+        .collect()
+}
+
+// --- JSON ---
+
+fn plano_json(raiz: &Json, dir: &Path) -> Result<Plano, String> {
+    if raiz.texto("type") == Some("tileset") {
+        return Err("es un tileset, no un mapa (guardarlo como .tsj)".to_string());
+    }
+    if raiz.texto("orientation").is_some_and(|o| o != "orthogonal") {
+        return Err("solo se importan mapas ortogonales".to_string());
+    }
+    if raiz.get("infinite").is_some_and(|v| matches!(v, Json::Bool(true))) {
+        return Err("los mapas infinitos no se importan (desmarcar 'Infinito' en Tiled)".to_string());
+    }
+    let mut tiles = HashMap::new();
+    for ts in raiz.lista("tilesets") {
+        let primero = ts.num("firstgid").unwrap_or(1.0) as u32;
+        match ts.texto("source") {
+            Some(f) => tileset_externo(dir, f, primero, &mut tiles)?,
+            None => tiles_json(ts, primero, &mut tiles)?,
+        }
+    }
+    let mut capas = Vec::new();
+    capas_json(raiz, &mut capas)?;
+    Ok(Plano {
+        ancho: raiz.num("width").ok_or("falta 'width'")? as usize,
+        alto: raiz.num("height").ok_or("falta 'height'")? as usize,
+        tile_w: raiz.num("tilewidth").ok_or("falta 'tilewidth'")? as f32,
+        tile_h: raiz.num("tileheight").ok_or("falta 'tileheight'")? as f32,
+        props: props_json(raiz),
+        tiles,
+        capas,
+    })
+}
+
+fn capas_json(e: &Json, capas: &mut Vec<Capa>) -> Result<(), String> {
+    for c in e.lista("layers") {
+        let nombre = c.texto("name").unwrap_or("").to_string();
+        match c.texto("type").unwrap_or("") {
+            "tilelayer" => {
+                let gids = match (c.texto("encoding"), c.get("data")) {
+                    (None | Some("csv"), Some(Json::Lista(v))) => v.iter().map(|g| g.como_num().unwrap_or(0.0) as u32).collect(),
+                    (Some("csv"), Some(Json::Texto(s))) => csv(s).map_err(|e| format!("capa '{nombre}': {e}"))?,
+                    (Some(x), _) => return Err(format!("capa '{nombre}': codificación '{x}' no soportada (guardar las capas como CSV)")),
+                    _ => return Err(format!("capa '{nombre}' sin datos")),
+                };
+                capas.push(Capa::Tiles(nombre, gids));
+            }
+            "objectgroup" => {
+                let mut objetos = Vec::new();
+                for o in c.lista("objects") {
+                    let id = o.num("id").unwrap_or(0.0) as u32;
+                    if o.get("template").is_some() {
+                        return Err(format!("objeto {id}: las plantillas no se soportan (desvincularlo en Tiled)"));
+                    }
+                    for forma in ["ellipse", "polygon", "polyline", "text"] {
+                        if o.get(forma).is_some_and(|v| !matches!(v, Json::Bool(false))) {
+                            return Err(format!("objeto {id}: la forma '{forma}' no se soporta (usar puntos o rectángulos)"));
+                        }
+                    }
+                    objetos.push(Objeto {
+                        id,
+                        tipo: o.texto("class").or(o.texto("type")).unwrap_or("").to_string(),
+                        x: o.num("x").unwrap_or(0.0) as f32,
+                        y: o.num("y").unwrap_or(0.0) as f32,
+                        w: o.num("width").unwrap_or(0.0) as f32,
+                        h: o.num("height").unwrap_or(0.0) as f32,
+                        gid: o.num("gid").map(|g| g as u32),
+                        props: props_json(o),
+                    });
+                }
+                capas.push(Capa::Objetos(objetos));
+            }
+            "group" => capas_json(c, capas)?,
+            "imagelayer" => return Err(format!("la capa de imagen '{nombre}' no se soporta")),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn tiles_json(ts: &Json, primero: u32, tiles: &mut HashMap<u32, (String, Props)>) -> Result<(), String> {
+    for t in ts.lista("tiles") {
+        let id = t.num("id").ok_or("tile sin 'id' en el tileset")? as u32;
+        let clase = t.texto("class").or(t.texto("type")).unwrap_or("").to_string();
+        tiles.insert(primero + id, (clase, props_json(t)));
+    }
+    Ok(())
+}
+
+fn props_json(e: &Json) -> Props {
+    e.lista("properties").iter()
+        .map(|p| {
+            let valor = match p.get("value") {
+                Some(Json::Texto(s)) => s.clone(),
+                Some(Json::Num(n)) => n.to_string(),
+                Some(Json::Bool(b)) => b.to_string(),
+                _ => String::new(),
+            };
+            (p.texto("name").unwrap_or("").to_string(), valor)
+        })
+        .collect()
+}
+
+// --- lector de JSON (lo justo para Tiled) ---
+
+enum Json {
+    Nulo,
+    Bool(bool),
+    Num(f64),
+    Texto(String),
+    Lista(Vec<Json>),
+    Objeto(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn leer(s: &str) -> Result<Json, String> {
+        let mut l = Lector { c: s.chars().collect(), i: 0 };
+        let v = l.valor()?;
+        l.espacios();
+        if l.i < l.c.len() { return Err(l.error("sobra texto al final")); }
+        Ok(v)
+    }
+
+    fn get(&self, clave: &str) -> Option<&Json> {
+        match self {
+            Json::Objeto(v) => v.iter().find(|(k, _)| k == clave).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn como_num(&self) -> Option<f64> {
+        match self { Json::Num(n) => Some(*n), _ => None }
+    }
+
+    fn num(&self, clave: &str) -> Option<f64> {
+        self.get(clave).and_then(Json::como_num)
+    }
+
+    fn texto(&self, clave: &str) -> Option<&str> {
+        match self.get(clave) { Some(Json::Texto(s)) => Some(s), _ => None }
+    }
+
+    fn lista(&self, clave: &str) -> &[Json] {
+        match self.get(clave) { Some(Json::Lista(v)) => v, _ => &[] }
+    }
+}
+
+/// Cursor sobre el texto, compartido por los lectores de JSON y XML
+struct Lector {
+    c: Vec<char>,
+    i: usize,
+}
+
+impl Lector {
+    fn error(&self, que: &str) -> String {
+        let linea = self.c[..self.i.min(self.c.len())].iter().filter(|&&c| c == '\n').count() + 1;
+        format!("línea {linea}: {que}")
+    }
+
+    fn ver(&self) -> Option<char> {
+        self.c.get(self.i).copied()
+    }
+
+    fn espacios(&mut self) {
+        while self.ver().is_some_and(char::is_whitespace) { self.i += 1; }
+    }
+
+    /// ¿Sigue `s`? Si sigue, lo consume.
+    fn sigue(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        if self.c.len() >= self.i + n && self.c[self.i..self.i + n].iter().copied().eq(s.chars()) {
+            self.i += n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn esperar(&mut self, s: &str) -> Result<(), String> {
+        if self.sigue(s) { Ok(()) } else { Err(self.error(&format!("se esperaba '{s}'"))) }
+    }
+
+    fn valor(&mut self) -> Result<Json, String> {
+        self.espacios();
+        match self.ver() {
+            Some('{') => {
+                self.i += 1;
+                let mut v = Vec::new();
+                self.espacios();
+                if self.sigue("}") { return Ok(Json::Objeto(v)); }
+                loop {
+                    self.espacios();
+                    let k = self.cadena()?;
+                    self.espacios();
+                    self.esperar(":")?;
+                    v.push((k, self.valor()?));
+                    self.espacios();
+                    if self.sigue("}") { return Ok(Json::Objeto(v)); }
+                    self.esperar(",")?;
+                }
+            }
+            Some('[') => {
+                self.i += 1;
+                let mut v = Vec::new();
+                self.espacios();
+                if self.sigue("]") { return Ok(Json::Lista(v)); }
+                loop {
+                    v.push(self.valor()?);
+                    self.espacios();
+                    if self.sigue("]") { return Ok(Json::Lista(v)); }
+                    self.esperar(",")?;
+                }
+            }
+            Some('"') => Ok(Json::Texto(self.cadena()?)),
+            Some('t') if self.sigue("true") => Ok(Json::Bool(true)),
+            Some('f') if self.sigue("false") => Ok(Json::Bool(false)),
+            Some('n') if self.sigue("null") => Ok(Json::Nulo),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let ini = self.i;
+                while self.ver().is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c)) { self.i += 1; }
+                let s: String = self.c[ini..self.i].iter().collect();
+                s.parse().map(Json::Num).map_err(|_| self.error(&format!("número inválido '{s}'")))
+            }
+            _ => Err(self.error("valor JSON inválido")),
+        }
+    }
+
+    fn cadena(&mut self) -> Result<String, String> {
+        self.esperar("\"")?;
+        let mut s = String::new();
+        loop {
+            let c = self.ver().ok_or_else(|| self.error("texto sin cerrar"))?;
+            self.i += 1;
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let e = self.ver().ok_or_else(|| self.error("texto sin cerrar"))?;
+                    self.i += 1;
+                    s.push(match e {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            let hex: String = self.c.get(self.i..self.i + 4).unwrap_or(&[]).iter().collect();
+                            self.i += 4;
+                            u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).unwrap_or('?')
+                        }
+                        otro => otro,
+                    });
+                }
+                _ => s.push(c),
+            }
+        }
+    }
+}
+
+// --- lector de XML (elementos, atributos y texto; sin DTD) ---
+
+struct Xml {
+    nombre: String,
+    attrs: Vec<(String, String)>,
+    hijos: Vec<Xml>,
+    texto: String,
+}
+
+impl Xml {
+    fn leer(s: &str) -> Result<Xml, String> {
+        let mut l = Lector { c: s.chars().collect(), i: 0 };
+        l.saltar_prologo()?;
+        l.elemento()
+    }
+
+    fn attr(&self, nombre: &str) -> Option<&str> {
+        self.attrs.iter().find(|(n, _)| n == nombre).map(|(_, v)| v.as_str())
+    }
+
+    fn hijos<'a>(&'a self, nombre: &'a str) -> impl Iterator<Item = &'a Xml> {
+        self.hijos.iter().filter(move |h| h.nombre == nombre)
+    }
+}
+
+impl Lector {
+    /// Declaración, comentarios y doctype antes del elemento raíz
+    fn saltar_prologo(&mut self) -> Result<(), String> {
+        loop {
+            self.espacios();
+            if self.sigue("<?") {
+                self.hasta("?>")?;
+            } else if self.sigue("<!--") {
+                self.hasta("-->")?;
+            } else if self.sigue("<!") {
+                self.hasta(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Avanza hasta después de `fin`
+    fn hasta(&mut self, fin: &str) -> Result<String, String> {
+        let ini = self.i;
+        while !self.sigue(fin) {
+            if self.ver().is_none() { return Err(self.error(&format!("falta '{fin}'"))); }
+            self.i += 1;
+        }
+        Ok(self.c[ini..self.i - fin.chars().count()].iter().collect())
+    }
+
+    fn nombre_xml(&mut self) -> String {
+        let ini = self.i;
+        while self.ver().is_some_and(|c| c.is_alphanumeric() || "_-:.".contains(c)) { self.i += 1; }
+        self.c[ini..self.i].iter().collect()
+    }
+
+    fn elemento(&mut self) -> Result<Xml, String> {
+        self.esperar("<")?;
+        let nombre = self.nombre_xml();
+        if nombre.is_empty() { return Err(self.error("etiqueta sin nombre")); }
+        let mut e = Xml { nombre, attrs: Vec::new(), hijos: Vec::new(), texto: String::new() };
+        loop {
+            self.espacios();
+            if self.sigue("/>") { return Ok(e); }
+            if self.sigue(">") { break; }
+            let k = self.nombre_xml();
+            if k.is_empty() { return Err(self.error(&format!("atributo inválido en <{}>", e.nombre))); }
+            self.espacios();
+            self.esperar("=")?;
+            self.espacios();
+            let comilla = if self.sigue("\"") { "\"" } else { self.esperar("'")?; "'" };
+            let v = self.hasta(comilla)?;
+            e.attrs.push((k, entidades(&v)));
+        }
+        loop {
+            if self.sigue("<!--") {
+                self.hasta("-->")?;
+            } else if self.sigue("<![CDATA[") {
+                e.texto += &self.hasta("]]>")?;
+            } else if self.sigue("</") {
+                let cierre = self.nombre_xml();
+                if cierre != e.nombre {
+                    return Err(self.error(&format!("se cerró <{cierre}> pero estaba abierto <{}>", e.nombre)));
+                }
+                self.espacios();
+                self.esperar(">")?;
+                return Ok(e);
+            } else if self.ver() == Some('<') {
+                e.hijos.push(self.elemento()?);
+            } else {
+                let ini = self.i;
+                while self.ver().is_some_and(|c| c != '<') { self.i += 1; }
+                if self.ver().is_none() { return Err(self.error(&format!("falta cerrar <{}>", e.nombre))); }
+                e.texto += &entidades(&self.c[ini..self.i].iter().collect::<String>());
+            }
+        }
+    }
+}
+
+fn entidades(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmx(txt: &str) -> Result<Nivel, String> {
+        convertir(plano_tmx(&Xml::leer(txt)?, Path::new("."))?, "prueba")
+    }
+
+    fn tmj(txt: &str) -> Result<Nivel, String> {
+        convertir(plano_json(&Json::leer(txt)?, Path::new("."))?, "prueba")
+    }
+
+    fn error(r: Result<Nivel, String>) -> String {
+        match r {
+            Ok(_) => panic!("se esperaba un error"),
+            Err(e) => e,
+        }
+    }
+
+    fn filas(n: &Nivel) -> Vec<String> {
+        n.mapa.iter().map(|f| f.iter().collect()).collect()
+    }
+
+    /// Mapa de 4×3 con tiles de 16: pared (1), moneda por clase (2) y
+    /// salida por propiedad (3). `capa` y `objetos` van adentro de <map>.
+    fn mapa_tmx(atributos: &str, capa: &str, objetos: &str) -> String {
+        format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="4" height="3" tilewidth="16" tileheight="16" {atributos}>
+ <properties><property name="nombre" value="Prueba"/></properties>
+ <tileset firstgid="1" name="t" tilewidth="16" tileheight="16">
+  <tile id="0"><properties><property name="textura" value="1"/></properties></tile>
+  <tile id="1" class="moneda"/>
+  <tile id="2"><properties><property name="tipo" value="salida"/></properties></tile>
+ </tileset>
+ {capa}
+ <objectgroup name="objetos">
+  <object id="1" class="inicio" x="24" y="24"><point/></object>
+  {objetos}
+ </objectgroup>
+</map>"#)
+    }
+
+    const CSV: &str = r#"<layer name="paredes" width="4" height="3"><data encoding="csv">
+1,1,1,1,
+1,0,2,1,
+1,1,3,1
+</data></layer>"#;
+
+    #[test]
+    fn capa_csv() {
+        let n = tmx(&mapa_tmx("", CSV, "")).unwrap();
+        assert_eq!(filas(&n), ["1111", "1 C1", "11E1"]);
+        assert_eq!(n.titulo.as_deref(), Some("Prueba"));
+        assert_eq!((n.inicio.0, n.inicio.1), (1, 1));
+    }
+
+    #[test]
+    fn capa_con_tiles_sueltos() {
+        let tiles: String = [1, 1, 1, 1, 1, 0, 2, 1, 1, 1, 3, 1].iter()
+            .map(|g| format!(r#"<tile gid="{g}"/>"#))
+            .collect();
+        let capa = format!(r#"<layer name="paredes"><data>{tiles}</data></layer>"#);
+        assert_eq!(filas(&tmx(&mapa_tmx("", &capa, "")).unwrap()), ["1111", "1 C1", "11E1"]);
+    }
+
+    #[test]
+    fn bits_de_giro() {
+        // el mismo tile espejado en horizontal, vertical y diagonal
+        let capa = format!(r#"<layer name="p"><data encoding="csv">{},{},{},1,1,0,0,1,1,1,1,1</data></layer>"#,
+                           0x8000_0001u32, 0x4000_0001u32, 0x2000_0002u32);
+        assert_eq!(filas(&tmx(&mapa_tmx("", &capa, "")).unwrap()), ["11C1", "1  1", "1111"]);
+    }
+
+    #[test]
+    fn objeto_tile_anclado_abajo() {
+        // la esquina (32, 32) es la de abajo: el tile ocupa la celda (2, 1)
+        let obj = r#"<object id="2" gid="2" x="32" y="32" width="16" height="16"/>"#;
+        let n = tmx(&mapa_tmx("", "", obj)).unwrap();
+        assert_eq!(n.mapa[1][2], 'C');
+        assert_eq!(n.mapa[2][2], ' ');
+    }
+
+    #[test]
+    fn disparador() {
+        let obj = r#"<object id="2" class="disparador" x="16" y="16" width="32" height="16">
+   <properties>
+    <property name="acciones">abrir 1 1
+mensaje hola</property>
+    <property name="cuando" value="apretar"/>
+   </properties>
+  </object>"#;
+        let n = tmx(&mapa_tmx("", "", obj)).unwrap();
+        assert_eq!(n.disparadores, [Disparador {
+            x: 1, y: 1, w: 2, h: 1,
+            cuando: Cuando::Apretar,
+            acciones: "abrir 1 1; mensaje hola".to_string(),
+        }]);
+
+        let obj = obj.replace("apretar", "pisar");
+        assert!(error(tmx(&mapa_tmx("", "", &obj))).contains("'cuando' desconocido"));
+    }
+
+    #[test]
+    fn no_soportados_tmx() {
+        let casos = [
+            (mapa_tmx(r#"infinite="1""#, "", ""), "infinitos"),
+            (mapa_tmx("", r#"<layer name="p"><data encoding="base64">AQAAAA==</data></layer>"#, ""), "base64"),
+            (mapa_tmx("", "", r#"<object id="2" template="cosa.tx" x="0" y="0"/>"#), "plantillas"),
+            (mapa_tmx("", "", r#"<object id="2" x="0" y="0"><polygon points="0,0 8,0 0,8"/></object>"#), "polygon"),
+        ];
+        for (txt, esperado) in casos {
+            let e = error(tmx(&txt));
+            assert!(e.contains(esperado), "'{e}' no dice '{esperado}'");
+        }
+        let imagen = CSV.to_string() + r#"<imagelayer name="fondo"><image source="f.png"/></imagelayer>"#;
+        assert!(error(tmx(&mapa_tmx("", &imagen, ""))).contains("capa de imagen 'fondo'"));
+    }
+
+    /// El mismo mapa que `mapa_tmx`, en JSON
+    fn mapa_json(datos: &str, objetos: &str) -> String {
+        format!(r#"{{
+ "type": "map", "orientation": "orthogonal", "infinite": false,
+ "width": 4, "height": 3, "tilewidth": 16, "tileheight": 16,
+ "properties": [{{"name": "reglas", "type": "string", "value": "minimo 1"}}],
+ "tilesets": [{{"firstgid": 1, "name": "t", "tiles": [
+  {{"id": 0, "properties": [{{"name": "textura", "type": "int", "value": 1}}]}},
+  {{"id": 1, "type": "moneda"}},
+  {{"id": 2, "properties": [{{"name": "tipo", "type": "string", "value": "salida"}}]}}
+ ]}}],
+ "layers": [
+  {{"type": "tilelayer", "name": "paredes", {datos}}},
+  {{"type": "objectgroup", "name": "objetos", "objects": [
+   {{"id": 1, "type": "inicio", "x": 24, "y": 24, "point": true}}{objetos}
+  ]}}
+ ]
+}}"#)
+    }
+
+    #[test]
+    fn json() {
+        let datos = r#""data": [1, 1, 1, 1, 1, 0, 2, 1, 1, 1, 2147483651, 1]"#;
+        let objetos = r#", {"id": 2, "gid": 2, "x": 16, "y": 32, "width": 16, "height": 16},
+   {"id": 3, "type": "disparador", "x": 32, "y": 16, "width": 16, "height": 16,
+    "properties": [{"name": "acciones", "type": "string", "value": "ganar"}]}"#;
+        let n = tmj(&mapa_json(datos, objetos)).unwrap();
+        assert_eq!(filas(&n), ["1111", "1CC1", "11E1"]);
+        assert_eq!(n.reglas.len(), 1);
+        assert_eq!(n.disparadores.len(), 1);
+        assert_eq!((n.disparadores[0].x, n.disparadores[0].y, n.disparadores[0].cuando), (2, 1, Cuando::Entrar));
+
+        let csv = r#""encoding": "csv", "data": "1,1,1,1,1,0,2,1,1,1,3,1""#;
+        assert_eq!(filas(&tmj(&mapa_json(csv, "")).unwrap()), ["1111", "1 C1", "11E1"]);
+    }
+
+    #[test]
+    fn no_soportados_json() {
+        let base64 = r#""encoding": "base64", "data": "AQAAAA==""#;
+        assert!(error(tmj(&mapa_json(base64, ""))).contains("base64"));
+        let datos = r#""data": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]"#;
+        let poligono = r#", {"id": 2, "x": 0, "y": 0, "polygon": [{"x": 0, "y": 0}, {"x": 8, "y": 0}]}"#;
+        assert!(error(tmj(&mapa_json(datos, poligono))).contains("polygon"));
+        let plantilla = r#", {"id": 2, "template": "cosa.tj", "x": 0, "y": 0}"#;
+        assert!(error(tmj(&mapa_json(datos, plantilla))).contains("plantillas"));
+        let infinito = mapa_json(datos, "").replace(r#""infinite": false"#, r#""infinite": true"#);
+        assert!(error(tmj(&infinito)).contains("infinitos"));
+        let imagen = mapa_json(datos, "").replace(r#""type": "tilelayer""#, r#""type": "imagelayer""#);
+        assert!(error(tmj(&imagen)).contains("capa de imagen 'paredes'"));
+    }
+}